@@ -0,0 +1,124 @@
+use std::fmt;
+use std::sync::{Condvar, Mutex, MutexGuard};
+use std::time::{Duration, Instant};
+
+use serde::Serialize;
+
+/// Bounds how many tool calls run at once. Calls beyond the limit queue for
+/// a free slot and, when `max_wait` is set, are rejected with [`ServerBusy`]
+/// once they have waited that long. The stdio loop runs one call at a time,
+/// so it never exceeds a limit of one or more; the limit is meant for
+/// transports that run calls concurrently.
+pub struct CallLimiter {
+    max_concurrent: usize,
+    max_wait: Option<Duration>,
+    state: Mutex<LimiterState>,
+    released: Condvar,
+}
+
+#[derive(Debug, Default, Clone, Copy, Serialize)]
+pub struct LimiterState {
+    /// Calls currently running.
+    pub active: usize,
+    /// Calls waiting for a slot.
+    pub queued: usize,
+    /// Calls turned away since startup.
+    pub rejected: u64,
+}
+
+/// Held by a running call; frees its slot when dropped.
+pub struct CallPermit<'a> {
+    limiter: &'a CallLimiter,
+}
+
+/// Returned when no slot freed up within the wait limit.
+#[derive(Debug)]
+pub struct ServerBusy {
+    pub max_concurrent: usize,
+    pub waited: Duration,
+}
+
+impl fmt::Display for ServerBusy {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "Server busy: all {} call slots stayed in use for {} ms",
+            self.max_concurrent,
+            self.waited.as_millis()
+        )
+    }
+}
+
+impl std::error::Error for ServerBusy {}
+
+impl CallLimiter {
+    /// `max_concurrent` is raised to 1 so calls can always make progress.
+    /// `max_wait` of `None` queues excess calls until a slot frees; zero
+    /// rejects them immediately.
+    pub fn new(max_concurrent: usize, max_wait: Option<Duration>) -> Self {
+        Self {
+            max_concurrent: max_concurrent.max(1),
+            max_wait,
+            state: Mutex::new(LimiterState::default()),
+            released: Condvar::new(),
+        }
+    }
+
+    pub fn max_concurrent(&self) -> usize {
+        self.max_concurrent
+    }
+
+    pub fn max_wait(&self) -> Option<Duration> {
+        self.max_wait
+    }
+
+    /// Current counters, as reported by `status`.
+    pub fn snapshot(&self) -> LimiterState {
+        *self.lock()
+    }
+
+    /// Take a slot, waiting for one as configured.
+    pub fn acquire(&self) -> Result<CallPermit<'_>, ServerBusy> {
+        let started = Instant::now();
+        let deadline = self.max_wait.map(|wait| started + wait);
+        let mut state = self.lock();
+        state.queued += 1;
+        while state.active >= self.max_concurrent {
+            state = match deadline {
+                None => self
+                    .released
+                    .wait(state)
+                    .unwrap_or_else(|err| err.into_inner()),
+                Some(deadline) => {
+                    let now = Instant::now();
+                    if now >= deadline {
+                        state.queued -= 1;
+                        state.rejected += 1;
+                        return Err(ServerBusy {
+                            max_concurrent: self.max_concurrent,
+                            waited: now - started,
+                        });
+                    }
+                    self.released
+                        .wait_timeout(state, deadline - now)
+                        .unwrap_or_else(|err| err.into_inner())
+                        .0
+                }
+            };
+        }
+        state.queued -= 1;
+        state.active += 1;
+        Ok(CallPermit { limiter: self })
+    }
+
+    fn lock(&self) -> MutexGuard<'_, LimiterState> {
+        self.state.lock().unwrap_or_else(|err| err.into_inner())
+    }
+}
+
+impl Drop for CallPermit<'_> {
+    fn drop(&mut self) {
+        self.limiter.lock().active -= 1;
+        self.limiter.released.notify_one();
+    }
+}
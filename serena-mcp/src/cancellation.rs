@@ -0,0 +1,92 @@
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::fmt;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex, MutexGuard};
+
+use serde_json::Value;
+
+/// Flag shared between a running request and whoever may cancel it.
+#[derive(Debug, Clone, Default)]
+pub struct CancelToken(Arc<AtomicBool>);
+
+impl CancelToken {
+    pub fn cancel(&self) {
+        self.0.store(true, Ordering::Relaxed);
+    }
+
+    pub fn is_cancelled(&self) -> bool {
+        self.0.load(Ordering::Relaxed)
+    }
+}
+
+/// Returned by [`checkpoint`] once the current request has been cancelled.
+#[derive(Debug)]
+pub struct Cancelled;
+
+impl fmt::Display for Cancelled {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "Request cancelled")
+    }
+}
+
+impl std::error::Error for Cancelled {}
+
+thread_local! {
+    static CURRENT: RefCell<Option<CancelToken>> = const { RefCell::new(None) };
+}
+
+/// Run `f` with `token` as the cancellation flag of work on this thread.
+pub fn with_token<R>(token: CancelToken, f: impl FnOnce() -> R) -> R {
+    let previous = CURRENT.with(|slot| slot.borrow_mut().replace(token));
+    let result = f();
+    CURRENT.with(|slot| *slot.borrow_mut() = previous);
+    result
+}
+
+/// Fail with [`Cancelled`] when the request running on this thread has been
+/// cancelled; a no-op outside a request. Cancellation is cooperative: scans
+/// call this between files and stop there, work between two checkpoints
+/// always completes, and tools without checkpoints cannot be cancelled.
+pub(crate) fn checkpoint() -> Result<(), Cancelled> {
+    let cancelled = CURRENT.with(|slot| {
+        slot.borrow()
+            .as_ref()
+            .is_some_and(CancelToken::is_cancelled)
+    });
+    if cancelled { Err(Cancelled) } else { Ok(()) }
+}
+
+/// Tokens of the requests received but not yet answered, by JSON-RPC id.
+#[derive(Debug, Default)]
+pub struct PendingRequests {
+    tokens: Mutex<HashMap<String, CancelToken>>,
+}
+
+impl PendingRequests {
+    /// Track the request `id` until [`PendingRequests::finish`].
+    pub fn register(&self, id: &Value) -> CancelToken {
+        let token = CancelToken::default();
+        self.lock().insert(id.to_string(), token.clone());
+        token
+    }
+
+    /// Cancel the pending request `id`, returning whether there was one.
+    pub fn cancel(&self, id: &Value) -> bool {
+        match self.lock().get(&id.to_string()) {
+            Some(token) => {
+                token.cancel();
+                true
+            }
+            None => false,
+        }
+    }
+
+    pub fn finish(&self, id: &Value) {
+        self.lock().remove(&id.to_string());
+    }
+
+    fn lock(&self) -> MutexGuard<'_, HashMap<String, CancelToken>> {
+        self.tokens.lock().unwrap_or_else(|err| err.into_inner())
+    }
+}
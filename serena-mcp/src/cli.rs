@@ -1,12 +1,16 @@
 use std::fmt;
 
 use clap::{Parser, ValueEnum};
+use serde_json::{Value, json};
+
+use crate::rpc::DEFAULT_MAX_LINE_BYTES;
 
 /// Command line interface for the Serena MCP server prototype.
 #[derive(Debug, Parser)]
 #[command(name = "serena-mcp", about = "Minimal Serena MCP server prototype")]
 pub struct Cli {
-    /// Optional project path or identifier to activate at startup.
+    /// Project directory to activate at startup. Tools default to it instead
+    /// of the working directory, and relative paths resolve against it.
     #[arg(long)]
     pub project: Option<String>,
 
@@ -21,6 +25,79 @@ pub struct Cli {
     /// Transport selection. For now only `stdio` is implemented but the flag helps keep CLI parity.
     #[arg(long, value_enum, default_value_t = Transport::Stdio)]
     pub transport: Transport,
+
+    /// Log verbosity. When omitted, `RUST_LOG` is honoured before defaulting to `info`.
+    #[arg(long, value_enum)]
+    pub log_level: Option<LogLevel>,
+
+    /// Log output format. `json` emits one object per line on stderr.
+    #[arg(long, value_enum, default_value_t = LogFormat::Text)]
+    pub log_format: LogFormat,
+
+    /// Shrink tool responses larger than this many bytes, dropping trailing
+    /// matches or clipping content, and mark them `response_truncated`.
+    #[arg(long)]
+    pub max_response_bytes: Option<usize>,
+
+    /// Disable every tool that writes files or persistent state. They are
+    /// omitted from `tools.list` and calls to them are rejected.
+    #[arg(long)]
+    pub read_only: bool,
+
+    /// Reject request lines longer than this many bytes with a parse error
+    /// instead of buffering them.
+    #[arg(long, default_value_t = DEFAULT_MAX_LINE_BYTES)]
+    pub max_line_bytes: usize,
+
+    /// Cache responses of pure read-only tools in memory for this many
+    /// seconds. Cacheable: find_symbol, find_referencing_symbols,
+    /// find_duplicate_symbols, get_symbols_overview, get_file_outline,
+    /// local_call_graph, search_pattern, render_tree and language_breakdown.
+    /// Mutating tools drop entries for the paths they touch; the
+    /// `clear_cache` method drops the rest.
+    #[arg(long, value_name = "SECS")]
+    pub result_cache_ttl: Option<u64>,
+
+    /// Restrict the result cache to this tool; repeat for several. Defaults
+    /// to every cacheable tool.
+    #[arg(
+        long = "result-cache-tool",
+        value_name = "TOOL",
+        requires = "result_cache_ttl"
+    )]
+    pub result_cache_tools: Vec<String>,
+
+    /// Run at most this many tool calls at once; further calls queue for a
+    /// free slot. Reported with live counts by the `status` method.
+    #[arg(long, value_name = "N", value_parser = clap::value_parser!(u64).range(1..))]
+    pub max_concurrent_calls: Option<u64>,
+
+    /// Reject a queued call with a "server busy" error after waiting this
+    /// many milliseconds for a slot; 0 rejects excess calls immediately.
+    /// Without it queued calls wait indefinitely.
+    #[arg(long, value_name = "MS", requires = "max_concurrent_calls")]
+    pub call_queue_timeout_ms: Option<u64>,
+}
+
+impl Cli {
+    /// The parsed flags, with defaults applied, as reported by `get_config`.
+    pub fn settings(&self) -> Value {
+        json!({
+            "project": self.project,
+            "context": self.context,
+            "modes": self.modes.iter().map(Mode::to_string).collect::<Vec<_>>(),
+            "transport": self.transport.to_string(),
+            "log_level": self.log_level.map(|level| level.to_string()),
+            "log_format": self.log_format.to_string(),
+            "max_response_bytes": self.max_response_bytes,
+            "read_only": self.read_only,
+            "max_line_bytes": self.max_line_bytes,
+            "result_cache_ttl": self.result_cache_ttl,
+            "result_cache_tools": self.result_cache_tools,
+            "max_concurrent_calls": self.max_concurrent_calls,
+            "call_queue_timeout_ms": self.call_queue_timeout_ms,
+        })
+    }
 }
 
 /// Stub representation of available modes.
@@ -41,6 +118,24 @@ pub enum Transport {
     StreamableHttp,
 }
 
+/// Log verbosity levels accepted on the command line.
+#[derive(Debug, Clone, Copy, ValueEnum, PartialEq, Eq)]
+pub enum LogLevel {
+    Off,
+    Error,
+    Warn,
+    Info,
+    Debug,
+    Trace,
+}
+
+/// Output formats for server logs.
+#[derive(Debug, Clone, Copy, ValueEnum, PartialEq, Eq)]
+pub enum LogFormat {
+    Text,
+    Json,
+}
+
 impl fmt::Display for Mode {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         let value = match self {
@@ -62,3 +157,27 @@ impl fmt::Display for Transport {
         write!(f, "{value}")
     }
 }
+
+impl fmt::Display for LogLevel {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let value = match self {
+            LogLevel::Off => "off",
+            LogLevel::Error => "error",
+            LogLevel::Warn => "warn",
+            LogLevel::Info => "info",
+            LogLevel::Debug => "debug",
+            LogLevel::Trace => "trace",
+        };
+        write!(f, "{value}")
+    }
+}
+
+impl fmt::Display for LogFormat {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let value = match self {
+            LogFormat::Text => "text",
+            LogFormat::Json => "json",
+        };
+        write!(f, "{value}")
+    }
+}
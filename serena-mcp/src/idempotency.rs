@@ -0,0 +1,123 @@
+use std::collections::HashMap;
+use std::fs;
+use std::process;
+use std::sync::Mutex;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+use crate::result_cache::canonical;
+use crate::tools::{state_file, writable_state_file};
+
+/// Optional argument accepted by every mutating tool. A repeated key replays
+/// the recorded response instead of running the mutation again.
+pub const IDEMPOTENCY_KEY: &str = "idempotency_key";
+
+/// How long a recorded response stays eligible for replay.
+pub const IDEMPOTENCY_WINDOW_SECS: u64 = 600;
+
+const STORE_FILE: &str = "idempotency.json";
+
+/// Serialises read-modify-write cycles on the store within this process.
+static STORE_LOCK: Mutex<()> = Mutex::new(());
+
+#[derive(Debug, Serialize, Deserialize)]
+struct RecordedCall {
+    tool: String,
+    /// [`fingerprint`] of the arguments the response was recorded for.
+    #[serde(default)]
+    arguments: String,
+    response: Value,
+    recorded_at: u64,
+}
+
+/// Stable hash of `params` (without the idempotency key), independent of
+/// argument order, used to detect a key reused for a different call.
+pub(crate) fn fingerprint(params: &Value) -> String {
+    // FNV-1a: stable across builds, unlike `DefaultHasher`.
+    let hash = canonical(params.clone())
+        .to_string()
+        .bytes()
+        .fold(0xcbf2_9ce4_8422_2325_u64, |hash, byte| {
+            (hash ^ u64::from(byte)).wrapping_mul(0x0000_0100_0000_01b3)
+        });
+    format!("{hash:016x}")
+}
+
+/// Response previously recorded for `key` on `tool`, if still inside the
+/// replay window. Fails when the key was recorded for different arguments.
+pub(crate) fn lookup(tool: &str, key: &str, arguments: &str) -> Result<Option<Value>> {
+    let _guard = STORE_LOCK
+        .lock()
+        .unwrap_or_else(|poisoned| poisoned.into_inner());
+    let Some(call) = load()?.remove(&store_key(tool, key)) else {
+        return Ok(None);
+    };
+    if !call.arguments.is_empty() && call.arguments != arguments {
+        anyhow::bail!("Idempotency key {key} was already used for {tool} with different arguments");
+    }
+    Ok(Some(call.response))
+}
+
+/// Remember `response` for `key` on `tool`, dropping expired entries.
+pub(crate) fn record(tool: &str, key: &str, arguments: &str, response: &Value) -> Result<()> {
+    let _guard = STORE_LOCK
+        .lock()
+        .unwrap_or_else(|poisoned| poisoned.into_inner());
+    let mut calls = load()?;
+    calls.insert(
+        store_key(tool, key),
+        RecordedCall {
+            tool: tool.to_string(),
+            arguments: arguments.to_string(),
+            response: response.clone(),
+            recorded_at: now_secs(),
+        },
+    );
+
+    // Write a sibling file and rename it over the store so readers never
+    // observe a partially written store.
+    let path = writable_state_file(STORE_FILE)?;
+    let temp = path.with_extension(format!("json.{}.tmp", process::id()));
+    let payload = serde_json::to_vec(&calls).context("Failed to serialise idempotency store")?;
+    fs::write(&temp, payload)
+        .with_context(|| format!("Failed to write idempotency store to {}", temp.display()))?;
+    if let Err(err) = fs::rename(&temp, &path) {
+        let _ = fs::remove_file(&temp);
+        return Err(err)
+            .with_context(|| format!("Failed to replace idempotency store at {}", path.display()));
+    }
+    Ok(())
+}
+
+fn load() -> Result<HashMap<String, RecordedCall>> {
+    let path = state_file(STORE_FILE)?;
+    if !path.exists() {
+        return Ok(HashMap::new());
+    }
+
+    let bytes = fs::read(&path)
+        .with_context(|| format!("Failed to read idempotency store at {}", path.display()))?;
+    if bytes.is_empty() {
+        return Ok(HashMap::new());
+    }
+
+    let mut calls: HashMap<String, RecordedCall> = serde_json::from_slice(&bytes)
+        .with_context(|| format!("Failed to parse idempotency store at {}", path.display()))?;
+    let cutoff = now_secs().saturating_sub(IDEMPOTENCY_WINDOW_SECS);
+    calls.retain(|_, call| call.recorded_at >= cutoff);
+    Ok(calls)
+}
+
+fn store_key(tool: &str, key: &str) -> String {
+    format!("{tool}:{key}")
+}
+
+fn now_secs() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|elapsed| elapsed.as_secs())
+        .unwrap_or_default()
+}
@@ -1,4 +1,14 @@
+pub mod call_limit;
+pub mod cancellation;
 pub mod cli;
+pub mod idempotency;
+pub mod logging;
+pub mod metrics;
+pub mod notifications;
+pub mod path_style;
+pub mod response_limit;
+pub mod result_cache;
 pub mod rpc;
+pub mod suggest;
 pub mod tool;
 pub mod tools;
@@ -0,0 +1,98 @@
+use std::env;
+use std::io::{self, Write};
+use std::str::FromStr;
+
+use log::{LevelFilter, Log, Metadata, Record};
+use serde_json::json;
+use simplelog::{ConfigBuilder, SimpleLogger};
+use time::{OffsetDateTime, format_description::well_known::Rfc3339};
+
+use crate::cli::{LogFormat, LogLevel};
+
+/// Install the global logger.
+///
+/// An explicit `--log-level` wins; otherwise `RUST_LOG` is consulted before
+/// falling back to `info`.
+pub fn init(level: Option<LogLevel>, format: LogFormat) {
+    let filter = level
+        .map(LevelFilter::from)
+        .or_else(level_from_env)
+        .unwrap_or(LevelFilter::Info);
+
+    match format {
+        LogFormat::Text => {
+            let config = ConfigBuilder::new()
+                .set_time_level(LevelFilter::Off)
+                .set_location_level(LevelFilter::Off)
+                .build();
+            let _ = SimpleLogger::init(filter, config);
+        }
+        LogFormat::Json => {
+            if log::set_boxed_logger(Box::new(JsonLogger { level: filter })).is_ok() {
+                log::set_max_level(filter);
+            }
+        }
+    }
+}
+
+/// Derive a level from `RUST_LOG`, accepting either a bare level (`debug`) or
+/// `env_logger`-style directives (`serena_mcp=debug,info`). The most verbose
+/// level mentioned wins since directives are not scoped per module here.
+fn level_from_env() -> Option<LevelFilter> {
+    let value = env::var("RUST_LOG").ok()?;
+    value
+        .split(',')
+        .filter_map(|directive| {
+            let level = directive.rsplit('=').next()?.trim();
+            LevelFilter::from_str(level).ok()
+        })
+        .max()
+}
+
+impl From<LogLevel> for LevelFilter {
+    fn from(level: LogLevel) -> Self {
+        match level {
+            LogLevel::Off => LevelFilter::Off,
+            LogLevel::Error => LevelFilter::Error,
+            LogLevel::Warn => LevelFilter::Warn,
+            LogLevel::Info => LevelFilter::Info,
+            LogLevel::Debug => LevelFilter::Debug,
+            LogLevel::Trace => LevelFilter::Trace,
+        }
+    }
+}
+
+/// Emits one JSON object per record on stderr so stdout stays reserved for
+/// the JSON-RPC stream.
+struct JsonLogger {
+    level: LevelFilter,
+}
+
+impl Log for JsonLogger {
+    fn enabled(&self, metadata: &Metadata<'_>) -> bool {
+        metadata.level() <= self.level
+    }
+
+    fn log(&self, record: &Record<'_>) {
+        if !self.enabled(record.metadata()) {
+            return;
+        }
+
+        let timestamp = OffsetDateTime::now_utc()
+            .format(&Rfc3339)
+            .unwrap_or_else(|_| "1970-01-01T00:00:00Z".to_string());
+        let line = json!({
+            "timestamp": timestamp,
+            "level": record.level().as_str(),
+            "target": record.target(),
+            "message": record.args().to_string(),
+        });
+
+        let mut stderr = io::stderr().lock();
+        let _ = writeln!(stderr, "{line}");
+    }
+
+    fn flush(&self) {
+        let _ = io::stderr().flush();
+    }
+}
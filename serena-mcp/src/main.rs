@@ -1,10 +1,14 @@
+use std::time::Duration;
+
 use anyhow::Result;
 use clap::Parser;
 use log::{error, info};
-use simplelog::{ConfigBuilder, LevelFilter, SimpleLogger};
 
 use serena_mcp::{
+    call_limit::CallLimiter,
     cli::{Cli, Transport},
+    logging,
+    result_cache::ResultCache,
     rpc, tools,
 };
 
@@ -17,7 +21,7 @@ fn main() {
 
 fn run() -> Result<()> {
     let cli = Cli::parse();
-    init_logging();
+    logging::init(cli.log_level, cli.log_format);
 
     info!(
         "Starting Serena MCP prototype | context={} transport={:?} project={:?}",
@@ -28,14 +32,23 @@ fn run() -> Result<()> {
         anyhow::bail!("Only stdio transport is implemented in the Rust prototype");
     }
 
-    let registry = tools::build_registry();
-    rpc::run_stdio_server(&registry)
-}
-
-fn init_logging() {
-    let config = ConfigBuilder::new()
-        .set_time_level(LevelFilter::Off)
-        .set_location_level(LevelFilter::Off)
-        .build();
-    let _ = SimpleLogger::init(LevelFilter::Info, config);
+    let mut registry = tools::build_registry();
+    registry.set_max_response_bytes(cli.max_response_bytes);
+    registry.set_read_only(cli.read_only);
+    registry.set_launch_settings(cli.settings());
+    if let Some(ttl) = cli.result_cache_ttl {
+        let tools = (!cli.result_cache_tools.is_empty())
+            .then(|| cli.result_cache_tools.iter().cloned().collect());
+        registry.set_result_cache(Some(ResultCache::new(Duration::from_secs(ttl), tools)))?;
+    }
+    if let Some(max) = cli.max_concurrent_calls {
+        let max_wait = cli.call_queue_timeout_ms.map(Duration::from_millis);
+        registry.set_call_limit(Some(CallLimiter::new(max as usize, max_wait)));
+    }
+    if let Some(project) = &cli.project {
+        let root = tools::project_root(project)?;
+        info!("Activated project {}", root.display());
+        registry.set_project_root(Some(root));
+    }
+    rpc::run_stdio_server(&registry, cli.max_line_bytes)
 }
@@ -0,0 +1,108 @@
+use std::collections::HashMap;
+use std::sync::{Mutex, MutexGuard};
+use std::time::{Duration, Instant};
+
+use serde_json::{Value, json};
+use time::{OffsetDateTime, format_description::well_known::Rfc3339};
+
+/// Per-tool call counts, failures and durations since startup or the last
+/// reset, as reported by the `metrics` method.
+pub struct Metrics {
+    window: Mutex<Window>,
+}
+
+struct Window {
+    started: Instant,
+    started_at: OffsetDateTime,
+    tools: HashMap<String, ToolMetrics>,
+}
+
+#[derive(Default)]
+struct ToolMetrics {
+    calls: u64,
+    errors: u64,
+    total: Duration,
+    max: Duration,
+}
+
+impl Default for Metrics {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Metrics {
+    pub fn new() -> Self {
+        Self {
+            window: Mutex::new(Window::new()),
+        }
+    }
+
+    /// Count one call to `tool` that took `elapsed`.
+    pub(crate) fn record(&self, tool: &str, elapsed: Duration, failed: bool) {
+        let mut window = self.lock();
+        let metrics = window.tools.entry(tool.to_string()).or_default();
+        metrics.calls += 1;
+        metrics.errors += u64::from(failed);
+        metrics.total += elapsed;
+        metrics.max = metrics.max.max(elapsed);
+    }
+
+    /// Totals and the per-tool breakdown, busiest tool first. With `reset`
+    /// the counters start over afterwards, so successive reads cover
+    /// consecutive windows.
+    pub fn snapshot(&self, reset: bool) -> Value {
+        let mut window = self.lock();
+        let mut tools = window.tools.iter().collect::<Vec<_>>();
+        tools.sort_by(|(a_name, a), (b_name, b)| b.calls.cmp(&a.calls).then(a_name.cmp(b_name)));
+        let millis = |duration: Duration| duration.as_secs_f64() * 1000.0;
+        let breakdown = tools
+            .iter()
+            .map(|(name, metrics)| {
+                json!({
+                    "tool": name,
+                    "calls": metrics.calls,
+                    "errors": metrics.errors,
+                    "total_ms": millis(metrics.total),
+                    "avg_ms": millis(metrics.total) / metrics.calls.max(1) as f64,
+                    "max_ms": millis(metrics.max),
+                })
+            })
+            .collect::<Vec<_>>();
+
+        let snapshot = json!({
+            "since": window
+                .started_at
+                .format(&Rfc3339)
+                .unwrap_or_else(|_| "1970-01-01T00:00:00Z".to_string()),
+            "window_secs": window.started.elapsed().as_secs(),
+            "calls": tools.iter().map(|(_, metrics)| metrics.calls).sum::<u64>(),
+            "errors": tools.iter().map(|(_, metrics)| metrics.errors).sum::<u64>(),
+            "tools": breakdown,
+            "reset": reset,
+        });
+        if reset {
+            *window = Window::new();
+        }
+        snapshot
+    }
+
+    /// Start a new window, discarding the counters.
+    pub fn reset(&self) {
+        *self.lock() = Window::new();
+    }
+
+    fn lock(&self) -> MutexGuard<'_, Window> {
+        self.window.lock().unwrap_or_else(|err| err.into_inner())
+    }
+}
+
+impl Window {
+    fn new() -> Self {
+        Self {
+            started: Instant::now(),
+            started_at: OffsetDateTime::now_utc(),
+            tools: HashMap::new(),
+        }
+    }
+}
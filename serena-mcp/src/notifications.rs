@@ -0,0 +1,138 @@
+use std::cell::RefCell;
+use std::sync::Arc;
+
+use serde_json::{Value, json};
+
+/// Receives server-initiated notifications (`method`, `params`) emitted while
+/// a tool runs. Transports that can interleave messages with the response
+/// (SSE/HTTP) install one around each call; plain stdio request/response does
+/// not, which turns every notification into a no-op.
+pub type NotificationSink = Arc<dyn Fn(&str, Value) + Send + Sync>;
+
+/// Files scanned between two `progress` notifications.
+pub const DEFAULT_PROGRESS_INTERVAL: usize = 250;
+
+thread_local! {
+    static SINK: RefCell<Option<(NotificationSink, usize)>> = const { RefCell::new(None) };
+}
+
+/// Run `f` with `sink` receiving notifications emitted on this thread.
+/// `progress_interval` controls how many files pass between progress events.
+pub fn with_sink<R>(sink: NotificationSink, progress_interval: usize, f: impl FnOnce() -> R) -> R {
+    let previous = SINK.with(|slot| slot.borrow_mut().replace((sink, progress_interval.max(1))));
+    let result = f();
+    SINK.with(|slot| *slot.borrow_mut() = previous);
+    result
+}
+
+/// Emit a notification to the active sink, if any.
+pub fn notify(method: &str, params: Value) {
+    let sink = SINK.with(|slot| slot.borrow().as_ref().map(|(sink, _)| sink.clone()));
+    if let Some(sink) = sink {
+        sink(method, params);
+    }
+}
+
+fn progress_interval() -> Option<usize> {
+    SINK.with(|slot| slot.borrow().as_ref().map(|(_, interval)| *interval))
+}
+
+/// Counts scanned files for a long-running operation and periodically emits
+/// `progress` notifications of the form `{ operation, scanned, matches }`,
+/// leaving out `matches` for operations that do not count any.
+pub(crate) struct ProgressReporter {
+    operation: &'static str,
+    interval: Option<usize>,
+    scanned: usize,
+}
+
+impl ProgressReporter {
+    pub(crate) fn new(operation: &'static str) -> Self {
+        Self {
+            operation,
+            interval: progress_interval(),
+            scanned: 0,
+        }
+    }
+
+    /// Record one scanned file; `matches` is the running result count.
+    pub(crate) fn file_scanned(&mut self, matches: usize) {
+        self.record(Some(matches));
+    }
+
+    /// Record one scanned file for an operation without a result count.
+    pub(crate) fn file_visited(&mut self) {
+        self.record(None);
+    }
+
+    fn record(&mut self, matches: Option<usize>) {
+        self.scanned += 1;
+        if let Some(interval) = self.interval
+            && self.scanned.is_multiple_of(interval)
+        {
+            self.emit(matches);
+        }
+    }
+
+    fn emit(&self, matches: Option<usize>) {
+        let mut params = json!({
+            "operation": self.operation,
+            "scanned": self.scanned,
+        });
+        if let Some(matches) = matches {
+            params["matches"] = json!(matches);
+        }
+        notify("progress", params);
+    }
+}
+
+/// Sends a tool's results as `partial_result` notifications of the form
+/// `{ tool, index, item }` while they are found, for calls that asked to
+/// `stream` on a transport that delivers notifications. Otherwise it stays
+/// inactive and the results are returned in the response as usual.
+pub(crate) struct ResultStream {
+    tool: &'static str,
+    active: bool,
+    sent: usize,
+}
+
+impl ResultStream {
+    pub(crate) fn new(tool: &'static str, requested: bool) -> Self {
+        Self {
+            tool,
+            active: requested && progress_interval().is_some(),
+            sent: 0,
+        }
+    }
+
+    /// Emit `item` when streaming; a no-op otherwise.
+    pub(crate) fn send(&mut self, item: Value) {
+        if !self.active {
+            return;
+        }
+        notify(
+            "partial_result",
+            json!({
+                "tool": self.tool,
+                "index": self.sent,
+                "item": item,
+            }),
+        );
+        self.sent += 1;
+    }
+
+    /// Turn `response` into the closing summary of a streamed call: the
+    /// `fields` that held the results are dropped, since every item was
+    /// already sent, and `streamed` counts them. Untouched when inactive.
+    pub(crate) fn finish(&self, response: &mut Value, fields: &[&str]) {
+        if !self.active {
+            return;
+        }
+        if let Some(object) = response.as_object_mut() {
+            for field in fields {
+                object.remove(*field);
+            }
+        }
+        response["streamed"] = json!(self.sent);
+    }
+}
@@ -0,0 +1,78 @@
+use std::cell::Cell;
+use std::path::Path;
+
+use serde::Deserialize;
+
+use crate::tools::base_dir;
+
+/// Optional argument accepted by every tool selecting how file paths in the
+/// response are written. Omitting it keeps each tool's historical mix of
+/// absolute and relative paths; that default is deprecated and clients should
+/// pass an explicit style.
+pub const PATH_STYLE_KEY: &str = "path_style";
+
+/// Path convention for tool responses.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum PathStyle {
+    /// Absolute paths everywhere.
+    Absolute,
+    /// Paths relative to the call's `cwd` (the server's working directory
+    /// by default), the same root relative tool arguments resolve against.
+    /// Paths outside it stay absolute.
+    RelativeToRoot,
+}
+
+thread_local! {
+    static CURRENT: Cell<Option<PathStyle>> = const { Cell::new(None) };
+}
+
+/// Restores the previous style when dropped.
+pub(crate) struct StyleScope {
+    previous: Option<PathStyle>,
+}
+
+impl Drop for StyleScope {
+    fn drop(&mut self) {
+        CURRENT.with(|current| current.set(self.previous));
+    }
+}
+
+/// Apply `style` to paths rendered on this thread until the scope is dropped.
+pub(crate) fn scoped(style: Option<PathStyle>) -> StyleScope {
+    StyleScope {
+        previous: CURRENT.with(|current| current.replace(style)),
+    }
+}
+
+/// Render a path that the tool historically reported as absolute.
+pub(crate) fn display_path(path: &Path) -> String {
+    match CURRENT.with(Cell::get) {
+        Some(PathStyle::RelativeToRoot) => relative_to_root(path),
+        Some(PathStyle::Absolute) | None => path.to_string_lossy().to_string(),
+    }
+}
+
+/// Render a path that the tool historically reported relative to `base`.
+pub(crate) fn display_path_from(path: &Path, base: &Path) -> String {
+    match CURRENT.with(Cell::get) {
+        Some(PathStyle::Absolute) => path.to_string_lossy().to_string(),
+        Some(PathStyle::RelativeToRoot) => relative_to_root(path),
+        None => path
+            .strip_prefix(base)
+            .unwrap_or(path)
+            .to_string_lossy()
+            .to_string(),
+    }
+}
+
+fn relative_to_root(path: &Path) -> String {
+    let Ok(root) = base_dir() else {
+        return path.to_string_lossy().to_string();
+    };
+    match path.strip_prefix(&root) {
+        Ok(relative) if relative.as_os_str().is_empty() => ".".to_string(),
+        Ok(relative) => relative.to_string_lossy().to_string(),
+        Err(_) => path.to_string_lossy().to_string(),
+    }
+}
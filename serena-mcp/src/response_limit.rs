@@ -0,0 +1,100 @@
+use serde_json::{Value, json};
+
+/// Bytes kept free for the `response_truncated`/`original_bytes` markers.
+const MARKER_RESERVE: usize = 64;
+
+/// Upper bound on shrink passes so pathological payloads cannot spin.
+const MAX_PASSES: usize = 64;
+
+/// Shrink `response` until its serialised form fits in `limit` bytes by
+/// dropping trailing items from the largest array or clipping the largest
+/// string, whichever dominates. Responses that had to be shrunk gain
+/// `response_truncated: true` and `original_bytes`; anything but an object
+/// is first wrapped as `{ result }` to carry them.
+pub(crate) fn enforce(response: &mut Value, limit: usize) {
+    let original = serialized_len(response);
+    if original <= limit {
+        return;
+    }
+    if !response.is_object() {
+        *response = json!({"result": response.take()});
+    }
+
+    let budget = limit.saturating_sub(MARKER_RESERVE);
+    for _ in 0..MAX_PASSES {
+        let size = serialized_len(response);
+        if size <= budget {
+            break;
+        }
+        let Some(pointer) = largest_member(response, String::new()).map(|(_, pointer)| pointer)
+        else {
+            break;
+        };
+        let Some(target) = response.pointer_mut(&pointer) else {
+            break;
+        };
+        if !shrink(target, size - budget) {
+            break;
+        }
+    }
+
+    response["response_truncated"] = json!(true);
+    response["original_bytes"] = json!(original);
+}
+
+fn serialized_len(value: &Value) -> usize {
+    serde_json::to_vec(value).map_or(0, |bytes| bytes.len())
+}
+
+/// Serialised size and JSON pointer of the largest shrinkable array or
+/// string inside `value`. Arrays are not descended into: whole trailing items
+/// are dropped before their contents are touched.
+fn largest_member(value: &Value, pointer: String) -> Option<(usize, String)> {
+    match value {
+        Value::Array(items) if !items.is_empty() => Some((serialized_len(value), pointer)),
+        Value::String(text) if !text.is_empty() => Some((serialized_len(value), pointer)),
+        Value::Object(map) => map
+            .iter()
+            .filter_map(|(key, item)| {
+                let key = key.replace('~', "~0").replace('/', "~1");
+                largest_member(item, format!("{pointer}/{key}"))
+            })
+            .max_by_key(|(size, _)| *size),
+        _ => None,
+    }
+}
+
+/// Remove roughly `excess` serialised bytes from `value`.
+fn shrink(value: &mut Value, excess: usize) -> bool {
+    match value {
+        Value::Array(items) => {
+            let mut removed = 0;
+            while removed < excess {
+                let Some(item) = items.pop() else {
+                    break;
+                };
+                removed += serialized_len(&item) + 1;
+            }
+            removed > 0
+        }
+        Value::String(text) => {
+            // Escapes make the serialised form longer than the text, so
+            // scale the cut instead of subtracting `excess` directly.
+            let serialized = text.len() + 2 + text.chars().filter(|c| needs_escape(*c)).count();
+            let target = serialized.saturating_sub(excess + '…'.len_utf8());
+            let keep = text.len() * target / serialized;
+            let mut cut = keep;
+            while !text.is_char_boundary(cut) {
+                cut -= 1;
+            }
+            text.truncate(cut);
+            text.push('…');
+            true
+        }
+        _ => false,
+    }
+}
+
+fn needs_escape(c: char) -> bool {
+    matches!(c, '"' | '\\') || c.is_control()
+}
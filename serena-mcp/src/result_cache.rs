@@ -0,0 +1,219 @@
+use std::collections::{HashMap, HashSet};
+use std::path::PathBuf;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+use serde_json::Value;
+
+use crate::path_style::PathStyle;
+use crate::tool::Tool;
+use crate::tools::{base_dir, resolve_path};
+
+/// Entries kept at once; the oldest is evicted to make room.
+const MAX_ENTRIES: usize = 256;
+
+/// Arguments that ask a tool to recompute; a call setting one to `true`
+/// skips the lookup (its fresh result still replaces the cached one).
+/// `stream` is one because a cached summary would send no results.
+const BYPASS_KEYS: &[&str] = &["refresh", "no_cache", "stream"];
+
+/// Path arguments (one path or a list) a mutating call may write under.
+/// `root` without a value means the base directory.
+const MUTATED_PATH_KEYS: &[&str] = &["path", "paths", "from", "to", "root"];
+
+/// In-memory cache of responses from read-only tools marked
+/// [`Tool::cacheable`], keyed on the tool name, its canonicalised arguments,
+/// the directory relative paths resolve against and the requested path
+/// style. Entries expire after the TTL and are dropped when a mutating call
+/// touches a path inside (or containing) the directory they describe.
+pub struct ResultCache {
+    ttl: Duration,
+    /// Tools allowed to use the cache; every cacheable tool when `None`.
+    tools: Option<HashSet<String>>,
+    entries: Mutex<HashMap<String, CachedResult>>,
+}
+
+struct CachedResult {
+    tool: String,
+    /// Directories or files the response describes.
+    scope: Vec<PathBuf>,
+    stored_at: Instant,
+    response: Value,
+}
+
+impl ResultCache {
+    pub fn new(ttl: Duration, tools: Option<HashSet<String>>) -> Self {
+        Self {
+            ttl,
+            tools,
+            entries: Mutex::new(HashMap::new()),
+        }
+    }
+
+    pub fn ttl(&self) -> Duration {
+        self.ttl
+    }
+
+    pub fn tools(&self) -> Option<&HashSet<String>> {
+        self.tools.as_ref()
+    }
+
+    pub(crate) fn applies_to(&self, tool: &Tool) -> bool {
+        tool.is_cacheable()
+            && self
+                .tools
+                .as_ref()
+                .is_none_or(|tools| tools.contains(tool.name()))
+    }
+
+    /// Unexpired response stored under `key`.
+    pub(crate) fn get(&self, key: &str) -> Option<Value> {
+        let mut entries = self.entries.lock().unwrap_or_else(|err| err.into_inner());
+        match entries.get(key) {
+            Some(entry) if entry.stored_at.elapsed() < self.ttl => Some(entry.response.clone()),
+            Some(_) => {
+                entries.remove(key);
+                None
+            }
+            None => None,
+        }
+    }
+
+    pub(crate) fn insert(&self, key: String, tool: &str, scope: Vec<PathBuf>, response: Value) {
+        let mut entries = self.entries.lock().unwrap_or_else(|err| err.into_inner());
+        let ttl = self.ttl;
+        entries.retain(|_, entry| entry.stored_at.elapsed() < ttl);
+        if entries.len() >= MAX_ENTRIES
+            && let Some(oldest) = entries
+                .iter()
+                .min_by_key(|(_, entry)| entry.stored_at)
+                .map(|(key, _)| key.clone())
+        {
+            entries.remove(&oldest);
+        }
+        entries.insert(
+            key,
+            CachedResult {
+                tool: tool.to_string(),
+                scope,
+                stored_at: Instant::now(),
+                response,
+            },
+        );
+    }
+
+    /// Drop entries with a scope that contains, or lies inside, one of
+    /// `paths`; every entry when `paths` is empty. Returns how many were
+    /// dropped.
+    pub(crate) fn invalidate(&self, paths: &[PathBuf]) -> usize {
+        let mut entries = self.entries.lock().unwrap_or_else(|err| err.into_inner());
+        let before = entries.len();
+        entries.retain(|_, entry| {
+            !paths.is_empty()
+                && !paths.iter().any(|path| {
+                    entry
+                        .scope
+                        .iter()
+                        .any(|scope| path.starts_with(scope) || scope.starts_with(path))
+                })
+        });
+        before - entries.len()
+    }
+
+    /// Drop the entries of `tool`, or all entries, returning how many.
+    pub fn clear(&self, tool: Option<&str>) -> usize {
+        let mut entries = self.entries.lock().unwrap_or_else(|err| err.into_inner());
+        let before = entries.len();
+        entries.retain(|_, entry| tool.is_some_and(|tool| entry.tool != tool));
+        before - entries.len()
+    }
+
+    pub fn len(&self) -> usize {
+        self.entries
+            .lock()
+            .unwrap_or_else(|err| err.into_inner())
+            .len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+}
+
+/// Cache key for calling `tool` with `params` (already stripped of `cwd`
+/// and `path_style`) under the current base directory and `path_style`.
+pub(crate) fn key(tool: &str, params: &Value, path_style: Option<PathStyle>) -> String {
+    let base = base_dir()
+        .map(|dir| dir.to_string_lossy().to_string())
+        .unwrap_or_default();
+    format!(
+        "{tool}\0{base}\0{path_style:?}\0{}",
+        canonical(params.clone())
+    )
+}
+
+/// Whether `params` asks the tool to recompute rather than reuse results.
+pub(crate) fn bypasses(params: &Value) -> bool {
+    BYPASS_KEYS
+        .iter()
+        .any(|key| params.get(key).and_then(Value::as_bool) == Some(true))
+}
+
+/// What a cacheable call describes: the roots named by its `path` (one or a
+/// list) or `project_root` argument, otherwise the base directory.
+pub(crate) fn scope(params: &Value) -> Vec<PathBuf> {
+    let named = ["path", "project_root"]
+        .iter()
+        .filter_map(|key| params.get(key))
+        .flat_map(|value| match value {
+            Value::Array(items) => items.iter().filter_map(Value::as_str).collect(),
+            value => value.as_str().into_iter().collect::<Vec<_>>(),
+        })
+        .filter_map(|path| resolve_path(path).ok())
+        .collect::<Vec<_>>();
+    if named.is_empty() {
+        base_dir().into_iter().collect()
+    } else {
+        named
+    }
+}
+
+/// Paths a mutating call to `tool` may write under. Empty when it takes no
+/// path arguments, so its success clears the whole cache.
+pub(crate) fn mutated_paths(tool: &Tool, params: &Value) -> Vec<PathBuf> {
+    let mut paths = MUTATED_PATH_KEYS
+        .iter()
+        .filter_map(|key| params.get(key))
+        .flat_map(|value| match value {
+            Value::Array(items) => items.iter().filter_map(Value::as_str).collect(),
+            value => value.as_str().into_iter().collect::<Vec<_>>(),
+        })
+        .filter_map(|path| resolve_path(path).ok())
+        .collect::<Vec<_>>();
+    if params.get("root").is_none()
+        && tool.declares_argument("root")
+        && let Ok(base) = base_dir()
+    {
+        paths.push(base);
+    }
+    paths
+}
+
+/// `value` with object members sorted by name at every level, so argument
+/// order does not change the key.
+pub(crate) fn canonical(value: Value) -> Value {
+    match value {
+        Value::Object(object) => {
+            let mut members = object.into_iter().collect::<Vec<_>>();
+            members.sort_by(|(a, _), (b, _)| a.cmp(b));
+            Value::Object(
+                members
+                    .into_iter()
+                    .map(|(name, member)| (name, canonical(member)))
+                    .collect(),
+            )
+        }
+        Value::Array(items) => Value::Array(items.into_iter().map(canonical).collect()),
+        other => other,
+    }
+}
@@ -1,45 +1,371 @@
+use std::env;
+use std::fs;
 use std::io::{self, BufRead, Write};
+use std::sync::{Arc, mpsc};
+use std::thread;
+use std::time::Instant;
 
 use anyhow::{Context, Result};
-use log::{debug, error, info};
+use log::{debug, error, info, warn};
+use once_cell::sync::Lazy;
 use serde::{Deserialize, Serialize};
 use serde_json::{Value, json};
 
-use crate::tool::ToolRegistry;
+use crate::call_limit::ServerBusy;
+use crate::cancellation::{self, CancelToken, Cancelled, PendingRequests};
+use crate::notifications::{self, DEFAULT_PROGRESS_INTERVAL, NotificationSink};
+use crate::suggest::suggest_tools;
+use crate::tool::{InvalidArguments, ToolRegistry};
+use crate::tools::{memory_layout, state_dir, state_dir_source, writable_state_dir};
+
+/// When the server started serving requests; reported as uptime by `status`.
+static STARTED: Lazy<Instant> = Lazy::new(Instant::now);
+
+/// Number of tools `tools.suggest` returns when no `limit` is given.
+const DEFAULT_SUGGESTIONS: usize = 5;
+
+/// Default cap on a single request line, see [`serve`].
+pub const DEFAULT_MAX_LINE_BYTES: usize = 64 * 1024 * 1024;
 
 /// Run a minimal JSON-RPC 2.0 loop over stdio.
-pub fn run_stdio_server(registry: &ToolRegistry) -> Result<()> {
+pub fn run_stdio_server(registry: &ToolRegistry, max_line_bytes: usize) -> Result<()> {
     info!("Starting stdio JSON-RPC loop");
-    let stdin = io::stdin();
-    let mut stdout = io::stdout();
+    serve(
+        registry,
+        io::BufReader::new(io::stdin()),
+        io::stdout(),
+        max_line_bytes,
+    )?;
+    info!("Stdio loop terminated");
+    Ok(())
+}
+
+/// A line read by the request reader, in arrival order.
+enum Incoming {
+    Request(JsonRpcRequest, Option<CancelToken>),
+    /// Answer prepared by the reader itself, e.g. for a parse error.
+    Reply(JsonRpcResponse),
+}
+
+/// Serve newline-delimited JSON-RPC requests from `reader` until end of
+/// input. Lines longer than `max_line_bytes` are answered with a parse error
+/// and skipped without being buffered in full.
+///
+/// Requests are read on a separate thread so that a `cancel` notification
+/// (`{"method": "cancel", "params": {"id": ...}}`, also accepted as
+/// `$/cancelRequest`) takes effect while an earlier request is still
+/// running. Cancellation is cooperative: the request stops at its tool's
+/// next checkpoint, between files of a scan, and is answered with a
+/// "Request cancelled" error; tools without checkpoints finish normally.
+///
+/// A `tools.call` whose arguments set `stream: true` has its notifications
+/// (`partial_result` items and `progress`) written as they are emitted, one
+/// JSON-RPC notification per line carrying the call's `request_id`, before
+/// the response that closes the call.
+pub fn serve(
+    registry: &ToolRegistry,
+    reader: impl BufRead + Send + 'static,
+    stdout: impl Write + Send,
+    max_line_bytes: usize,
+) -> Result<()> {
+    Lazy::force(&STARTED);
+    let pending = Arc::new(PendingRequests::default());
+    let (sender, receiver) = mpsc::channel();
+    let reader_thread = {
+        let pending = Arc::clone(&pending);
+        thread::spawn(move || read_requests(reader, &sender, &pending, max_line_bytes))
+    };
+
+    // Lines are written on their own thread so notifications of a running
+    // call go out while it is still running.
+    let written = thread::scope(|scope| {
+        let (lines, line_receiver) = mpsc::channel();
+        let writer = scope.spawn(move || write_lines(stdout, line_receiver));
+
+        for incoming in receiver {
+            let response = match incoming {
+                Incoming::Reply(response) => response,
+                Incoming::Request(request, token) => {
+                    respond(registry, request, token, &pending, &lines)
+                }
+            };
+            let payload = serde_json::to_string(&response).context("serialize response")?;
+            debug!("Responding: {payload}");
+            if lines.send(payload).is_err() {
+                break;
+            }
+        }
+        drop(lines);
+        writer
+            .join()
+            .unwrap_or_else(|_| Err(anyhow::anyhow!("stdout writer panicked")))
+    });
+
+    let _ = reader_thread.join();
+    written
+}
+
+/// Answer `request`, unless `token` was cancelled before it started, with a
+/// notification sink feeding `lines` when the call asked to stream.
+fn respond(
+    registry: &ToolRegistry,
+    request: JsonRpcRequest,
+    token: Option<CancelToken>,
+    pending: &PendingRequests,
+    lines: &mpsc::Sender<String>,
+) -> JsonRpcResponse {
+    let id = request.id.clone();
+    if token.as_ref().is_some_and(CancelToken::is_cancelled) {
+        pending.finish(&id.unwrap_or_default());
+        return JsonRpcResponse::error(request.id, JsonRpcError::request_cancelled());
+    }
+
+    let sink = wants_stream(&request).then(|| stream_sink(id.clone(), lines.clone()));
+    let run = || match sink {
+        Some(sink) => notifications::with_sink(sink, DEFAULT_PROGRESS_INTERVAL, || {
+            handle_request(registry, request)
+        }),
+        None => handle_request(registry, request),
+    };
+    match token {
+        Some(token) => {
+            let response = cancellation::with_token(token, run);
+            pending.finish(&id.unwrap_or_default());
+            response
+        }
+        None => run(),
+    }
+}
+
+/// Whether `request` is a `tools.call` whose arguments set `stream: true`.
+fn wants_stream(request: &JsonRpcRequest) -> bool {
+    request.method == "tools.call"
+        && request
+            .params
+            .as_ref()
+            .and_then(|params| params.get("arguments"))
+            .and_then(|arguments| arguments.get("stream"))
+            .and_then(Value::as_bool)
+            == Some(true)
+}
+
+/// Sink writing each notification as a JSON-RPC notification line tagged
+/// with the `request_id` of the call that emitted it.
+fn stream_sink(request_id: Option<Value>, lines: mpsc::Sender<String>) -> NotificationSink {
+    Arc::new(move |method: &str, mut params: Value| {
+        if let Some(object) = params.as_object_mut() {
+            object.insert("request_id".to_string(), json!(request_id));
+        }
+        let notification = json!({"jsonrpc": "2.0", "method": method, "params": params});
+        if lines.send(notification.to_string()).is_err() {
+            warn!("Dropped {method} notification: output closed");
+        }
+    })
+}
+
+/// Write each received line to `stdout` until every sender is gone.
+fn write_lines(mut stdout: impl Write, lines: mpsc::Receiver<String>) -> Result<()> {
+    for line in lines {
+        stdout
+            .write_all(line.as_bytes())
+            .and_then(|_| stdout.write_all(b"\n"))
+            .and_then(|_| stdout.flush())
+            .context("write to stdout")?;
+    }
+    Ok(())
+}
+
+/// Read requests from `reader` into `sender` until end of input, acting on
+/// cancellations immediately instead of queueing them.
+fn read_requests(
+    mut reader: impl BufRead,
+    sender: &mpsc::Sender<Incoming>,
+    pending: &PendingRequests,
+    max_line_bytes: usize,
+) {
+    let mut buffer = Vec::new();
 
-    for line in stdin.lock().lines() {
-        let line = match line {
-            Ok(line) if !line.trim().is_empty() => line,
-            Ok(_) => continue,
+    loop {
+        let line = match read_bounded_line(&mut reader, &mut buffer, max_line_bytes) {
+            Ok(LineRead::Eof) => break,
+            Ok(LineRead::Line) => String::from_utf8_lossy(&buffer),
+            Ok(LineRead::TooLong { bytes }) => {
+                error!("Rejected request line of {bytes} bytes (limit {max_line_bytes})");
+                // The retained prefix usually still holds the id.
+                let response = JsonRpcResponse::error(
+                    recover_id(&String::from_utf8_lossy(&buffer)),
+                    JsonRpcError::parse_error(format!(
+                        "Request line of {bytes} bytes exceeds the maximum of {max_line_bytes} bytes"
+                    )),
+                );
+                if sender.send(Incoming::Reply(response)).is_err() {
+                    break;
+                }
+                continue;
+            }
             Err(err) => {
                 error!("Failed reading stdin: {err}");
                 break;
             }
         };
+        if line.trim().is_empty() {
+            continue;
+        }
 
         debug!("Received: {line}");
-        let request: JsonRpcRequest = match serde_json::from_str(&line) {
-            Ok(req) => req,
-            Err(err) => {
-                let response =
-                    JsonRpcResponse::error(None, JsonRpcError::parse_error(err.to_string()));
-                write_response(&mut stdout, &response)?;
-                continue;
+        let incoming = match serde_json::from_str::<JsonRpcRequest>(&line) {
+            Ok(request) if CANCEL_METHODS.contains(&request.method.as_str()) => {
+                match cancel_request(pending, request) {
+                    Some(response) => Incoming::Reply(response),
+                    None => continue,
+                }
             }
+            Ok(request) => {
+                let token = request.id.as_ref().map(|id| pending.register(id));
+                Incoming::Request(request, token)
+            }
+            Err(err) => Incoming::Reply(JsonRpcResponse::error(
+                recover_id(&line),
+                JsonRpcError::parse_error(err.to_string()),
+            )),
         };
+        if sender.send(incoming).is_err() {
+            break;
+        }
+    }
+}
 
-        let response = handle_request(registry, request);
-        write_response(&mut stdout, &response)?;
+/// Methods that cancel the pending request named by `params.id`.
+const CANCEL_METHODS: &[&str] = &["cancel", "$/cancelRequest"];
+
+/// Flag the pending request named by `params.id` as cancelled. Sent as a
+/// notification it gets no answer; sent with an id it is answered with
+/// whether a pending request was found.
+fn cancel_request(pending: &PendingRequests, request: JsonRpcRequest) -> Option<JsonRpcResponse> {
+    let target = request
+        .params
+        .as_ref()
+        .and_then(|params| params.get("id"))
+        .filter(|id| id.is_string() || id.is_number());
+    let Some(target) = target else {
+        warn!(
+            "Ignoring {} without a string or number `id`",
+            request.method
+        );
+        return request.id.map(|id| {
+            JsonRpcResponse::error(
+                Some(id),
+                JsonRpcError::invalid_params("Missing `id` of the request to cancel"),
+            )
+        });
+    };
+    let cancelled = pending.cancel(target);
+    debug!("Cancel request for {target}: pending={cancelled}");
+    request
+        .id
+        .map(|id| JsonRpcResponse::result(Some(id), json!({ "cancelled": cancelled })))
+}
+
+enum LineRead {
+    Eof,
+    /// A complete line, without its terminator, is in the buffer.
+    Line,
+    /// The line was longer than the limit; the buffer holds only its first
+    /// `limit` bytes and the rest was discarded.
+    TooLong {
+        bytes: usize,
+    },
+}
+
+/// Read one `\n`-terminated line into `buffer`, never holding more than
+/// `limit` bytes of it. Oversized lines are consumed through their newline so
+/// the next read starts at the following request.
+fn read_bounded_line(
+    reader: &mut impl BufRead,
+    buffer: &mut Vec<u8>,
+    limit: usize,
+) -> io::Result<LineRead> {
+    buffer.clear();
+    let mut total = 0usize;
+    let mut read_any = false;
+    loop {
+        let available = match reader.fill_buf() {
+            Ok(available) => available,
+            Err(err) if err.kind() == io::ErrorKind::Interrupted => continue,
+            Err(err) => return Err(err),
+        };
+        if available.is_empty() {
+            break;
+        }
+        let (chunk, consumed, done) = match available.iter().position(|b| *b == b'\n') {
+            Some(newline) => (&available[..newline], newline + 1, true),
+            None => (available, available.len(), false),
+        };
+        let room = limit.saturating_sub(buffer.len());
+        buffer.extend_from_slice(&chunk[..chunk.len().min(room)]);
+        total += chunk.len();
+        reader.consume(consumed);
+        read_any = true;
+        if done {
+            break;
+        }
     }
 
-    info!("Stdio loop terminated");
-    Ok(())
+    if !read_any {
+        return Ok(LineRead::Eof);
+    }
+    if total > limit {
+        return Ok(LineRead::TooLong { bytes: total });
+    }
+    if buffer.last() == Some(&b'\r') {
+        buffer.pop();
+    }
+    Ok(LineRead::Line)
+}
+
+/// Best-effort request `id` from a line that did not parse as a request, so
+/// the parse error can still be correlated. Only string and number ids are
+/// recovered; `None` (a null id) means it truly could not be determined.
+fn recover_id(line: &str) -> Option<Value> {
+    let is_valid_id = |id: &Value| id.is_string() || id.is_number();
+
+    if let Ok(value) = serde_json::from_str::<Value>(line) {
+        return value.get("id").filter(|id| is_valid_id(id)).cloned();
+    }
+
+    // Malformed JSON: find an `"id"` key at the top nesting level and parse
+    // the value that follows it.
+    let bytes = line.as_bytes();
+    let mut depth = 0usize;
+    let mut index = 0;
+    while index < bytes.len() {
+        match bytes[index] {
+            b'{' | b'[' => depth += 1,
+            b'}' | b']' => depth = depth.saturating_sub(1),
+            b'"' => {
+                let start = index;
+                index += 1;
+                while index < bytes.len() && bytes[index] != b'"' {
+                    index += if bytes[index] == b'\\' { 2 } else { 1 };
+                }
+                index = (index + 1).min(bytes.len());
+                if depth == 1
+                    && &line[start..index] == "\"id\""
+                    && let Some(rest) = line[index..].trim_start().strip_prefix(':')
+                {
+                    return serde_json::Deserializer::from_str(rest.trim_start())
+                        .into_iter::<Value>()
+                        .next()
+                        .and_then(Result::ok)
+                        .filter(is_valid_id);
+                }
+                continue;
+            }
+            _ => {}
+        }
+        index += 1;
+    }
+    None
 }
 
 fn handle_request(registry: &ToolRegistry, request: JsonRpcRequest) -> JsonRpcResponse {
@@ -49,11 +375,205 @@ fn handle_request(registry: &ToolRegistry, request: JsonRpcRequest) -> JsonRpcRe
             let descriptors = registry.descriptors();
             JsonRpcResponse::result(request.id, json!({ "tools": descriptors }))
         }
+        "tools.schemas" => JsonRpcResponse::result(request.id, registry.schema_bundle()),
+        "tools.suggest" => suggest(registry, request),
         "tools.call" => call_tool(registry, request),
+        "tools.callMany" => call_many(registry, request),
+        "status" => JsonRpcResponse::result(request.id, server_status(registry)),
+        "get_config" => JsonRpcResponse::result(request.id, server_config(registry)),
+        "clear_cache" => clear_cache(registry, request),
+        "metrics" => metrics(registry, request),
+        "reset_metrics" => {
+            registry.metrics().reset();
+            JsonRpcResponse::result(request.id, json!({ "reset": true }))
+        }
         other => JsonRpcResponse::error(request.id, JsonRpcError::method_not_found(other)),
     }
 }
 
+/// Introspection for health checks: uptime, registered tools, whether every
+/// tool schema compiled, whether the state directory accepts writes and,
+/// when calls are limited, how many are running, queued or were rejected.
+fn server_status(registry: &ToolRegistry) -> Value {
+    let invalid_schemas = registry.invalid_schemas();
+    // A read-only server never writes state, so it does not probe (or
+    // create) the directory either.
+    let state_dir = if registry.is_read_only() {
+        state_dir().ok()
+    } else {
+        writable_state_dir().ok()
+    };
+    let state_writable = !registry.is_read_only()
+        && state_dir.as_ref().is_some_and(|dir| {
+            let probe = dir.join(format!(".status-probe-{}", std::process::id()));
+            let writable = fs::write(&probe, b"ok").is_ok();
+            let _ = fs::remove_file(&probe);
+            writable
+        });
+
+    json!({
+        "version": env!("CARGO_PKG_VERSION"),
+        "uptime_secs": STARTED.elapsed().as_secs(),
+        "tool_count": registry.len(),
+        "schemas_valid": invalid_schemas.is_empty(),
+        "invalid_schemas": invalid_schemas,
+        "read_only": registry.is_read_only(),
+        "state_dir": state_dir.map(|dir| dir.to_string_lossy().to_string()),
+        "state_writable": state_writable,
+        "call_limit": registry.call_limit().map(|limit| {
+            let state = limit.snapshot();
+            json!({
+                "max_concurrent_calls": limit.max_concurrent(),
+                "queue_timeout_ms": limit.max_wait().map(|wait| wait.as_millis() as u64),
+                "active": state.active,
+                "queued": state.queued,
+                "rejected": state.rejected,
+            })
+        }),
+    })
+}
+
+/// Environment variables that change where or how the server works.
+const CONFIG_ENV_VARS: &[&str] = &[
+    "SERENA_STATE_DIR",
+    "SERENA_MEMORY_LAYOUT",
+    "SERENA_LANG_MAP",
+    "HOME",
+    "RUST_LOG",
+];
+
+/// The settings the running server resolved: launch flags, the state
+/// directory and where it came from, and the environment behind them.
+fn server_config(registry: &ToolRegistry) -> Value {
+    let env = CONFIG_ENV_VARS
+        .iter()
+        .map(|name| (name.to_string(), json!(env::var(name).ok())))
+        .collect::<serde_json::Map<_, _>>();
+    let error_text = |err: anyhow::Error| format!("{err:#}");
+
+    json!({
+        "version": env!("CARGO_PKG_VERSION"),
+        "launch": registry.launch_settings(),
+        "log_level": log::max_level().to_string().to_lowercase(),
+        "working_dir": env::current_dir().ok().map(|dir| dir.to_string_lossy().to_string()),
+        "project_root": registry.project_root().map(|dir| dir.to_string_lossy().to_string()),
+        "state_dir": match state_dir() {
+            Ok(dir) => json!({
+                "path": dir.to_string_lossy(),
+                "source": state_dir_source(),
+            }),
+            Err(err) => json!({"error": error_text(err)}),
+        },
+        "memory_layout": match memory_layout() {
+            Ok(layout) => json!(layout),
+            Err(err) => json!({"error": error_text(err)}),
+        },
+        "read_only": registry.is_read_only(),
+        "max_response_bytes": registry.max_response_bytes(),
+        "result_cache": registry.result_cache().map(|cache| json!({
+            "ttl_secs": cache.ttl().as_secs(),
+            "tools": registry.cached_tools(),
+            "entries": cache.len(),
+        })),
+        "tool_count": registry.len(),
+        "env": env,
+    })
+}
+
+/// Drop cached tool results, all of them or only those of `params.tool`.
+fn clear_cache(registry: &ToolRegistry, request: JsonRpcRequest) -> JsonRpcResponse {
+    let id = request.id.clone();
+    let tool = match &request.params {
+        None => None,
+        Some(Value::Object(params)) => match params.get("tool") {
+            None | Some(Value::Null) => None,
+            Some(Value::String(tool)) => Some(tool.clone()),
+            Some(_) => {
+                return JsonRpcResponse::error(
+                    id,
+                    JsonRpcError::invalid_params("`tool` must be a string"),
+                );
+            }
+        },
+        Some(_) => {
+            return JsonRpcResponse::error(
+                id,
+                JsonRpcError::invalid_params("Expected object params"),
+            );
+        }
+    };
+
+    let Some(cache) = registry.result_cache() else {
+        return JsonRpcResponse::result(
+            id,
+            json!({"enabled": false, "cleared": 0, "remaining": 0}),
+        );
+    };
+    let cleared = cache.clear(tool.as_deref());
+    JsonRpcResponse::result(
+        id,
+        json!({"enabled": true, "cleared": cleared, "remaining": cache.len()}),
+    )
+}
+
+/// Per-tool call counts, failures and durations; `params.reset` starts a new
+/// window after reading.
+fn metrics(registry: &ToolRegistry, request: JsonRpcRequest) -> JsonRpcResponse {
+    let reset = match &request.params {
+        None => false,
+        Some(Value::Object(params)) => match params.get("reset") {
+            None | Some(Value::Null) => false,
+            Some(Value::Bool(reset)) => *reset,
+            Some(_) => {
+                return JsonRpcResponse::error(
+                    request.id,
+                    JsonRpcError::invalid_params("`reset` must be a boolean"),
+                );
+            }
+        },
+        Some(_) => {
+            return JsonRpcResponse::error(
+                request.id,
+                JsonRpcError::invalid_params("Expected object params"),
+            );
+        }
+    };
+    JsonRpcResponse::result(request.id, registry.metrics().snapshot(reset))
+}
+
+/// Rank tools by keyword overlap with `params.task`.
+fn suggest(registry: &ToolRegistry, request: JsonRpcRequest) -> JsonRpcResponse {
+    let id = request.id.clone();
+    let Some(Value::Object(params)) = request.params else {
+        return JsonRpcResponse::error(id, JsonRpcError::invalid_params("Expected object params"));
+    };
+    let Some(task) = params.get("task").and_then(Value::as_str) else {
+        return JsonRpcResponse::error(id, JsonRpcError::invalid_params("Missing `task` string"));
+    };
+    let limit = match params.get("limit") {
+        None => DEFAULT_SUGGESTIONS,
+        Some(limit) => match limit.as_u64().filter(|limit| *limit > 0) {
+            Some(limit) => limit as usize,
+            None => {
+                return JsonRpcResponse::error(
+                    id,
+                    JsonRpcError::invalid_params("`limit` must be a positive integer"),
+                );
+            }
+        },
+    };
+
+    let suggestions = suggest_tools(registry, task, limit);
+    JsonRpcResponse::result(
+        id,
+        json!({
+            "task": task,
+            "count": suggestions.len(),
+            "suggestions": suggestions,
+        }),
+    )
+}
+
 fn call_tool(registry: &ToolRegistry, request: JsonRpcRequest) -> JsonRpcResponse {
     let id = request.id.clone();
     let params = match request.params {
@@ -77,20 +597,103 @@ fn call_tool(registry: &ToolRegistry, request: JsonRpcRequest) -> JsonRpcRespons
     };
     let arguments = params.get("arguments").cloned().unwrap_or(Value::Null);
 
-    match registry.call(&tool_name, arguments) {
+    match dispatch_tool(registry, &tool_name, arguments) {
         Ok(result) => JsonRpcResponse::result(id, json!({ "tool": tool_name, "result": result })),
-        Err(err) => JsonRpcResponse::error(id, JsonRpcError::internal_error(err.to_string())),
+        Err(error) => JsonRpcResponse::error(id, error),
     }
 }
 
-fn write_response(stdout: &mut impl Write, response: &JsonRpcResponse) -> Result<()> {
-    let payload = serde_json::to_string(response).context("serialize response")?;
-    debug!("Responding: {payload}");
-    stdout
-        .write_all(payload.as_bytes())
-        .and_then(|_| stdout.write_all(b"\n"))
-        .and_then(|_| stdout.flush())
-        .context("write to stdout")
+fn dispatch_tool(
+    registry: &ToolRegistry,
+    tool_name: &str,
+    arguments: Value,
+) -> Result<Value, JsonRpcError> {
+    let _permit = match registry
+        .call_limit()
+        .map(|limit| limit.acquire())
+        .transpose()
+    {
+        Ok(permit) => permit,
+        Err(busy) => {
+            error!("Rejected {tool_name} call: {busy}");
+            return Err(JsonRpcError::server_busy(&busy));
+        }
+    };
+    registry.call(tool_name, arguments).map_err(|err| {
+        if err.downcast_ref::<Cancelled>().is_some() {
+            return JsonRpcError::request_cancelled();
+        }
+        match err.downcast_ref::<InvalidArguments>() {
+            Some(invalid) => JsonRpcError::invalid_arguments(invalid),
+            None => JsonRpcError::internal_error(err.to_string()),
+        }
+    })
+}
+
+/// Run an ordered list of `{ tool, arguments }` steps in one request. Steps
+/// after the first failure are skipped unless `continue_on_error` is set.
+fn call_many(registry: &ToolRegistry, request: JsonRpcRequest) -> JsonRpcResponse {
+    #[derive(Deserialize)]
+    struct Step {
+        tool: String,
+        #[serde(default)]
+        arguments: Value,
+    }
+
+    #[derive(Deserialize)]
+    struct Params {
+        calls: Vec<Step>,
+        #[serde(default)]
+        continue_on_error: bool,
+    }
+
+    let id = request.id.clone();
+    let params: Params = match request.params.map(serde_json::from_value) {
+        Some(Ok(params)) => params,
+        Some(Err(err)) => {
+            return JsonRpcResponse::error(id, JsonRpcError::invalid_params(&err.to_string()));
+        }
+        None => {
+            return JsonRpcResponse::error(
+                id,
+                JsonRpcError::invalid_params("Missing `calls` array"),
+            );
+        }
+    };
+
+    let total = params.calls.len();
+    let mut steps = Vec::with_capacity(total);
+    let mut failed = 0usize;
+    for (index, step) in params.calls.into_iter().enumerate() {
+        match dispatch_tool(registry, &step.tool, step.arguments) {
+            Ok(result) => steps.push(json!({
+                "index": index,
+                "tool": step.tool,
+                "result": result,
+            })),
+            Err(error) => {
+                failed += 1;
+                steps.push(json!({
+                    "index": index,
+                    "tool": step.tool,
+                    "error": error,
+                }));
+                if !params.continue_on_error {
+                    break;
+                }
+            }
+        }
+    }
+
+    JsonRpcResponse::result(
+        id,
+        json!({
+            "total": total,
+            "completed": steps.len(),
+            "failed": failed,
+            "steps": steps,
+        }),
+    )
 }
 
 #[derive(Debug, Deserialize)]
@@ -164,6 +767,33 @@ impl JsonRpcError {
         )
     }
 
+    fn invalid_arguments(invalid: &InvalidArguments) -> Self {
+        Self::new(
+            -32602,
+            "Invalid params",
+            Some(json!({
+                "details": invalid.to_string(),
+                "tool": invalid.tool,
+                "errors": invalid.errors,
+            })),
+        )
+    }
+
+    fn server_busy(busy: &ServerBusy) -> Self {
+        Self::new(
+            -32000,
+            "Server busy",
+            Some(json!({
+                "details": busy.to_string(),
+                "max_concurrent_calls": busy.max_concurrent,
+            })),
+        )
+    }
+
+    fn request_cancelled() -> Self {
+        Self::new(-32800, "Request cancelled", None)
+    }
+
     fn internal_error(message: String) -> Self {
         Self::new(
             -32603,
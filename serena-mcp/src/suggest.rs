@@ -0,0 +1,90 @@
+use std::collections::BTreeMap;
+
+use serde::Serialize;
+
+use crate::tool::ToolRegistry;
+
+/// Words too common in task descriptions to say anything about a tool.
+const STOP_WORDS: &[&str] = &[
+    "a", "an", "and", "any", "are", "as", "at", "be", "by", "can", "do", "for", "from", "how", "i",
+    "in", "into", "is", "it", "its", "me", "my", "of", "on", "or", "so", "that", "the", "their",
+    "them", "then", "this", "to", "want", "we", "what", "when", "where", "which", "with",
+];
+
+/// A tool ranked against a task description.
+#[derive(Debug, Clone, Serialize)]
+pub struct ToolSuggestion {
+    pub tool: String,
+    /// Share of the task's keywords found in the tool's name or description.
+    pub score: f64,
+    pub matched: Vec<String>,
+}
+
+/// Rank the registry's available tools by keyword overlap between `task` and
+/// each tool's name and description, returning at most `limit` tools with a
+/// non-zero score, best first.
+pub fn suggest_tools(registry: &ToolRegistry, task: &str, limit: usize) -> Vec<ToolSuggestion> {
+    let query = keywords(task);
+    if query.is_empty() {
+        return Vec::new();
+    }
+
+    let mut suggestions = registry
+        .descriptors()
+        .into_iter()
+        .filter_map(|descriptor| {
+            let text = format!("{} {}", descriptor.name, descriptor.description);
+            let tool_keywords = keywords(&text);
+            let matched = query
+                .iter()
+                .filter(|(stem, _)| tool_keywords.contains_key(*stem))
+                .map(|(_, word)| word.clone())
+                .collect::<Vec<_>>();
+            if matched.is_empty() {
+                return None;
+            }
+            let score = matched.len() as f64 / query.len() as f64;
+            Some(ToolSuggestion {
+                tool: descriptor.name,
+                score: (score * 1000.0).round() / 1000.0,
+                matched,
+            })
+        })
+        .collect::<Vec<_>>();
+
+    suggestions.sort_by(|a, b| {
+        b.score
+            .total_cmp(&a.score)
+            .then_with(|| a.tool.cmp(&b.tool))
+    });
+    suggestions.truncate(limit);
+    suggestions
+}
+
+/// Lowercase word stems of `text` mapped to the first word producing each,
+/// split on anything that is not alphanumeric (so `find_symbol` yields `find`
+/// and `symbol`).
+fn keywords(text: &str) -> BTreeMap<String, String> {
+    let mut keywords = BTreeMap::new();
+    for word in text
+        .split(|c: char| !c.is_alphanumeric())
+        .map(str::to_lowercase)
+        .filter(|word| word.len() > 1 && !STOP_WORDS.contains(&word.as_str()))
+    {
+        keywords.entry(stem(&word)).or_insert(word);
+    }
+    keywords
+}
+
+/// Crude suffix stripping so `files`, `renaming` and `renamed` meet `file`
+/// and `rename`: one inflection suffix, then a trailing `e`.
+fn stem(word: &str) -> String {
+    let base = ["ing", "ed", "es", "s"]
+        .iter()
+        .find_map(|suffix| word.strip_suffix(suffix).filter(|base| base.len() >= 3))
+        .unwrap_or(word);
+    match base.strip_suffix('e') {
+        Some(stripped) if stripped.len() >= 3 => stripped.to_string(),
+        _ => base.to_string(),
+    }
+}
@@ -1,8 +1,29 @@
 use std::collections::HashMap;
+use std::fmt;
+use std::path::{Path, PathBuf};
+use std::time::Instant;
 
 use anyhow::Result;
+use jsonschema::Validator;
+use log::{debug, warn};
 use serde::{Deserialize, Serialize};
-use serde_json::Value;
+use serde_json::{Value, json};
+
+use crate::call_limit::CallLimiter;
+use crate::idempotency::{self, IDEMPOTENCY_KEY};
+use crate::metrics::Metrics;
+use crate::path_style::{self, PATH_STYLE_KEY, PathStyle};
+use crate::response_limit;
+use crate::result_cache::{self, ResultCache};
+use crate::tools::{CWD_KEY, scoped_base_dir, scoped_cwd, scoped_read_only};
+
+/// Base `$id` of the schema bundle; each tool's schema is `<base>/<tool>`.
+pub const SCHEMA_BUNDLE_ID: &str = "urn:serena-mcp:tools";
+
+/// Schema member listing the arguments a positional `arguments` array maps
+/// onto, in order. Object members are unordered, so `properties` cannot
+/// carry that order itself.
+pub const POSITIONAL_ORDER_KEY: &str = "x-positional-order";
 
 /// Handler signature for incoming tool calls.
 pub type ToolHandler = Box<dyn Fn(Value) -> Result<Value> + Send + Sync>;
@@ -13,6 +34,20 @@ pub struct Tool {
     description: String,
     parameters: Value,
     handler: ToolHandler,
+    validator: Option<Validator>,
+    mutating: bool,
+    resolves_paths: bool,
+    cacheable: bool,
+}
+
+fn compile_validator(name: &str, parameters: &Value) -> Option<Validator> {
+    match jsonschema::validator_for(parameters) {
+        Ok(validator) => Some(validator),
+        Err(err) => {
+            warn!("Schema for tool {name} does not compile; arguments are not validated: {err}");
+            None
+        }
+    }
 }
 
 impl Tool {
@@ -22,18 +57,181 @@ impl Tool {
         parameters: Value,
         handler: ToolHandler,
     ) -> Self {
+        let name = name.into();
+        let mut parameters = parameters;
+        if let Some(properties) = parameters
+            .get_mut("properties")
+            .and_then(Value::as_object_mut)
+        {
+            properties.insert(
+                PATH_STYLE_KEY.to_string(),
+                json!({
+                    "type": "string",
+                    "enum": ["absolute", "relative-to-root"],
+                    "description": "How file paths in the response are written; omitting it keeps the deprecated per-tool default",
+                }),
+            );
+        }
+        let validator = compile_validator(&name, &parameters);
+
         Self {
-            name: name.into(),
+            name,
             description: description.into(),
             parameters,
             handler,
+            validator,
+            mutating: false,
+            resolves_paths: false,
+            cacheable: false,
+        }
+    }
+
+    /// Mark the tool as changing files or state. Mutating tools accept an
+    /// optional `idempotency_key` so retried calls replay the first response.
+    pub fn mutating(mut self) -> Self {
+        self.mutating = true;
+        if let Some(properties) = self
+            .parameters
+            .get_mut("properties")
+            .and_then(Value::as_object_mut)
+        {
+            properties.insert(
+                IDEMPOTENCY_KEY.to_string(),
+                json!({
+                    "type": "string",
+                    "minLength": 1,
+                    "description": "Replay the recorded response when a call with this key was already applied recently",
+                }),
+            );
+        }
+        self.validator = compile_validator(&self.name, &self.parameters);
+        self
+    }
+
+    pub fn is_mutating(&self) -> bool {
+        self.mutating
+    }
+
+    /// Mark the tool as taking file paths. Such tools accept an optional
+    /// `cwd` that relative paths resolve against for that call only.
+    pub fn resolves_paths(mut self) -> Self {
+        self.resolves_paths = true;
+        if let Some(properties) = self
+            .parameters
+            .get_mut("properties")
+            .and_then(Value::as_object_mut)
+        {
+            properties.insert(
+                CWD_KEY.to_string(),
+                json!({
+                    "type": "string",
+                    "minLength": 1,
+                    "description": "Directory relative paths resolve against for this call (defaults to the server's working directory)",
+                }),
+            );
         }
+        self.validator = compile_validator(&self.name, &self.parameters);
+        self
+    }
+
+    pub fn is_path_aware(&self) -> bool {
+        self.resolves_paths
+    }
+
+    /// Mark the tool's responses as depending only on its arguments and the
+    /// files it reads, so the registry's result cache may reuse them.
+    pub fn cacheable(mut self) -> Self {
+        self.cacheable = true;
+        self
+    }
+
+    pub fn is_cacheable(&self) -> bool {
+        self.cacheable
+    }
+
+    /// Whether the schema declares an argument called `name`.
+    pub fn declares_argument(&self, name: &str) -> bool {
+        self.parameters
+            .get("properties")
+            .and_then(Value::as_object)
+            .is_some_and(|properties| properties.contains_key(name))
+    }
+
+    /// Whether the argument schema compiled, so calls are validated.
+    pub fn schema_compiles(&self) -> bool {
+        self.validator.is_some()
     }
 
     pub fn call(&self, params: Value) -> Result<Value> {
         (self.handler)(params)
     }
 
+    /// Map positional `arguments` onto the names listed under
+    /// [`POSITIONAL_ORDER_KEY`]; the injected `cwd`, `path_style` and
+    /// `idempotency_key` arguments are never positional. A `null` element
+    /// leaves that argument unset, so optional arguments can be skipped to
+    /// reach later ones.
+    pub fn named_arguments(&self, arguments: Vec<Value>) -> Result<Value, InvalidArguments> {
+        let names = self
+            .parameters
+            .get(POSITIONAL_ORDER_KEY)
+            .and_then(Value::as_array)
+            .map(|names| {
+                names
+                    .iter()
+                    .filter_map(Value::as_str)
+                    .map(str::to_owned)
+                    .collect::<Vec<_>>()
+            })
+            .unwrap_or_default();
+        if arguments.len() > names.len() {
+            return Err(InvalidArguments {
+                tool: self.name.clone(),
+                errors: vec![ArgumentError {
+                    path: String::new(),
+                    message: format!(
+                        "got {} positional arguments but the tool takes at most {} ({})",
+                        arguments.len(),
+                        names.len(),
+                        names.join(", ")
+                    ),
+                }],
+            });
+        }
+
+        let named = names
+            .into_iter()
+            .zip(arguments)
+            .filter(|(_, value)| !value.is_null())
+            .collect();
+        Ok(Value::Object(named))
+    }
+
+    /// Check `params` against the tool's JSON schema, collecting every
+    /// violation with the JSON pointer of the offending value.
+    pub fn validate(&self, params: &Value) -> Result<(), InvalidArguments> {
+        let Some(validator) = &self.validator else {
+            return Ok(());
+        };
+
+        let errors: Vec<ArgumentError> = validator
+            .iter_errors(params)
+            .map(|error| ArgumentError {
+                path: error.instance_path.to_string(),
+                message: error.to_string(),
+            })
+            .collect();
+
+        if errors.is_empty() {
+            Ok(())
+        } else {
+            Err(InvalidArguments {
+                tool: self.name.clone(),
+                errors,
+            })
+        }
+    }
+
     pub fn descriptor(&self) -> ToolDescriptor {
         ToolDescriptor {
             name: self.name.clone(),
@@ -55,31 +253,364 @@ pub struct ToolDescriptor {
     pub parameters: Value,
 }
 
+/// A single schema violation; `path` is a JSON pointer into the arguments
+/// (empty for the arguments object itself).
+#[derive(Debug, Clone, Serialize)]
+pub struct ArgumentError {
+    pub path: String,
+    pub message: String,
+}
+
+/// Arguments rejected by schema validation before the handler ran.
+#[derive(Debug, Clone, Serialize)]
+pub struct InvalidArguments {
+    pub tool: String,
+    pub errors: Vec<ArgumentError>,
+}
+
+impl fmt::Display for InvalidArguments {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "Invalid arguments for {}: ", self.tool)?;
+        for (idx, error) in self.errors.iter().enumerate() {
+            if idx > 0 {
+                write!(f, "; ")?;
+            }
+            if error.path.is_empty() {
+                write!(f, "{}", error.message)?;
+            } else {
+                write!(f, "{}: {}", error.path, error.message)?;
+            }
+        }
+        Ok(())
+    }
+}
+
+impl std::error::Error for InvalidArguments {}
+
 /// Registry storing all available tools.
 pub struct ToolRegistry {
     tools: HashMap<String, Tool>,
+    max_response_bytes: Option<usize>,
+    read_only: bool,
+    /// Startup settings reported by `get_config`; null when not set.
+    launch_settings: Value,
+    /// Activated project that relative and omitted paths default to.
+    project_root: Option<PathBuf>,
+    result_cache: Option<ResultCache>,
+    call_limit: Option<CallLimiter>,
+    metrics: Metrics,
+}
+
+impl Default for ToolRegistry {
+    fn default() -> Self {
+        Self::new()
+    }
 }
 
 impl ToolRegistry {
     pub fn new() -> Self {
         Self {
             tools: HashMap::new(),
+            max_response_bytes: None,
+            read_only: false,
+            launch_settings: Value::Null,
+            project_root: None,
+            result_cache: None,
+            call_limit: None,
+            metrics: Metrics::new(),
         }
     }
 
+    /// Hide mutating tools from listings and reject calls to them.
+    pub fn set_read_only(&mut self, read_only: bool) {
+        self.read_only = read_only;
+    }
+
+    pub fn is_read_only(&self) -> bool {
+        self.read_only
+    }
+
+    /// Tools that may be called, i.e. everything except mutating tools when
+    /// the registry is read-only.
+    fn available(&self) -> impl Iterator<Item = (&String, &Tool)> {
+        self.tools
+            .iter()
+            .filter(|(_, tool)| !(self.read_only && tool.is_mutating()))
+    }
+
+    /// Cap the serialised size of every tool response; larger responses are
+    /// shrunk and flagged with `response_truncated`.
+    pub fn set_max_response_bytes(&mut self, limit: Option<usize>) {
+        self.max_response_bytes = limit;
+    }
+
+    pub fn max_response_bytes(&self) -> Option<usize> {
+        self.max_response_bytes
+    }
+
+    pub fn set_launch_settings(&mut self, settings: Value) {
+        self.launch_settings = settings;
+    }
+
+    pub fn launch_settings(&self) -> &Value {
+        &self.launch_settings
+    }
+
+    /// Default root for every call: path-optional tools operate on it and
+    /// relative paths resolve against it, unless a call passes `cwd`.
+    pub fn set_project_root(&mut self, root: Option<PathBuf>) {
+        self.project_root = root;
+    }
+
+    pub fn project_root(&self) -> Option<&Path> {
+        self.project_root.as_deref()
+    }
+
+    /// Serve repeated calls to cacheable tools from `cache`, or disable
+    /// caching with `None`. Fails when `cache` names a tool that is unknown
+    /// or not cacheable.
+    pub fn set_result_cache(&mut self, cache: Option<ResultCache>) -> Result<()> {
+        if let Some(tools) = cache.as_ref().and_then(ResultCache::tools) {
+            for name in tools {
+                match self.tools.get(name) {
+                    Some(tool) if tool.is_cacheable() => {}
+                    Some(_) => anyhow::bail!(
+                        "Tool {name} cannot be cached; cacheable tools: {}",
+                        self.cacheable_tools().join(", ")
+                    ),
+                    None => anyhow::bail!("Unknown tool: {name}"),
+                }
+            }
+        }
+        self.result_cache = cache;
+        Ok(())
+    }
+
+    pub fn result_cache(&self) -> Option<&ResultCache> {
+        self.result_cache.as_ref()
+    }
+
+    /// Bound how many tool calls the RPC server runs at once, or lift the
+    /// bound with `None`.
+    pub fn set_call_limit(&mut self, limit: Option<CallLimiter>) {
+        self.call_limit = limit;
+    }
+
+    pub fn call_limit(&self) -> Option<&CallLimiter> {
+        self.call_limit.as_ref()
+    }
+
+    /// Call counts, failures and durations of every tool called so far.
+    pub fn metrics(&self) -> &Metrics {
+        &self.metrics
+    }
+
+    /// Names of the tools that are cached when caching is enabled, sorted.
+    pub fn cached_tools(&self) -> Vec<String> {
+        let Some(cache) = &self.result_cache else {
+            return Vec::new();
+        };
+        let mut names = self
+            .available()
+            .filter(|(_, tool)| cache.applies_to(tool))
+            .map(|(name, _)| name.clone())
+            .collect::<Vec<_>>();
+        names.sort();
+        names
+    }
+
+    /// Names of every tool marked [`Tool::cacheable`], sorted.
+    pub fn cacheable_tools(&self) -> Vec<String> {
+        let mut names = self
+            .available()
+            .filter(|(_, tool)| tool.is_cacheable())
+            .map(|(name, _)| name.clone())
+            .collect::<Vec<_>>();
+        names.sort();
+        names
+    }
+
+    /// Names of the registered tools whose schema does not compile, sorted.
+    /// Includes mutating tools hidden in read-only mode.
+    pub fn invalid_schemas(&self) -> Vec<String> {
+        let mut names = self
+            .tools
+            .iter()
+            .filter(|(_, tool)| !tool.schema_compiles())
+            .map(|(name, _)| name.clone())
+            .collect::<Vec<_>>();
+        names.sort();
+        names
+    }
+
     pub fn register(&mut self, tool: Tool) {
         let name = tool.name().to_owned();
         self.tools.insert(name, tool);
     }
 
+    pub fn len(&self) -> usize {
+        self.available().count()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
     pub fn descriptors(&self) -> Vec<ToolDescriptor> {
-        self.tools.values().map(|tool| tool.descriptor()).collect()
+        self.available()
+            .map(|(_, tool)| tool.descriptor())
+            .collect()
+    }
+
+    pub fn get(&self, name: &str) -> Option<&Tool> {
+        self.tools
+            .get(name)
+            .filter(|tool| !(self.read_only && tool.is_mutating()))
+    }
+
+    /// Every tool's argument schema as one JSON Schema document. Each tool is
+    /// a `$defs` entry with its own `$id` (`SCHEMA_BUNDLE_ID/<tool>`), so
+    /// clients can validate payloads against exactly what the server enforces.
+    pub fn schema_bundle(&self) -> Value {
+        let mut names = self.available().map(|(name, _)| name).collect::<Vec<_>>();
+        names.sort();
+
+        let defs = names
+            .into_iter()
+            .map(|name| {
+                let mut schema = self.tools[name].parameters.clone();
+                if let Some(object) = schema.as_object_mut() {
+                    object.insert(
+                        "$id".to_string(),
+                        json!(format!("{SCHEMA_BUNDLE_ID}/{name}")),
+                    );
+                }
+                (name.clone(), schema)
+            })
+            .collect::<serde_json::Map<_, _>>();
+
+        json!({
+            "$schema": "https://json-schema.org/draft/2020-12/schema",
+            "$id": SCHEMA_BUNDLE_ID,
+            "$defs": defs,
+        })
     }
 
     pub fn call(&self, name: &str, params: Value) -> Result<Value> {
-        match self.tools.get(name) {
-            Some(tool) => tool.call(params),
-            None => anyhow::bail!("Unknown tool: {name}"),
+        let Some(tool) = self.tools.get(name) else {
+            anyhow::bail!("Unknown tool: {name}");
+        };
+        let started = Instant::now();
+        let result = self.run(tool, name, params);
+        self.metrics
+            .record(name, started.elapsed(), result.is_err());
+        result
+    }
+
+    fn run(&self, tool: &Tool, name: &str, params: Value) -> Result<Value> {
+        if self.read_only && tool.is_mutating() {
+            anyhow::bail!(
+                "Tool {name} modifies files or state and is disabled because the server is running in read-only mode (--read-only)"
+            );
+        }
+
+        // A missing `arguments` member means "no arguments", and an array
+        // holds them positionally.
+        let params = match params {
+            Value::Null => Value::Object(Default::default()),
+            Value::Array(arguments) => tool.named_arguments(arguments)?,
+            params => params,
+        };
+        tool.validate(&params)?;
+
+        let mut params = params;
+        let cwd = if tool.is_path_aware() {
+            params
+                .as_object_mut()
+                .and_then(|args| args.remove(CWD_KEY))
+                .and_then(|cwd| cwd.as_str().map(str::to_owned))
+        } else {
+            None
+        };
+        let _project = scoped_base_dir(self.project_root.clone());
+        let _read_only = scoped_read_only(self.read_only);
+        let _cwd = scoped_cwd(cwd.as_deref())?;
+        let path_style = params
+            .as_object_mut()
+            .and_then(|args| args.remove(PATH_STYLE_KEY))
+            .map(serde_json::from_value::<PathStyle>)
+            .transpose()?;
+        let _path_style = path_style::scoped(path_style);
+
+        let cache = self
+            .result_cache
+            .as_ref()
+            .filter(|cache| cache.applies_to(tool));
+        let cache_key = cache.map(|_| result_cache::key(name, &params, path_style));
+        if let (Some(cache), Some(key)) = (cache, &cache_key)
+            && !result_cache::bypasses(&params)
+            && let Some(cached) = cache.get(key)
+        {
+            debug!("Tool {name} served from the result cache");
+            return Ok(cached);
+        }
+        let cache_scope = cache.map(|_| result_cache::scope(&params));
+        let mutated_paths = match &self.result_cache {
+            Some(_) if tool.is_mutating() => Some(result_cache::mutated_paths(tool, &params)),
+            _ => None,
+        };
+
+        let idempotency_key = if tool.is_mutating() {
+            params
+                .as_object_mut()
+                .and_then(|args| args.remove(IDEMPOTENCY_KEY))
+                .and_then(|key| key.as_str().map(str::to_owned))
+        } else {
+            None
+        };
+        let arguments = idempotency_key
+            .as_ref()
+            .map(|_| idempotency::fingerprint(&params));
+        if let (Some(key), Some(arguments)) = (&idempotency_key, &arguments)
+            && let Some(recorded) = idempotency::lookup(name, key, arguments)?
+        {
+            debug!("Tool {name} replayed recorded response for idempotency key {key}");
+            return Ok(recorded);
+        }
+
+        let started = Instant::now();
+        let mut result = tool.call(params);
+        debug!(
+            "Tool {name} {} in {:?}",
+            if result.is_ok() {
+                "completed"
+            } else {
+                "failed"
+            },
+            started.elapsed()
+        );
+
+        if let (Some(limit), Ok(response)) = (self.max_response_bytes, &mut result) {
+            response_limit::enforce(response, limit);
+        }
+
+        if let (Some(cache), Some(key), Some(scope), Ok(response)) =
+            (cache, cache_key, cache_scope, &result)
+        {
+            cache.insert(key, name, scope, response.clone());
+        }
+        if let (Some(cache), Some(paths), Ok(_)) = (&self.result_cache, &mutated_paths, &result) {
+            let dropped = cache.invalidate(paths);
+            if dropped > 0 {
+                debug!("Tool {name} invalidated {dropped} cached results");
+            }
+        }
+
+        if let (Some(key), Some(arguments), Ok(response)) = (&idempotency_key, &arguments, &result)
+            && let Err(err) = idempotency::record(name, key, arguments, response)
+        {
+            warn!("Failed to record idempotency key {key} for {name}: {err:#}");
         }
+        result
     }
 }
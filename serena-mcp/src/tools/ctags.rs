@@ -0,0 +1,357 @@
+use std::collections::{HashMap, HashSet};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use anyhow::{Context, Result};
+use serde::Deserialize;
+use serde_json::{Value, json};
+
+use crate::path_style::display_path;
+use crate::tool::{Tool, ToolRegistry};
+use crate::tools::symbols::symbol_name_matches;
+use crate::tools::{base_dir, resolve_path};
+
+/// Tag file names tried, in order, when no `tags_file` is given.
+const DEFAULT_TAG_FILES: &[&str] = &["tags", ".tags"];
+
+/// Extension fields naming the scope a tag is declared in.
+const SCOPE_FIELDS: &[&str] = &[
+    "class",
+    "struct",
+    "union",
+    "enum",
+    "interface",
+    "implementation",
+    "namespace",
+    "module",
+    "trait",
+    "function",
+    "method",
+    "package",
+];
+
+pub fn register(registry: &mut ToolRegistry) {
+    registry.register(query_ctags_tool());
+}
+
+/// One line of a ctags `tags` file.
+#[derive(Debug)]
+struct Tag {
+    name: String,
+    /// As written in the tags file, usually relative to its directory.
+    file: String,
+    /// The ex command locating the tag: a line number or search pattern.
+    address: String,
+    kind: Option<String>,
+    line: Option<usize>,
+    container: Option<String>,
+    signature: Option<String>,
+    language: Option<String>,
+}
+
+fn query_ctags_tool() -> Tool {
+    let schema = json!({
+        "type": "object",
+        "properties": {
+            "name": {
+                "type": "string",
+                "description": "Symbol name or pattern to search for",
+            },
+            "tags_file": {
+                "type": "string",
+                "description": "ctags file to read. Defaults to `tags` or `.tags` in the current working directory",
+            },
+            "path": {
+                "type": "string",
+                "description": "Only tags in this file or under this directory",
+            },
+            "match_substring": {
+                "type": "boolean",
+                "description": "Allow substring matches instead of exact matches",
+                "default": true,
+            },
+            "case_sensitive": {
+                "type": "boolean",
+                "description": "Whether matching is case sensitive",
+                "default": false,
+            },
+            "kinds": {
+                "type": "array",
+                "items": {"type": "string"},
+                "description": "Restrict to specific symbol kinds (e.g. function, class). Single-letter kinds are expanded to common names; run ctags with `--fields=+K` for exact kind names",
+            },
+            "max_results": {
+                "type": "integer",
+                "minimum": 1,
+                "description": "Maximum number of results to return (default 50)",
+            }
+        },
+        "required": ["name"],
+        "examples": [
+            {"name": "parse", "kinds": ["function"]},
+            {"name": "Config", "tags_file": "build/tags", "path": "src", "match_substring": false}
+        ],
+        "x-positional-order": [
+            "name", "tags_file", "path", "match_substring", "case_sensitive", "kinds",
+            "max_results"
+        ],
+        "additionalProperties": false
+    });
+
+    #[derive(Deserialize)]
+    struct Params {
+        name: String,
+        #[serde(default)]
+        tags_file: Option<String>,
+        #[serde(default)]
+        path: Option<String>,
+        #[serde(default = "default_true")]
+        match_substring: bool,
+        #[serde(default)]
+        case_sensitive: bool,
+        #[serde(default)]
+        kinds: Option<Vec<String>>,
+        #[serde(default)]
+        max_results: Option<usize>,
+    }
+
+    let handler = move |params| -> Result<Value> {
+        let args: Params =
+            serde_json::from_value(params).context("Invalid arguments for query_ctags")?;
+        let tags_path = match &args.tags_file {
+            Some(file) => resolve_path(file)?,
+            None => find_tags_file()?,
+        };
+        let content = fs::read_to_string(&tags_path)
+            .with_context(|| format!("Failed to read tags file {}", tags_path.display()))?;
+        let tags_dir = tags_path.parent().unwrap_or(Path::new("")).to_path_buf();
+        let scope = args.path.as_deref().map(resolve_path).transpose()?;
+
+        let max_results = args.max_results.unwrap_or(50);
+        let kind_filter: Option<HashSet<String>> = args
+            .kinds
+            .as_ref()
+            .map(|kinds| kinds.iter().map(|s| s.to_lowercase()).collect());
+
+        let mut matches = Vec::new();
+        let mut truncated = false;
+        let mut malformed_lines = 0usize;
+        let mut sources = SourceCache::default();
+        for line in content.lines() {
+            if line.starts_with("!_TAG_") || line.trim().is_empty() {
+                continue;
+            }
+            let Some(tag) = parse_tag(line) else {
+                malformed_lines += 1;
+                continue;
+            };
+            if let Some(filter) = &kind_filter
+                && !tag
+                    .kind
+                    .as_ref()
+                    .is_some_and(|kind| filter.contains(&kind.to_lowercase()))
+            {
+                continue;
+            }
+            if !symbol_name_matches(
+                &tag.name,
+                &args.name,
+                args.match_substring,
+                args.case_sensitive,
+            ) {
+                continue;
+            }
+            let file = tags_dir.join(&tag.file);
+            if scope
+                .as_deref()
+                .is_some_and(|scope| !file.starts_with(scope))
+            {
+                continue;
+            }
+            if matches.len() >= max_results {
+                truncated = true;
+                break;
+            }
+
+            let line = tag.line.or_else(|| sources.locate(&file, &tag.address));
+            matches.push(json!({
+                "name": tag.name,
+                "kind": tag.kind,
+                "path": display_path(&file),
+                "line": line,
+                "signature": tag.signature,
+                "container": tag.container,
+                "language": tag.language,
+            }));
+        }
+
+        Ok(json!({
+            "query": args.name,
+            "tags_file": display_path(&tags_path),
+            "count": matches.len(),
+            "truncated": truncated,
+            "malformed_lines": malformed_lines,
+            "matches": matches,
+        }))
+    };
+
+    Tool::new(
+        "query_ctags",
+        "Search a ctags `tags` file (e.g. from universal-ctags) for symbol definitions, for languages or constructs the built-in parser misses",
+        schema,
+        Box::new(handler),
+    )
+    .resolves_paths()
+}
+
+fn find_tags_file() -> Result<PathBuf> {
+    let dir = base_dir()?;
+    DEFAULT_TAG_FILES
+        .iter()
+        .map(|name| dir.join(name))
+        .find(|path| path.is_file())
+        .with_context(|| {
+            format!(
+                "No tags file ({}) in {}; generate one with `ctags -R --fields=+nKS` or pass `tags_file`",
+                DEFAULT_TAG_FILES.join(", "),
+                dir.display()
+            )
+        })
+}
+
+/// Parse `name<TAB>file<TAB>address;"<TAB>fields...`. Fields are `key:value`
+/// pairs, except that a bare value is the kind.
+fn parse_tag(line: &str) -> Option<Tag> {
+    let mut parts = line.splitn(3, '\t');
+    let name = parts.next()?.to_string();
+    let file = parts.next()?.to_string();
+    let rest = parts.next()?;
+    if name.is_empty() || file.is_empty() {
+        return None;
+    }
+
+    // Search patterns may contain tabs, so the address ends at `;"` rather
+    // than the next tab.
+    let (address, fields) = match rest.find(";\"\t") {
+        Some(end) => (&rest[..end], &rest[end + 3..]),
+        None => (rest.strip_suffix(";\"").unwrap_or(rest), ""),
+    };
+
+    let mut tag = Tag {
+        name,
+        file,
+        address: address.to_string(),
+        kind: None,
+        line: address.trim().parse().ok(),
+        container: None,
+        signature: None,
+        language: None,
+    };
+    for field in fields.split('\t').filter(|field| !field.is_empty()) {
+        match field.split_once(':') {
+            Some(("kind", kind)) => tag.kind = Some(expand_kind(kind)),
+            Some(("line", line)) => tag.line = line.parse().ok().or(tag.line),
+            Some(("signature", signature)) => tag.signature = Some(signature.to_string()),
+            Some(("language", language)) => tag.language = Some(language.to_string()),
+            Some((key, scope)) if SCOPE_FIELDS.contains(&key) => {
+                tag.container = Some(scope.to_string());
+            }
+            Some(_) => {}
+            None => tag.kind = Some(expand_kind(field)),
+        }
+    }
+    Some(tag)
+}
+
+/// Common meaning of the single-letter kinds ctags writes by default. Full
+/// kind names pass through unchanged.
+fn expand_kind(kind: &str) -> String {
+    let expanded = match kind {
+        "c" => "class",
+        "d" => "macro",
+        "e" => "enumerator",
+        "f" => "function",
+        "g" => "enum",
+        "i" => "interface",
+        "m" => "member",
+        "n" => "namespace",
+        "p" => "prototype",
+        "s" => "struct",
+        "t" => "typedef",
+        "u" => "union",
+        "v" => "variable",
+        other => other,
+    };
+    expanded.to_string()
+}
+
+/// Source files read while resolving pattern addresses, so each is read at
+/// most once per query.
+#[derive(Default)]
+struct SourceCache {
+    files: HashMap<PathBuf, Option<Vec<String>>>,
+}
+
+impl SourceCache {
+    /// 1-based line matched by a `/^text$/` or `?^text$?` address, when the
+    /// file is readable and still contains it.
+    fn locate(&mut self, path: &Path, address: &str) -> Option<usize> {
+        let pattern = address
+            .strip_prefix('/')
+            .and_then(|rest| rest.strip_suffix('/'))
+            .or_else(|| {
+                address
+                    .strip_prefix('?')
+                    .and_then(|rest| rest.strip_suffix('?'))
+            })?;
+        let anchored_start = pattern.starts_with('^');
+        let pattern = pattern.strip_prefix('^').unwrap_or(pattern);
+        let (pattern, anchored_end) = match pattern.strip_suffix('$') {
+            Some(pattern) if !pattern.ends_with('\\') => (pattern, true),
+            _ => (pattern, false),
+        };
+        let text = unescape_pattern(pattern);
+
+        let lines = self
+            .files
+            .entry(path.to_path_buf())
+            .or_insert_with(|| {
+                fs::read_to_string(path)
+                    .ok()
+                    .map(|content| content.lines().map(str::to_string).collect())
+            })
+            .as_ref()?;
+        lines
+            .iter()
+            .position(|line| match (anchored_start, anchored_end) {
+                (true, true) => *line == text,
+                (true, false) => line.starts_with(&text),
+                (false, true) => line.ends_with(&text),
+                (false, false) => line.contains(&text),
+            })
+            .map(|index| index + 1)
+    }
+}
+
+/// Undo the escaping ctags applies to `/`, `?` and `\` in search patterns.
+fn unescape_pattern(pattern: &str) -> String {
+    let mut text = String::with_capacity(pattern.len());
+    let mut chars = pattern.chars();
+    while let Some(ch) = chars.next() {
+        if ch == '\\'
+            && let Some(next) = chars.next()
+        {
+            if !matches!(next, '\\' | '/' | '?') {
+                text.push(ch);
+            }
+            text.push(next);
+        } else {
+            text.push(ch);
+        }
+    }
+    text
+}
+
+fn default_true() -> bool {
+    true
+}
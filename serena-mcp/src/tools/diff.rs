@@ -0,0 +1,152 @@
+/// Past this many cells the line-matching table is skipped and the changed
+/// region is shown as one removal followed by one insertion.
+const MAX_LCS_CELLS: usize = 4_000_000;
+
+#[derive(Clone, Copy)]
+enum Op {
+    Equal(usize, usize),
+    Delete(usize),
+    Insert(usize),
+}
+
+/// Unified diff of `old` against `new`, with `context` unchanged lines around
+/// each change and `---`/`+++` headers naming `label`. Empty when the texts
+/// are equal.
+pub(crate) fn unified_diff(old: &str, new: &str, label: &str, context: usize) -> String {
+    let a = old.split_inclusive('\n').collect::<Vec<_>>();
+    let b = new.split_inclusive('\n').collect::<Vec<_>>();
+    let ops = diff_lines(&a, &b);
+
+    let changes = ops
+        .iter()
+        .enumerate()
+        .filter(|(_, op)| !matches!(op, Op::Equal(..)))
+        .map(|(index, _)| index)
+        .collect::<Vec<_>>();
+    let Some(&first) = changes.first() else {
+        return String::new();
+    };
+
+    // Group changes whose context windows touch into one hunk each.
+    let mut hunks = Vec::new();
+    let mut start = first.saturating_sub(context);
+    let mut end = first + 1;
+    for &change in &changes[1..] {
+        if change.saturating_sub(context) > end + context {
+            hunks.push((start, (end + context).min(ops.len())));
+            start = change.saturating_sub(context);
+        }
+        end = change + 1;
+    }
+    hunks.push((start, (end + context).min(ops.len())));
+
+    let mut out = format!("--- {label}\n+++ {label}\n");
+    for (start, end) in hunks {
+        let hunk = &ops[start..end];
+        // Lines of each side before the hunk, i.e. its 0-based start.
+        let (old_before, new_before) =
+            ops[..start].iter().fold((0, 0), |(old, new), op| match op {
+                Op::Equal(..) => (old + 1, new + 1),
+                Op::Delete(_) => (old + 1, new),
+                Op::Insert(_) => (old, new + 1),
+            });
+        let old_len = hunk
+            .iter()
+            .filter(|op| !matches!(op, Op::Insert(_)))
+            .count();
+        let new_len = hunk
+            .iter()
+            .filter(|op| !matches!(op, Op::Delete(_)))
+            .count();
+        out.push_str(&format!(
+            "@@ -{} +{} @@\n",
+            hunk_range(old_before, old_len),
+            hunk_range(new_before, new_len)
+        ));
+
+        for op in hunk {
+            let (marker, line) = match *op {
+                Op::Equal(i, _) => (' ', a[i]),
+                Op::Delete(i) => ('-', a[i]),
+                Op::Insert(j) => ('+', b[j]),
+            };
+            out.push(marker);
+            out.push_str(line);
+            if !line.ends_with('\n') {
+                out.push_str("\n\\ No newline at end of file\n");
+            }
+        }
+    }
+    out
+}
+
+/// `start,len` as written in a hunk header; an empty range names the line
+/// before it.
+fn hunk_range(before: usize, len: usize) -> String {
+    if len == 0 {
+        format!("{before},0")
+    } else {
+        format!("{},{len}", before + 1)
+    }
+}
+
+/// Edit script turning `a` into `b`: common prefix and suffix are matched
+/// directly and the rest by longest common subsequence.
+fn diff_lines(a: &[&str], b: &[&str]) -> Vec<Op> {
+    let prefix = a.iter().zip(b).take_while(|(x, y)| x == y).count();
+    let suffix = a[prefix..]
+        .iter()
+        .rev()
+        .zip(b[prefix..].iter().rev())
+        .take_while(|(x, y)| x == y)
+        .count();
+    let (a_mid, b_mid) = (&a[prefix..a.len() - suffix], &b[prefix..b.len() - suffix]);
+
+    let mut ops = (0..prefix).map(|i| Op::Equal(i, i)).collect::<Vec<_>>();
+    if a_mid.len().saturating_mul(b_mid.len()) > MAX_LCS_CELLS {
+        ops.extend((0..a_mid.len()).map(|i| Op::Delete(prefix + i)));
+        ops.extend((0..b_mid.len()).map(|j| Op::Insert(prefix + j)));
+    } else {
+        ops.extend(lcs_ops(a_mid, b_mid).into_iter().map(|op| match op {
+            Op::Equal(i, j) => Op::Equal(prefix + i, prefix + j),
+            Op::Delete(i) => Op::Delete(prefix + i),
+            Op::Insert(j) => Op::Insert(prefix + j),
+        }));
+    }
+    ops.extend((0..suffix).map(|k| Op::Equal(a.len() - suffix + k, b.len() - suffix + k)));
+    ops
+}
+
+fn lcs_ops(a: &[&str], b: &[&str]) -> Vec<Op> {
+    let width = b.len() + 1;
+    // `table[i * width + j]`: LCS length of `a[i..]` and `b[j..]`.
+    let mut table = vec![0u32; (a.len() + 1) * width];
+    for i in (0..a.len()).rev() {
+        for j in (0..b.len()).rev() {
+            table[i * width + j] = if a[i] == b[j] {
+                table[(i + 1) * width + j + 1] + 1
+            } else {
+                table[(i + 1) * width + j].max(table[i * width + j + 1])
+            };
+        }
+    }
+
+    let (mut i, mut j) = (0, 0);
+    let mut ops = Vec::with_capacity(a.len() + b.len());
+    while i < a.len() && j < b.len() {
+        if a[i] == b[j] {
+            ops.push(Op::Equal(i, j));
+            i += 1;
+            j += 1;
+        } else if table[(i + 1) * width + j] >= table[i * width + j + 1] {
+            ops.push(Op::Delete(i));
+            i += 1;
+        } else {
+            ops.push(Op::Insert(j));
+            j += 1;
+        }
+    }
+    ops.extend((i..a.len()).map(Op::Delete));
+    ops.extend((j..b.len()).map(Op::Insert));
+    ops
+}
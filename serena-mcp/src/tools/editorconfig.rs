@@ -0,0 +1,120 @@
+use std::collections::HashMap;
+use std::fs;
+use std::path::Path;
+
+use globset::GlobBuilder;
+
+const EDITORCONFIG: &str = ".editorconfig";
+
+/// Indentation unit `.editorconfig` prescribes for `path`: a tab for
+/// `indent_style = tab`, or `indent_size` (falling back to `tab_width`) spaces
+/// for `indent_style = space`. `None` when no file sets a usable style, so
+/// callers keep deriving indentation from the surrounding code.
+pub(crate) fn indent_unit(path: &Path) -> Option<String> {
+    let properties = properties_for(path);
+    match properties.get("indent_style").map(String::as_str) {
+        Some("tab") => Some("\t".to_string()),
+        Some("space") => {
+            let size = properties
+                .get("indent_size")
+                .filter(|size| *size != "tab")
+                .or_else(|| properties.get("tab_width"))?
+                .parse::<usize>()
+                .ok()
+                .filter(|size| (1..=16).contains(size))?;
+            Some(" ".repeat(size))
+        }
+        _ => None,
+    }
+}
+
+/// Lowercased properties applying to `path`, from every `.editorconfig`
+/// between its directory and the nearest one declaring `root = true`. Closer
+/// files, and later sections within a file, take precedence.
+fn properties_for(path: &Path) -> HashMap<String, String> {
+    let mut configs = Vec::new();
+    for dir in path.ancestors().skip(1) {
+        let Ok(content) = fs::read_to_string(dir.join(EDITORCONFIG)) else {
+            continue;
+        };
+        let config = parse(&content);
+        let is_root = config.root;
+        configs.push((dir, config));
+        if is_root {
+            break;
+        }
+    }
+
+    let mut properties = HashMap::new();
+    for (dir, config) in configs.into_iter().rev() {
+        let Ok(relative) = path.strip_prefix(dir) else {
+            continue;
+        };
+        let relative = relative.to_string_lossy().replace('\\', "/");
+        for section in config.sections {
+            if section_matches(&section.glob, &relative) {
+                properties.extend(section.properties);
+            }
+        }
+    }
+    properties
+}
+
+struct EditorConfig {
+    root: bool,
+    sections: Vec<Section>,
+}
+
+struct Section {
+    glob: String,
+    properties: Vec<(String, String)>,
+}
+
+fn parse(content: &str) -> EditorConfig {
+    let mut config = EditorConfig {
+        root: false,
+        sections: Vec::new(),
+    };
+
+    for line in content.lines().map(str::trim) {
+        if line.is_empty() || line.starts_with('#') || line.starts_with(';') {
+            continue;
+        }
+        if let Some(glob) = line
+            .strip_prefix('[')
+            .and_then(|rest| rest.strip_suffix(']'))
+        {
+            config.sections.push(Section {
+                glob: glob.to_string(),
+                properties: Vec::new(),
+            });
+            continue;
+        }
+        let Some((key, value)) = line.split_once('=') else {
+            continue;
+        };
+        let key = key.trim().to_lowercase();
+        let value = value.trim().to_lowercase();
+        match config.sections.last_mut() {
+            Some(section) => section.properties.push((key, value)),
+            None if key == "root" => config.root = value == "true",
+            None => {}
+        }
+    }
+    config
+}
+
+/// Whether a section glob matches `relative` (the path below the
+/// `.editorconfig`'s directory). Globs without a `/` match file names at any
+/// depth; `*` stops at `/` while `**` crosses it.
+fn section_matches(glob: &str, relative: &str) -> bool {
+    let pattern = if glob.contains('/') {
+        glob.trim_start_matches('/').to_string()
+    } else {
+        format!("**/{glob}")
+    };
+    GlobBuilder::new(&pattern)
+        .literal_separator(true)
+        .build()
+        .is_ok_and(|glob| glob.compile_matcher().is_match(relative))
+}
@@ -1,21 +1,51 @@
+use std::collections::HashMap;
 use std::fs::{self, OpenOptions};
-use std::io::Write;
+use std::io::{Read, Write};
+use std::ops::Range;
 use std::path::{Path, PathBuf};
+use std::time::SystemTime;
 
 use anyhow::{Context, Result};
-use regex::RegexBuilder;
-use serde::Deserialize;
+use base64::Engine;
+use base64::engine::general_purpose::STANDARD as BASE64;
+use memchr::memmem::Finder;
+use once_cell::sync::Lazy;
+use regex::{NoExpand, Regex, RegexBuilder};
+use serde::{Deserialize, Serialize};
 use serde_json::{Value, json};
+use time::OffsetDateTime;
+use time::format_description::well_known::Rfc3339;
 use walkdir::WalkDir;
 
+use crate::notifications::{ProgressReporter, ResultStream};
+use crate::path_style::display_path;
 use crate::tool::{Tool, ToolRegistry};
-use crate::tools::resolve_path;
+use crate::tools::diff::unified_diff;
+use crate::tools::journal;
+use crate::tools::symbols::Language;
+use crate::tools::syntax::{SpanKind, comment_syntax, lexical_spans, lexical_spans_with};
+use crate::tools::workflow::language_from_extension;
+use crate::tools::{
+    ContextWindow, DEFAULT_IGNORED_DIRS, DEFAULT_SEARCH_MAX_DEPTH, DEFAULT_SEARCH_MAX_FILES,
+    ExcludeSet, FileWalker, SearchRoots, WalkFilter, allow_entry, base_dir, normalize_extensions,
+    reject_empty_match, resolve_path, search_roots_schema,
+};
 
 pub fn register(registry: &mut ToolRegistry) {
     registry.register(read_file_tool());
+    registry.register(read_file_bytes_tool());
     registry.register(list_dir_tool());
+    registry.register(render_tree_tool());
     registry.register(write_file_tool());
+    registry.register(copy_file_tool());
+    registry.register(touch_tool());
     registry.register(search_pattern_tool());
+    registry.register(preview_replace_tool());
+    registry.register(replace_in_files_tool());
+    registry.register(file_stats_tool());
+    registry.register(detect_style_tool());
+    registry.register(extract_literals_tool());
+    registry.register(changed_since_tool());
 }
 
 #[derive(Debug, Deserialize)]
@@ -23,6 +53,8 @@ struct ReadFileParams {
     path: String,
     #[serde(default)]
     max_bytes: Option<usize>,
+    #[serde(default)]
+    with_line_numbers: bool,
 }
 
 fn read_file_tool() -> Tool {
@@ -37,34 +69,51 @@ fn read_file_tool() -> Tool {
                 "type": "integer",
                 "minimum": 1,
                 "description": "Optional soft limit. If the file is larger, content is truncated.",
+            },
+            "with_line_numbers": {
+                "type": "boolean",
+                "description": "Prefix each line with its right-aligned 1-based number (`N: text`)",
+                "default": false,
             }
         },
         "required": ["path"],
+        "examples": [{"path": "src/main.rs", "with_line_numbers": true}],
+        "x-positional-order": ["path", "max_bytes", "with_line_numbers"],
         "additionalProperties": false
     });
 
     let handler = move |params| -> Result<_> {
         let args: ReadFileParams =
             serde_json::from_value(params).context("Invalid arguments for read_file")?;
+        reject_stdio_path(&args.path)?;
         let path = resolve_path(&args.path)?;
-        let display_path = path.to_string_lossy().to_string();
-        let content =
-            fs::read_to_string(&path).with_context(|| format!("Failed to read {display_path}"))?;
+        let content = fs::read_to_string(&path)
+            .with_context(|| format!("Failed to read {}", path.display()))?;
+        let line_count = content.lines().count();
+        let content = if args.with_line_numbers {
+            number_lines(&content, line_count)
+        } else {
+            content
+        };
 
         let (content, truncated) = match args.max_bytes {
             Some(limit) if content.len() > limit => {
                 let mut slice = content[..limit].to_string();
-                slice.push_str("…");
+                slice.push('…');
                 (slice, true)
             }
             _ => (content, false),
         };
 
-        Ok(json!({
-            "path": display_path,
+        let mut response = json!({
+            "path": display_path(&path),
             "content": content,
             "truncated": truncated,
-        }))
+        });
+        if args.with_line_numbers {
+            response["line_count"] = json!(line_count);
+        }
+        Ok(response)
     };
 
     Tool::new(
@@ -73,6 +122,158 @@ fn read_file_tool() -> Tool {
         schema,
         Box::new(handler),
     )
+    .resolves_paths()
+}
+
+/// Default and hard upper bound on bytes returned by `read_file_bytes`; the
+/// base64 payload is a third larger.
+const READ_BYTES_DEFAULT: usize = 64 * 1024;
+const READ_BYTES_CAP: usize = 1024 * 1024;
+
+/// Leading bytes inspected by [`guess_mime`].
+const SNIFF_BYTES: usize = 16;
+
+fn read_file_bytes_tool() -> Tool {
+    let schema = json!({
+        "type": "object",
+        "properties": {
+            "path": {
+                "type": "string",
+                "description": "Absolute or relative filesystem path to read",
+            },
+            "max_bytes": {
+                "type": "integer",
+                "minimum": 1,
+                "maximum": READ_BYTES_CAP,
+                "description": "Return at most this many bytes from the start of the file (default 65536, at most 1048576)",
+            }
+        },
+        "required": ["path"],
+        "examples": [{"path": "assets/favicon.ico", "max_bytes": 4096}],
+        "x-positional-order": ["path", "max_bytes"],
+        "additionalProperties": false
+    });
+
+    #[derive(Deserialize)]
+    struct Params {
+        path: String,
+        #[serde(default)]
+        max_bytes: Option<usize>,
+    }
+
+    let handler = move |params| -> Result<Value> {
+        let args: Params =
+            serde_json::from_value(params).context("Invalid arguments for read_file_bytes")?;
+        reject_stdio_path(&args.path)?;
+        let path = resolve_path(&args.path)?;
+        if !path.is_file() {
+            anyhow::bail!("{} is not a file", path.display());
+        }
+        let limit = args
+            .max_bytes
+            .unwrap_or(READ_BYTES_DEFAULT)
+            .min(READ_BYTES_CAP);
+
+        let file =
+            fs::File::open(&path).with_context(|| format!("Failed to open {}", path.display()))?;
+        let size = file
+            .metadata()
+            .with_context(|| format!("Failed to read metadata for {}", path.display()))?
+            .len();
+        // Read enough to recognise magic numbers even for tiny limits.
+        let mut bytes = Vec::with_capacity(limit.max(SNIFF_BYTES).min(size as usize));
+        file.take(limit.max(SNIFF_BYTES) as u64)
+            .read_to_end(&mut bytes)
+            .with_context(|| format!("Failed to read {}", path.display()))?;
+        let mime = guess_mime(&path, &bytes);
+        bytes.truncate(limit);
+
+        Ok(json!({
+            "path": display_path(&path),
+            "size": size,
+            "bytes_returned": bytes.len(),
+            "truncated": (bytes.len() as u64) < size,
+            "mime": mime,
+            "encoding": "base64",
+            "content": BASE64.encode(&bytes),
+        }))
+    };
+
+    Tool::new(
+        "read_file_bytes",
+        "Read the leading bytes of a (possibly binary) file as base64 with a MIME type guess",
+        schema,
+        Box::new(handler),
+    )
+    .resolves_paths()
+}
+
+/// MIME type from well-known magic numbers, then the file extension, then
+/// whether the bytes look like UTF-8 text.
+fn guess_mime(path: &Path, bytes: &[u8]) -> &'static str {
+    const MAGIC: &[(&[u8], &str)] = &[
+        (b"\x89PNG\r\n\x1a\n", "image/png"),
+        (b"\xff\xd8\xff", "image/jpeg"),
+        (b"GIF87a", "image/gif"),
+        (b"GIF89a", "image/gif"),
+        (b"\x00\x00\x01\x00", "image/x-icon"),
+        (b"%PDF-", "application/pdf"),
+        (b"PK\x03\x04", "application/zip"),
+        (b"\x1f\x8b", "application/gzip"),
+        (b"\x7fELF", "application/x-elf"),
+        (b"\0asm", "application/wasm"),
+        (b"wOFF", "font/woff"),
+        (b"wOF2", "font/woff2"),
+    ];
+    if let Some((_, mime)) = MAGIC.iter().find(|(magic, _)| bytes.starts_with(magic)) {
+        return mime;
+    }
+    if bytes.starts_with(b"RIFF") && bytes.get(8..12) == Some(b"WEBP") {
+        return "image/webp";
+    }
+
+    let ext = path
+        .extension()
+        .map(|ext| ext.to_string_lossy().to_lowercase())
+        .unwrap_or_default();
+    match ext.as_str() {
+        "svg" => return "image/svg+xml",
+        "json" => return "application/json",
+        "html" | "htm" => return "text/html",
+        "css" => return "text/css",
+        "js" | "mjs" => return "text/javascript",
+        _ => {}
+    }
+
+    // A character cut off by the read limit still counts as text.
+    match std::str::from_utf8(bytes) {
+        Ok(_) => "text/plain",
+        Err(err) if err.error_len().is_none() => "text/plain",
+        Err(_) => "application/octet-stream",
+    }
+}
+
+/// `-` conventionally means stdin/stdout, but the stdio transport owns both
+/// streams for JSON-RPC framing. Refuse it rather than touching a file that is
+/// literally named `-`.
+fn reject_stdio_path(path: &str) -> Result<()> {
+    if path.trim() == "-" {
+        anyhow::bail!(
+            "Path '-' (stdin/stdout) is unavailable: the stdio transport reserves both streams for JSON-RPC messages. Pass a file path instead (use './-' for a file named '-')"
+        );
+    }
+    Ok(())
+}
+
+/// Render `content` as `N: text` lines with numbers right-aligned to the
+/// width of the largest line number.
+fn number_lines(content: &str, line_count: usize) -> String {
+    let width = line_count.max(1).to_string().len();
+    let mut numbered = String::with_capacity(content.len() + line_count * (width + 2));
+    for (idx, line) in content.lines().enumerate() {
+        numbered.push_str(&format!("{:>width$}: {line}\n", idx + 1));
+    }
+    numbered
 }
 
 #[derive(Debug, Deserialize)]
@@ -83,6 +284,8 @@ struct ListDirParams {
     max_entries: Option<usize>,
     #[serde(default)]
     include_hidden: Option<bool>,
+    #[serde(default)]
+    depth: Option<usize>,
 }
 
 fn list_dir_tool() -> Tool {
@@ -96,14 +299,22 @@ fn list_dir_tool() -> Tool {
             "max_entries": {
                 "type": "integer",
                 "minimum": 1,
-                "description": "Optional maximum number of entries to return",
+                "description": "Optional maximum number of entries to return, counted across the whole tree",
             },
             "include_hidden": {
                 "type": "boolean",
                 "description": "Whether to include dotfiles and dot-directories",
                 "default": false,
+            },
+            "depth": {
+                "type": "integer",
+                "minimum": 1,
+                "description": "Levels to list. Directories above the last level carry their entries in `children`",
+                "default": 1,
             }
         },
+        "examples": [{"path": "src", "max_entries": 50}, {"path": ".", "depth": 2}],
+        "x-positional-order": ["path", "max_entries", "include_hidden", "depth"],
         "additionalProperties": false
     });
 
@@ -112,24 +323,71 @@ fn list_dir_tool() -> Tool {
             serde_json::from_value(params).context("Invalid arguments for list_dir")?;
         let dir_path = match args.path {
             Some(path) => resolve_path(&path)?,
-            None => std::env::current_dir()?,
+            None => base_dir()?,
         };
-        let dir_display = dir_path.to_string_lossy().to_string();
-        let max_entries = args.max_entries.unwrap_or(usize::MAX);
-        let include_hidden = args.include_hidden.unwrap_or(false);
+        let mut listing = DirListing {
+            include_hidden: args.include_hidden.unwrap_or(false),
+            remaining: args.max_entries.unwrap_or(usize::MAX),
+            truncated: false,
+            skipped: Vec::new(),
+        };
+        let entries = listing.list(&dir_path, args.depth.unwrap_or(1))?;
 
-        let mut entries = Vec::new();
-        let read_dir = fs::read_dir(&dir_path)
-            .with_context(|| format!("Failed to list directory {dir_display}"))?;
+        Ok(json!({
+            "path": display_path(&dir_path),
+            "entries": entries,
+            "truncated": listing.truncated,
+            "skipped": listing.skipped,
+        }))
+    };
+
+    Tool::new(
+        "list_dir",
+        "List directory entries with basic metadata",
+        schema,
+        Box::new(handler),
+    )
+    .resolves_paths()
+}
+
+/// Depth-limited directory walk for `list_dir` sharing one entry budget
+/// across the whole tree.
+struct DirListing {
+    include_hidden: bool,
+    remaining: usize,
+    truncated: bool,
+    /// Entries below the listed directory that could not be read.
+    skipped: Vec<Value>,
+}
 
-        for entry in read_dir {
-            let entry = entry?;
+impl DirListing {
+    /// Entries of `dir` sorted by name; directories gain `children` while
+    /// `depth` allows. Symlinked directories are not followed.
+    fn list(&mut self, dir: &Path, depth: usize) -> Result<Vec<Value>> {
+        let mut dir_entries = fs::read_dir(dir)
+            .with_context(|| format!("Failed to list directory {}", dir.display()))?
+            .collect::<std::io::Result<Vec<_>>>()?;
+        dir_entries.sort_by_key(|entry| entry.file_name());
+
+        let mut entries = Vec::new();
+        for entry in dir_entries {
             let name = entry.file_name().to_string_lossy().to_string();
-            if !include_hidden && name.starts_with('.') {
+            if !self.include_hidden && name.starts_with('.') {
                 continue;
             }
+            if self.remaining == 0 {
+                self.truncated = true;
+                break;
+            }
+            self.remaining -= 1;
 
-            let metadata = entry.metadata()?;
+            let metadata = match entry.metadata() {
+                Ok(metadata) => metadata,
+                Err(err) => {
+                    self.skipped.push(skipped_entry(&entry.path(), &err.into()));
+                    continue;
+                }
+            };
             let file_type = metadata.file_type();
             let entry_type = if file_type.is_dir() {
                 "directory"
@@ -141,28 +399,179 @@ fn list_dir_tool() -> Tool {
                 "other"
             };
 
-            entries.push(json!({
+            let mut value = json!({
                 "name": name,
                 "type": entry_type,
-            }));
+            });
+            if file_type.is_dir() && depth > 1 {
+                match self.list(&entry.path(), depth - 1) {
+                    Ok(children) => value["children"] = json!(children),
+                    Err(err) => self.skipped.push(skipped_entry(&entry.path(), &err)),
+                }
+            }
+            entries.push(value);
+        }
+        Ok(entries)
+    }
+}
 
-            if entries.len() >= max_entries {
-                break;
+/// A `skipped` item for a directory entry a listing could not read.
+fn skipped_entry(path: &Path, err: &anyhow::Error) -> Value {
+    json!({
+        "path": display_path(path),
+        "reason": err.root_cause().to_string(),
+    })
+}
+
+fn render_tree_tool() -> Tool {
+    let schema = json!({
+        "type": "object",
+        "properties": {
+            "path": {
+                "type": "string",
+                "description": "Directory to render; defaults to current directory",
+            },
+            "max_depth": {
+                "type": "integer",
+                "minimum": 1,
+                "description": "Levels below `path` to show",
+                "default": 3,
+            },
+            "include_hidden": {
+                "type": "boolean",
+                "description": "Whether to include dotfiles and dot-directories",
+                "default": false,
+            },
+            "max_entries": {
+                "type": "integer",
+                "minimum": 1,
+                "description": "Stop after this many entries, marking where the tree was cut",
+                "default": 500,
             }
+        },
+        "examples": [{"path": "src", "max_depth": 2}],
+        "x-positional-order": ["path", "max_depth", "include_hidden", "max_entries"],
+        "additionalProperties": false
+    });
+
+    #[derive(Deserialize)]
+    struct Params {
+        #[serde(default)]
+        path: Option<String>,
+        #[serde(default)]
+        max_depth: Option<usize>,
+        #[serde(default)]
+        include_hidden: bool,
+        #[serde(default)]
+        max_entries: Option<usize>,
+    }
+
+    let handler = move |params| -> Result<Value> {
+        let args: Params =
+            serde_json::from_value(params).context("Invalid arguments for render_tree")?;
+        let root = match args.path {
+            Some(path) => resolve_path(&path)?,
+            None => base_dir()?,
+        };
+        if !root.is_dir() {
+            anyhow::bail!("{} is not a directory", root.display());
         }
 
+        let mut tree = TreeRenderer {
+            include_hidden: args.include_hidden,
+            remaining: args.max_entries.unwrap_or(500),
+            truncated: false,
+            directories: 0,
+            files: 0,
+            skipped: Vec::new(),
+            out: format!("{}\n", display_path(&root)),
+        };
+        tree.render(&root, "", args.max_depth.unwrap_or(3))?;
+
         Ok(json!({
-            "path": dir_display,
-            "entries": entries,
+            "path": display_path(&root),
+            "tree": tree.out,
+            "directories": tree.directories,
+            "files": tree.files,
+            "truncated": tree.truncated,
+            "skipped": tree.skipped,
         }))
     };
 
     Tool::new(
-        "list_dir",
-        "List directory entries with basic metadata",
+        "render_tree",
+        "Render a directory as a `tree`-style ASCII diagram, skipping build and VCS directories such as target, node_modules and .git",
         schema,
         Box::new(handler),
     )
+    .resolves_paths()
+    .cacheable()
+}
+
+/// Depth-limited `tree`-style rendering for `render_tree`, sharing one entry
+/// budget across the whole tree.
+struct TreeRenderer {
+    include_hidden: bool,
+    remaining: usize,
+    truncated: bool,
+    directories: usize,
+    files: usize,
+    /// Entries below the rendered directory that could not be read.
+    skipped: Vec<Value>,
+    out: String,
+}
+
+impl TreeRenderer {
+    /// Append the entries of `dir`, sorted by name, each line starting with
+    /// `prefix`. Directories end in `/` and are descended while `depth`
+    /// allows; symlinked directories are not followed.
+    fn render(&mut self, dir: &Path, prefix: &str, depth: usize) -> Result<()> {
+        let mut entries = fs::read_dir(dir)
+            .with_context(|| format!("Failed to list directory {}", dir.display()))?
+            .collect::<std::io::Result<Vec<_>>>()?;
+        entries.retain(|entry| {
+            let name = entry.file_name();
+            let name = name.to_string_lossy();
+            let is_dir = entry.file_type().is_ok_and(|kind| kind.is_dir());
+            (self.include_hidden || !name.starts_with('.'))
+                && !(is_dir && DEFAULT_IGNORED_DIRS.contains(&name.as_ref()))
+        });
+        entries.sort_by_key(|entry| entry.file_name());
+
+        for (index, entry) in entries.iter().enumerate() {
+            let last = index + 1 == entries.len();
+            if self.remaining == 0 {
+                self.truncated = true;
+                self.out.push_str(&format!("{prefix}└── …\n"));
+                return Ok(());
+            }
+            self.remaining -= 1;
+
+            let connector = if last { "└── " } else { "├── " };
+            let name = entry.file_name().to_string_lossy().to_string();
+            let is_dir = match entry.file_type() {
+                Ok(kind) => kind.is_dir(),
+                Err(err) => {
+                    self.skipped.push(skipped_entry(&entry.path(), &err.into()));
+                    continue;
+                }
+            };
+            if is_dir {
+                self.directories += 1;
+                self.out.push_str(&format!("{prefix}{connector}{name}/\n"));
+                if depth > 1 {
+                    let child_prefix = format!("{prefix}{}", if last { "    " } else { "│   " });
+                    if let Err(err) = self.render(&entry.path(), &child_prefix, depth - 1) {
+                        self.skipped.push(skipped_entry(&entry.path(), &err));
+                    }
+                }
+            } else {
+                self.files += 1;
+                self.out.push_str(&format!("{prefix}{connector}{name}\n"));
+            }
+        }
+        Ok(())
+    }
 }
 
 #[derive(Debug, Deserialize)]
@@ -175,6 +584,70 @@ struct WriteFileParams {
     create_dirs: bool,
     #[serde(default)]
     ensure_trailing_newline: bool,
+    #[serde(default)]
+    normalize: bool,
+    #[serde(default)]
+    line_ending: Option<LineEnding>,
+}
+
+/// Line terminator written by [`normalize_whitespace`] and by edits that
+/// keep a file's existing line endings.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub(crate) enum LineEnding {
+    #[default]
+    Lf,
+    Crlf,
+}
+
+impl LineEnding {
+    pub(crate) fn as_str(self) -> &'static str {
+        match self {
+            LineEnding::Lf => "\n",
+            LineEnding::Crlf => "\r\n",
+        }
+    }
+
+    /// The ending most lines of `content` use; LF on a tie, including for
+    /// content without line breaks.
+    pub(crate) fn detect(content: &str) -> Self {
+        let (lf_lines, crlf_lines) = line_ending_counts(content);
+        if crlf_lines > lf_lines {
+            LineEnding::Crlf
+        } else {
+            LineEnding::Lf
+        }
+    }
+
+    /// `text` with every line break, LF or CRLF, written as this ending.
+    pub(crate) fn apply(self, text: &str) -> String {
+        let lf = text.replace("\r\n", "\n");
+        match self {
+            LineEnding::Lf => lf,
+            LineEnding::Crlf => lf.replace('\n', "\r\n"),
+        }
+    }
+}
+
+/// Lines of `content` ending in a bare LF and in CRLF.
+fn line_ending_counts(content: &str) -> (usize, usize) {
+    let crlf_lines = content.matches("\r\n").count();
+    (content.matches('\n').count() - crlf_lines, crlf_lines)
+}
+
+/// Strip trailing whitespace from every line, terminate lines with `ending`
+/// and end with exactly one line ending. Blank-only content becomes empty.
+pub(crate) fn normalize_whitespace(content: &str, ending: LineEnding) -> String {
+    let mut lines: Vec<&str> = content.split('\n').map(str::trim_end).collect();
+    while lines.last().is_some_and(|line| line.is_empty()) {
+        lines.pop();
+    }
+    let mut normalized = String::with_capacity(content.len());
+    for line in lines {
+        normalized.push_str(line);
+        normalized.push_str(ending.as_str());
+    }
+    normalized
 }
 
 fn write_file_tool() -> Tool {
@@ -203,24 +676,50 @@ fn write_file_tool() -> Tool {
                 "type": "boolean",
                 "description": "Guarantee that the file ends with a newline",
                 "default": false,
+            },
+            "normalize": {
+                "type": "boolean",
+                "description": "Strip trailing whitespace, rewrite line endings as `line_ending` and end with a single newline",
+                "default": false,
+            },
+            "line_ending": {
+                "type": "string",
+                "enum": ["lf", "crlf"],
+                "description": "Line ending written when `normalize` is set (default lf)",
             }
         },
         "required": ["path", "content"],
+        "examples": [
+            {"path": "notes/todo.md", "content": "- ship it\n", "create_dirs": true},
+            {"path": "src/lib.rs", "content": "pub fn ok() {}  \r\n", "normalize": true, "line_ending": "lf"}
+        ],
+        "x-positional-order": [
+            "path", "content", "append", "create_dirs", "ensure_trailing_newline", "normalize",
+            "line_ending"
+        ],
         "additionalProperties": false
     });
 
     let handler = move |params| -> Result<Value> {
         let args: WriteFileParams =
             serde_json::from_value(params).context("Invalid arguments for write_file")?;
+        reject_stdio_path(&args.path)?;
         let path = resolve_path(&args.path)?;
-        if args.create_dirs {
-            if let Some(parent) = path.parent() {
-                fs::create_dir_all(parent)
-                    .with_context(|| format!("Failed to create parent directories for {path:?}"))?;
-            }
+        if args.create_dirs
+            && let Some(parent) = path.parent()
+        {
+            fs::create_dir_all(parent)
+                .with_context(|| format!("Failed to create parent directories for {path:?}"))?;
+        }
+
+        if args.line_ending.is_some() && !args.normalize {
+            anyhow::bail!("`line_ending` requires `normalize: true`");
         }
 
         let mut content = args.content;
+        if args.normalize {
+            content = normalize_whitespace(&content, args.line_ending.unwrap_or_default());
+        }
         if args.ensure_trailing_newline && !content.ends_with('\n') {
             content.push('\n');
         }
@@ -238,11 +737,13 @@ fn write_file_tool() -> Tool {
             .with_context(|| format!("Failed to open {}", path.to_string_lossy()))?;
         file.write_all(content.as_bytes())
             .with_context(|| format!("Failed writing to {}", path.to_string_lossy()))?;
+        let operation = if args.append { "append" } else { "overwrite" };
+        journal::record_edit("write_file", &path, Vec::new(), Some(operation.to_string()));
 
         Ok(json!({
-            "path": path.to_string_lossy(),
+            "path": display_path(&path),
             "bytes_written": content.len(),
-            "operation": if args.append { "append" } else { "overwrite" },
+            "operation": operation,
         }))
     };
 
@@ -252,281 +753,602 @@ fn write_file_tool() -> Tool {
         schema,
         Box::new(handler),
     )
+    .mutating()
+    .resolves_paths()
 }
 
-#[derive(Debug, Deserialize)]
-struct SearchPatternParams {
-    pattern: String,
-    #[serde(default)]
-    path: Option<String>,
-    #[serde(default)]
-    regex: bool,
-    #[serde(default)]
-    case_sensitive: Option<bool>,
-    #[serde(default)]
-    max_results: Option<usize>,
-    #[serde(default)]
-    context_lines: Option<usize>,
-    #[serde(default)]
-    include_hidden: Option<bool>,
+/// Whether two existing paths name the same file, seeing through `..`,
+/// symlinks and (on Unix) hard links.
+fn same_file(a: &Path, b: &Path) -> Result<bool> {
+    let canonical = |path: &Path| {
+        fs::canonicalize(path).with_context(|| format!("Failed to resolve {}", path.display()))
+    };
+    Ok(canonical(a)? == canonical(b)? || same_inode(a, b)?)
 }
 
-fn search_pattern_tool() -> Tool {
+#[cfg(unix)]
+fn same_inode(a: &Path, b: &Path) -> Result<bool> {
+    use std::os::unix::fs::MetadataExt;
+    let (a, b) = (fs::metadata(a)?, fs::metadata(b)?);
+    Ok(a.dev() == b.dev() && a.ino() == b.ino())
+}
+
+#[cfg(not(unix))]
+fn same_inode(_a: &Path, _b: &Path) -> Result<bool> {
+    Ok(false)
+}
+
+fn copy_file_tool() -> Tool {
     let schema = json!({
         "type": "object",
         "properties": {
-            "pattern": {
+            "from": {
                 "type": "string",
-                "description": "Needle to look for. If `regex` is true it is treated as a regular expression.",
+                "description": "File to copy",
             },
-            "path": {
+            "to": {
                 "type": "string",
-                "description": "Directory or file to search. Defaults to current working directory.",
+                "description": "Destination file path",
             },
-            "regex": {
+            "overwrite": {
                 "type": "boolean",
-                "description": "Interpret pattern as a Rust regular expression",
+                "description": "Replace the destination when it already exists",
                 "default": false,
             },
-            "case_sensitive": {
-                "type": "boolean",
-                "description": "Control case sensitivity (default true)",
-            },
-            "max_results": {
-                "type": "integer",
-                "minimum": 1,
-                "description": "Stop searching after this many matches (default 50)",
-            },
-            "context_lines": {
-                "type": "integer",
-                "minimum": 0,
-                "description": "Number of surrounding lines to include for each match (default 2)",
-            },
-            "include_hidden": {
+            "create_dirs": {
                 "type": "boolean",
-                "description": "Search files inside hidden directories (dot-prefixed)",
+                "description": "Create parent directories of the destination when they do not exist",
                 "default": false,
             }
         },
-        "required": ["pattern"],
+        "required": ["from", "to"],
+        "examples": [{"from": "templates/handler.rs", "to": "src/handlers/users.rs", "create_dirs": true}],
+        "x-positional-order": ["from", "to", "overwrite", "create_dirs"],
         "additionalProperties": false
     });
 
-    let handler = move |params| -> Result<Value> {
-        let args: SearchPatternParams =
-            serde_json::from_value(params).context("Invalid arguments for search_pattern")?;
-        let root = match &args.path {
-            Some(path) => resolve_path(path)?,
-            None => std::env::current_dir()?,
-        };
-
-        let max_results = args.max_results.unwrap_or(50);
-        let context_lines = args.context_lines.unwrap_or(2);
-        let case_sensitive = args.case_sensitive.unwrap_or(true);
-        let include_hidden = args.include_hidden.unwrap_or(false);
-
-        let mut results = Vec::new();
+    #[derive(Deserialize)]
+    struct Params {
+        from: String,
+        to: String,
+        #[serde(default)]
+        overwrite: bool,
+        #[serde(default)]
+        create_dirs: bool,
+    }
 
-        if root.is_file() {
-            search_in_file(
-                &root,
-                &args.pattern,
-                SearchOptions {
-                    regex: args.regex,
-                    case_sensitive,
-                    context_lines,
-                    max_results,
-                },
-                &mut results,
-            )?;
-        } else {
-            for entry in WalkDir::new(&root)
-                .follow_links(false)
-                .into_iter()
-                .filter_entry(|e| include_hidden || !is_hidden_path(e.path()))
-                .filter_map(|e| e.ok())
-            {
-                if !entry.file_type().is_file() {
-                    continue;
-                }
+    let handler = move |params| -> Result<Value> {
+        let args: Params =
+            serde_json::from_value(params).context("Invalid arguments for copy_file")?;
+        reject_stdio_path(&args.from)?;
+        reject_stdio_path(&args.to)?;
+        let from = resolve_path(&args.from)?;
+        let to = resolve_path(&args.to)?;
 
-                search_in_file(
-                    entry.path(),
-                    &args.pattern,
-                    SearchOptions {
-                        regex: args.regex,
-                        case_sensitive,
-                        context_lines,
-                        max_results,
-                    },
-                    &mut results,
-                )?;
-
-                if results.len() >= max_results {
-                    break;
-                }
+        if !from.is_file() {
+            anyhow::bail!("Source {} is not a file", from.display());
+        }
+        if to.is_dir() {
+            anyhow::bail!(
+                "Destination {} is a directory; pass the full file path",
+                to.display()
+            );
+        }
+        let overwritten = to.exists();
+        if from == to || (overwritten && same_file(&from, &to)?) {
+            anyhow::bail!("Source and destination are the same file");
+        }
+        if overwritten && !args.overwrite {
+            anyhow::bail!(
+                "Destination {} already exists; pass overwrite to replace it",
+                to.display()
+            );
+        }
+        if let Some(parent) = to.parent()
+            && !parent.exists()
+        {
+            if !args.create_dirs {
+                anyhow::bail!(
+                    "Parent directory {} does not exist; pass create_dirs to create it",
+                    parent.display()
+                );
             }
+            fs::create_dir_all(parent)
+                .with_context(|| format!("Failed to create {}", parent.display()))?;
         }
 
-        let truncated = results.len() >= max_results;
+        let bytes_copied = fs::copy(&from, &to)
+            .with_context(|| format!("Failed to copy {} to {}", from.display(), to.display()))?;
+
         Ok(json!({
-            "root": root.to_string_lossy(),
-            "pattern": args.pattern,
-            "regex": args.regex,
-            "case_sensitive": case_sensitive,
-            "matches": results,
-            "truncated": truncated,
+            "from": display_path(&from),
+            "to": display_path(&to),
+            "bytes_copied": bytes_copied,
+            "overwritten": overwritten,
         }))
     };
 
     Tool::new(
-        "search_pattern",
-        "Search for a literal string or regular expression across the project",
+        "copy_file",
+        "Copy a file to a new path, refusing to replace an existing file unless asked",
         schema,
         Box::new(handler),
     )
+    .mutating()
+    .resolves_paths()
 }
 
-struct SearchOptions {
-    regex: bool,
-    case_sensitive: bool,
-    context_lines: usize,
-    max_results: usize,
-}
-
-fn search_in_file(
-    path: &Path,
-    pattern: &str,
-    options: SearchOptions,
-    matches: &mut Vec<Value>,
-) -> Result<()> {
-    if matches.len() >= options.max_results {
-        return Ok(());
+fn touch_tool() -> Tool {
+    let schema = json!({
+        "type": "object",
+        "properties": {
+            "path": {
+                "type": "string",
+                "description": "File to create or touch",
+            },
+            "create_dirs": {
+                "type": "boolean",
+                "description": "Create missing parent directories",
+                "default": false,
+            }
+        },
+        "required": ["path"],
+        "examples": [{"path": "src/handlers/mod.rs", "create_dirs": true}],
+        "x-positional-order": ["path", "create_dirs"],
+        "additionalProperties": false
+    });
+
+    #[derive(Deserialize)]
+    struct Params {
+        path: String,
+        #[serde(default)]
+        create_dirs: bool,
     }
 
-    let content = match fs::read_to_string(path) {
-        Ok(content) => content,
-        Err(err) => {
-            if err.kind() == std::io::ErrorKind::InvalidData {
-                return Ok(()); // Skip non UTF-8 files
+    let handler = move |params| -> Result<Value> {
+        let args: Params = serde_json::from_value(params).context("Invalid arguments for touch")?;
+        reject_stdio_path(&args.path)?;
+        let path = resolve_path(&args.path)?;
+
+        if path.is_dir() {
+            anyhow::bail!("{} is a directory", path.display());
+        }
+        let created = !path.exists();
+        if created
+            && let Some(parent) = path.parent()
+            && !parent.exists()
+        {
+            if !args.create_dirs {
+                anyhow::bail!(
+                    "Parent directory {} does not exist; pass create_dirs to create it",
+                    parent.display()
+                );
             }
-            return Err(err).with_context(|| format!("Failed to read {}", path.display()));
+            fs::create_dir_all(parent)
+                .with_context(|| format!("Failed to create {}", parent.display()))?;
+        }
+
+        // Appending never truncates, so existing content is left alone.
+        let file = OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&path)
+            .with_context(|| format!("Failed to open {}", path.display()))?;
+        if !created {
+            file.set_modified(SystemTime::now())
+                .with_context(|| format!("Failed to update mtime of {}", path.display()))?;
         }
+        let size = file.metadata().map(|meta| meta.len()).unwrap_or(0);
+
+        Ok(json!({
+            "path": display_path(&path),
+            "action": if created { "created" } else { "touched" },
+            "size": size,
+        }))
     };
 
-    let lines: Vec<&str> = content.lines().collect();
-    let mut local_matches = Vec::new();
+    Tool::new(
+        "touch",
+        "Create an empty file if it does not exist, or update the modification time of an existing one without changing its content",
+        schema,
+        Box::new(handler),
+    )
+    .mutating()
+    .resolves_paths()
+}
+
+#[derive(Debug, Deserialize)]
+struct SearchPatternParams {
+    pattern: String,
+    #[serde(default)]
+    path: Option<SearchRoots>,
+    #[serde(default)]
+    regex: bool,
+    #[serde(default)]
+    case_sensitive: Option<bool>,
+    #[serde(default)]
+    max_results: Option<usize>,
+    #[serde(default)]
+    context_lines: Option<usize>,
+    #[serde(default)]
+    context_before: Option<usize>,
+    #[serde(default)]
+    context_after: Option<usize>,
+    #[serde(default)]
+    include_hidden: Option<bool>,
+    #[serde(default)]
+    max_depth: Option<usize>,
+    #[serde(default)]
+    max_files: Option<usize>,
+    #[serde(default)]
+    exclude: Option<Vec<String>>,
+    #[serde(default)]
+    extensions: Option<Vec<String>>,
+    #[serde(default)]
+    files_only: bool,
+    #[serde(default)]
+    output: SearchOutput,
+    #[serde(default)]
+    stream: bool,
+}
+
+/// How `search_pattern` reports what it found.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+enum SearchOutput {
+    /// Structured `matches` (or `files`).
+    #[default]
+    Json,
+    /// A single `grep` string of `path:line:col: text` lines.
+    Grep,
+    /// Both of the above.
+    Both,
+}
+
+fn search_pattern_tool() -> Tool {
+    let schema = json!({
+        "type": "object",
+        "properties": {
+            "pattern": {
+                "type": "string",
+                "description": "Needle to look for. If `regex` is true it is treated as a regular expression.",
+            },
+            "path": search_roots_schema("Directory or file to search, or a list of them searched in turn with each match reporting its `root`. Defaults to current working directory."),
+            "regex": {
+                "type": "boolean",
+                "description": "Interpret pattern as a Rust regular expression",
+                "default": false,
+            },
+            "case_sensitive": {
+                "type": "boolean",
+                "description": "Control case sensitivity (default true)",
+            },
+            "max_results": {
+                "type": "integer",
+                "minimum": 1,
+                "description": "Stop searching after this many matches (default 50)",
+            },
+            "context_lines": {
+                "type": "integer",
+                "minimum": 0,
+                "description": "Number of surrounding lines to include for each match (default 2)",
+            },
+            "context_before": {
+                "type": "integer",
+                "minimum": 0,
+                "description": "Lines of context before each match (defaults to `context_lines`)",
+            },
+            "context_after": {
+                "type": "integer",
+                "minimum": 0,
+                "description": "Lines of context after each match (defaults to `context_lines`)",
+            },
+            "include_hidden": {
+                "type": "boolean",
+                "description": "Search files inside hidden directories (dot-prefixed)",
+                "default": false,
+            },
+            "max_depth": {
+                "type": "integer",
+                "minimum": 1,
+                "description": "Maximum directory depth to descend (default 16)",
+            },
+            "max_files": {
+                "type": "integer",
+                "minimum": 1,
+                "description": "Stop after scanning this many files (default 10000)",
+            },
+            "exclude": {
+                "type": "array",
+                "items": {"type": "string"},
+                "description": "Glob patterns (relative to the search root) for files or directories to skip, e.g. `*_test.go` or `generated/**`",
+            },
+            "extensions": {
+                "type": "array",
+                "items": {"type": "string"},
+                "description": "Only scan files with these extensions (e.g. [\"rs\", \"toml\"]); other files are skipped without being read",
+            },
+            "files_only": {
+                "type": "boolean",
+                "description": "Return only the paths of files containing a match (like `grep -l`); `max_results` then caps the number of files",
+                "default": false,
+            },
+            "output": {
+                "type": "string",
+                "enum": ["json", "grep", "both"],
+                "description": "`json`: structured `matches`; `grep`: a single `grep` string with one `path:line:col: text` line per match (one path per line with `files_only`) instead; `both`: both fields",
+                "default": "json",
+            },
+            "stream": {
+                "type": "boolean",
+                "description": "On transports that deliver notifications, send each match (or file with `files_only`) as a `partial_result` notification as soon as it is found; the response is then a summary with `streamed` instead of `matches`, `files` or `grep`",
+                "default": false,
+            }
+        },
+        "required": ["pattern"],
+        "examples": [
+            {"pattern": "TODO", "path": "src", "context_lines": 1},
+            {"pattern": "serde", "extensions": ["rs", "toml"], "files_only": true},
+            {"pattern": "unwrap()", "path": ["server/src", "client/src"], "max_results": 20},
+            {"pattern": "TODO", "output": "grep"},
+            {"pattern": "unsafe", "max_results": 10000, "stream": true}
+        ],
+        "x-positional-order": [
+            "pattern", "path", "regex", "case_sensitive", "max_results", "context_lines",
+            "context_before", "context_after", "include_hidden", "max_depth", "max_files",
+            "exclude", "extensions", "files_only", "output", "stream"
+        ],
+        "additionalProperties": false
+    });
+
+    let handler = move |params| -> Result<Value> {
+        let args: SearchPatternParams =
+            serde_json::from_value(params).context("Invalid arguments for search_pattern")?;
+        let roots = SearchRoots::resolve(args.path.as_ref())?;
+        let multi_root = SearchRoots::is_many(args.path.as_ref());
+
+        let max_results = args.max_results.unwrap_or(50);
+        let context =
+            ContextWindow::new(args.context_lines, args.context_before, args.context_after);
+        let case_sensitive = args.case_sensitive.unwrap_or(true);
+        let include_hidden = args.include_hidden.unwrap_or(false);
 
-    if options.regex {
-        let regex = RegexBuilder::new(pattern)
-            .case_insensitive(!options.case_sensitive)
+        // Literal needles are escaped into a regex so case-insensitive matching
+        // happens on the original text and reported columns stay accurate for
+        // non-ASCII content.
+        let source = if args.regex {
+            args.pattern.clone()
+        } else {
+            regex::escape(&args.pattern)
+        };
+        let matcher = RegexBuilder::new(&source)
+            .case_insensitive(!case_sensitive)
             .build()
-            .with_context(|| format!("Failed to compile regex pattern '{pattern}'"))?;
-
-        for (line_idx, line) in lines.iter().enumerate() {
-            for capture in regex.find_iter(line) {
-                let column = line[..capture.start()].chars().count() + 1;
-                local_matches.push(MatchInfo::new(
-                    path,
-                    line_idx,
-                    column,
-                    line,
-                    &lines,
-                    options.context_lines,
-                ));
-
-                if matches.len() + local_matches.len() >= options.max_results {
-                    break;
-                }
+            .with_context(|| format!("Failed to compile regex pattern '{}'", args.pattern))?;
+        let options = SearchOptions {
+            matcher: &matcher,
+            prefilter: (!args.regex && case_sensitive && !args.pattern.is_empty())
+                .then(|| Finder::new(args.pattern.as_bytes())),
+            context,
+            max_results,
+        };
+
+        let max_depth = args.max_depth.unwrap_or(DEFAULT_SEARCH_MAX_DEPTH);
+        let max_files = args.max_files.unwrap_or(DEFAULT_SEARCH_MAX_FILES);
+        let mut results = Vec::new();
+        let extensions = normalize_extensions(args.extensions.as_deref());
+        let filter = if include_hidden {
+            WalkFilter::All
+        } else {
+            WalkFilter::SkipHidden
+        };
+        let mut walker =
+            FileWalker::new(filter, max_depth, max_files).extensions(extensions.as_ref());
+        let scan = |path: &Path, results: &mut Vec<Value>| -> Result<()> {
+            if !args.files_only {
+                return search_in_file(path, &options, results);
             }
+            if file_has_match(path, &options)? {
+                results.push(json!(display_path(path)));
+            }
+            Ok(())
+        };
 
-            if matches.len() + local_matches.len() >= options.max_results {
+        let mut progress = ProgressReporter::new("search_pattern");
+        let mut stream = ResultStream::new("search_pattern", args.stream);
+        let mut root_counts = Vec::new();
+
+        // Roots share the result and file caps; later roots are skipped
+        // once either is reached.
+        for root in &roots {
+            if results.len() >= max_results || walker.files_capped {
                 break;
             }
+            let before = results.len();
+            let exclude = ExcludeSet::new(root, args.exclude.as_deref())?;
+            let root_label = multi_root.then(|| display_path(root));
+            // Tag results found since `from` with their root and stream them.
+            // `files_only` results are bare paths, already naming their root.
+            let mut publish = |results: &mut [Value], from: usize| {
+                for found in &mut results[from..] {
+                    if let Some(root) = &root_label
+                        && found.is_object()
+                    {
+                        found["root"] = json!(root);
+                    }
+                    stream.send(found.clone());
+                }
+            };
+
+            walker.walk(root, &exclude, |path| {
+                let from = results.len();
+                scan(path, &mut results)?;
+                publish(&mut results, from);
+                progress.file_scanned(results.len());
+                Ok(results.len() < max_results)
+            })?;
+
+            if let Some(root) = root_label {
+                root_counts.push(json!({"root": root, "count": results.len() - before}));
+            }
         }
-    } else {
-        let needle = if options.case_sensitive {
-            pattern.to_string()
+
+        let truncated = results.len() >= max_results;
+        let mut response = json!({
+            "pattern": args.pattern,
+            "regex": args.regex,
+            "case_sensitive": case_sensitive,
+            "truncated": truncated,
+            "files_scanned": walker.files_scanned,
+            "files_capped": walker.files_capped,
+            "depth_limited": walker.depth_limited,
+            "files_excluded": walker.files_excluded,
+        });
+        if multi_root {
+            response["roots"] = json!(root_counts);
         } else {
-            pattern.to_lowercase()
-        };
+            response["root"] = json!(display_path(&roots[0]));
+        }
+        if args.output != SearchOutput::Json {
+            response["grep"] = json!(grep_lines(&results));
+        }
+        response["count"] = json!(results.len());
+        if args.output != SearchOutput::Grep {
+            let field = if args.files_only { "files" } else { "matches" };
+            response[field] = json!(results);
+        }
+        stream.finish(&mut response, &["matches", "files", "grep"]);
+        Ok(response)
+    };
 
-        for (line_idx, line) in lines.iter().enumerate() {
-            let haystack = if options.case_sensitive {
-                (*line).to_string()
-            } else {
-                line.to_lowercase()
-            };
+    Tool::new(
+        "search_pattern",
+        "Search for a literal string or regular expression across the project",
+        schema,
+        Box::new(handler),
+    )
+    .resolves_paths()
+    .cacheable()
+}
 
-            let mut search_start = 0;
-            let mut remainder = haystack.as_str();
-            while let Some(pos) = remainder.find(&needle) {
-                let absolute_pos = search_start + pos;
-                let column = line[..absolute_pos].chars().count() + 1;
-                local_matches.push(MatchInfo::new(
-                    path,
-                    line_idx,
-                    column,
-                    line,
-                    &lines,
-                    options.context_lines,
-                ));
-
-                if matches.len() + local_matches.len() >= options.max_results {
-                    break;
-                }
+struct SearchOptions<'a> {
+    matcher: &'a Regex,
+    /// Case-sensitive literal needle. Files whose bytes do not contain it are
+    /// skipped before any line is split or matched.
+    prefilter: Option<Finder<'a>>,
+    context: ContextWindow,
+    max_results: usize,
+}
 
-                let advance = pos + needle.len();
-                search_start += advance;
-                remainder = &remainder[advance..];
-            }
+fn search_in_file(
+    path: &Path,
+    options: &SearchOptions<'_>,
+    matches: &mut Vec<Value>,
+) -> Result<()> {
+    if matches.len() >= options.max_results {
+        return Ok(());
+    }
+
+    let Some(content) = read_searchable(path, options.prefilter.as_ref())? else {
+        return Ok(());
+    };
+
+    let lines: Vec<&str> = content.lines().collect();
+    let mut local_matches = Vec::new();
+
+    for (line_idx, line) in lines.iter().enumerate() {
+        for capture in options.matcher.find_iter(line) {
+            local_matches.push(MatchInfo::new(
+                path,
+                line_idx,
+                capture.range(),
+                line,
+                &lines,
+                options.context,
+            ));
 
             if matches.len() + local_matches.len() >= options.max_results {
                 break;
             }
         }
+
+        if matches.len() + local_matches.len() >= options.max_results {
+            break;
+        }
     }
 
     matches.extend(local_matches.into_iter().map(|m| m.into_value()));
     Ok(())
 }
 
+/// Whether any line of `path` matches, stopping at the first hit.
+fn file_has_match(path: &Path, options: &SearchOptions<'_>) -> Result<bool> {
+    let Some(content) = read_searchable(path, options.prefilter.as_ref())? else {
+        return Ok(false);
+    };
+    Ok(content.lines().any(|line| options.matcher.is_match(line)))
+}
+
+/// Content of `path` for searching, or `None` when it is not UTF-8 or
+/// `prefilter`'s needle does not occur anywhere in it.
+fn read_searchable(path: &Path, prefilter: Option<&Finder<'_>>) -> Result<Option<String>> {
+    let bytes = fs::read(path).with_context(|| format!("Failed to read {}", path.display()))?;
+    // Binary files fail validation quickly, so check UTF-8 before the needle.
+    let Ok(content) = String::from_utf8(bytes) else {
+        return Ok(None);
+    };
+    if prefilter.is_some_and(|finder| finder.find(content.as_bytes()).is_none()) {
+        return Ok(None);
+    }
+    Ok(Some(content))
+}
+
 struct MatchInfo<'a> {
     path: PathBuf,
     line_idx: usize,
+    /// 1-based character columns of the first matched character and of the
+    /// character just past the match.
     column: usize,
+    match_end: usize,
+    matched: &'a str,
     line: &'a str,
     context: Vec<(&'a str, usize)>,
+    context_before: usize,
+    context_after: usize,
 }
 
 impl<'a> MatchInfo<'a> {
     fn new(
         path: &Path,
         line_idx: usize,
-        column: usize,
+        span: Range<usize>,
         line: &'a str,
         lines: &'a [&'a str],
-        context_lines: usize,
+        window: ContextWindow,
     ) -> Self {
-        let mut context = Vec::new();
-
-        if context_lines > 0 {
-            let start = line_idx.saturating_sub(context_lines);
-            let end = usize::min(line_idx + context_lines, lines.len().saturating_sub(1));
-            for idx in start..=end {
-                if idx == line_idx {
-                    continue;
-                }
-                context.push((lines[idx], idx));
-            }
-        }
+        let column = line[..span.start].chars().count() + 1;
+        let matched = &line[span];
+        let match_end = column + matched.chars().count();
+        let (start, end) = window.bounds(line_idx, lines.len());
+        let context = lines
+            .iter()
+            .enumerate()
+            .take(end + 1)
+            .skip(start)
+            .filter(|(idx, _)| *idx != line_idx)
+            .map(|(idx, text)| (*text, idx))
+            .collect();
 
         Self {
             path: path.to_path_buf(),
             line_idx,
             column,
+            match_end,
+            matched,
             line,
             context,
+            context_before: line_idx - start,
+            context_after: end - line_idx,
         }
     }
 
@@ -544,18 +1366,1001 @@ impl<'a> MatchInfo<'a> {
             .collect::<Vec<_>>();
 
         json!({
-            "path": self.path.to_string_lossy(),
+            "path": display_path(&self.path),
             "line": self.line_idx + 1,
             "column": self.column,
+            "match_start": self.column,
+            "match_end": self.match_end,
+            "match_text": self.matched,
             "preview": preview,
             "context": context,
+            "context_before": self.context_before,
+            "context_after": self.context_after,
         })
     }
 }
 
-fn is_hidden_path(path: &Path) -> bool {
-    path.components().any(|component| match component {
-        std::path::Component::Normal(os_str) => os_str.to_string_lossy().starts_with('.'),
-        _ => false,
-    })
+/// `results` as grep prints them: `path:line:col: text` for matches and the
+/// bare path for `files_only` results, one per line.
+fn grep_lines(results: &[Value]) -> String {
+    results
+        .iter()
+        .map(|result| match result {
+            Value::String(path) => format!("{path}\n"),
+            found => format!(
+                "{}:{}:{}: {}\n",
+                found["path"].as_str().unwrap_or_default(),
+                found["line"],
+                found["column"],
+                found["preview"].as_str().unwrap_or_default()
+            ),
+        })
+        .collect()
+}
+
+fn preview_replace_tool() -> Tool {
+    let schema = json!({
+        "type": "object",
+        "properties": {
+            "pattern": {
+                "type": "string",
+                "minLength": 1,
+                "description": "Needle to replace. If `regex` is true it is treated as a regular expression.",
+            },
+            "replacement": {
+                "type": "string",
+                "description": "Text each match would become. With `regex`, `$1` or `${name}` insert capture groups and `$$` is a literal `$`",
+            },
+            "path": search_roots_schema("Directory or file to search, or a list of them. Defaults to current working directory."),
+            "regex": {
+                "type": "boolean",
+                "description": "Interpret pattern as a Rust regular expression",
+                "default": false,
+            },
+            "case_sensitive": {
+                "type": "boolean",
+                "description": "Control case sensitivity (default true)",
+            },
+            "max_results": {
+                "type": "integer",
+                "minimum": 1,
+                "description": "Stop after this many changed lines (default 100)",
+            },
+            "include_hidden": {
+                "type": "boolean",
+                "description": "Search files inside hidden directories (dot-prefixed)",
+                "default": false,
+            },
+            "max_depth": {
+                "type": "integer",
+                "minimum": 1,
+                "description": "Maximum directory depth to descend (default 16)",
+            },
+            "max_files": {
+                "type": "integer",
+                "minimum": 1,
+                "description": "Stop after scanning this many files (default 10000)",
+            },
+            "exclude": {
+                "type": "array",
+                "items": {"type": "string"},
+                "description": "Glob patterns (relative to the search root) for files or directories to skip",
+            },
+            "extensions": {
+                "type": "array",
+                "items": {"type": "string"},
+                "description": "Only scan files with these extensions (e.g. [\"rs\", \"toml\"])",
+            },
+            "return_diff": {
+                "type": "boolean",
+                "description": "Also return `diffs: [{ path, diff }]`, a unified diff per changed file covering every match in it",
+                "default": false,
+            }
+        },
+        "required": ["pattern", "replacement"],
+        "examples": [
+            {"pattern": "old_name", "replacement": "new_name", "path": "src"},
+            {"pattern": "unwrap\\(\\)", "replacement": "expect(\"TODO\")", "regex": true, "extensions": ["rs"], "return_diff": true}
+        ],
+        "x-positional-order": [
+            "pattern", "replacement", "path", "regex", "case_sensitive", "max_results",
+            "include_hidden", "max_depth", "max_files", "exclude", "extensions", "return_diff"
+        ],
+        "additionalProperties": false
+    });
+
+    #[derive(Deserialize)]
+    struct Params {
+        pattern: String,
+        replacement: String,
+        #[serde(default)]
+        path: Option<SearchRoots>,
+        #[serde(default)]
+        regex: bool,
+        #[serde(default)]
+        case_sensitive: Option<bool>,
+        #[serde(default)]
+        max_results: Option<usize>,
+        #[serde(default)]
+        include_hidden: bool,
+        #[serde(default)]
+        max_depth: Option<usize>,
+        #[serde(default)]
+        max_files: Option<usize>,
+        #[serde(default)]
+        exclude: Option<Vec<String>>,
+        #[serde(default)]
+        extensions: Option<Vec<String>>,
+        #[serde(default)]
+        return_diff: bool,
+    }
+
+    let handler = move |params| -> Result<Value> {
+        let args: Params =
+            serde_json::from_value(params).context("Invalid arguments for preview_replace")?;
+        let roots = SearchRoots::resolve(args.path.as_ref())?;
+        let case_sensitive = args.case_sensitive.unwrap_or(true);
+        let max_results = args.max_results.unwrap_or(100);
+
+        let source = if args.regex {
+            args.pattern.clone()
+        } else {
+            regex::escape(&args.pattern)
+        };
+        let matcher = RegexBuilder::new(&source)
+            .case_insensitive(!case_sensitive)
+            .build()
+            .with_context(|| format!("Failed to compile regex pattern '{}'", args.pattern))?;
+        reject_empty_match(&matcher, &args.pattern)?;
+        let prefilter =
+            (!args.regex && case_sensitive).then(|| Finder::new(args.pattern.as_bytes()));
+        let replace = |line: &str| -> String {
+            if args.regex {
+                matcher
+                    .replace_all(line, args.replacement.as_str())
+                    .into_owned()
+            } else {
+                matcher
+                    .replace_all(line, NoExpand(&args.replacement))
+                    .into_owned()
+            }
+        };
+
+        let max_depth = args.max_depth.unwrap_or(DEFAULT_SEARCH_MAX_DEPTH);
+        let max_files = args.max_files.unwrap_or(DEFAULT_SEARCH_MAX_FILES);
+        let extensions = normalize_extensions(args.extensions.as_deref());
+        let mut changes = Vec::new();
+        let mut diffs = Vec::new();
+        let mut files_changed = 0usize;
+        let mut replacements = 0usize;
+        let mut truncated = false;
+        let filter = if args.include_hidden {
+            WalkFilter::All
+        } else {
+            WalkFilter::SkipHidden
+        };
+        let mut walker =
+            FileWalker::new(filter, max_depth, max_files).extensions(extensions.as_ref());
+
+        // Adds the changed lines of `path` to `changes`, returning whether
+        // `max_results` cut them short.
+        let mut preview_file = |path: &Path, changes: &mut Vec<Value>| -> Result<bool> {
+            let Some(content) = read_searchable(path, prefilter.as_ref())? else {
+                return Ok(false);
+            };
+            let mut changed = false;
+            let mut truncated = false;
+            for (line_idx, line) in content.lines().enumerate() {
+                let count = matcher.find_iter(line).count();
+                if count == 0 {
+                    continue;
+                }
+                if changes.len() >= max_results {
+                    truncated = true;
+                    break;
+                }
+                changed = true;
+                replacements += count;
+                changes.push(json!({
+                    "path": display_path(path),
+                    "line": line_idx + 1,
+                    "replacements": count,
+                    "original": line,
+                    "replaced": replace(line),
+                }));
+            }
+            if changed {
+                files_changed += 1;
+                if args.return_diff {
+                    let updated = content
+                        .split_inclusive('\n')
+                        .map(|line| {
+                            let body = line.trim_end_matches(['\n', '\r']);
+                            format!("{}{}", replace(body), &line[body.len()..])
+                        })
+                        .collect::<String>();
+                    diffs.push(json!({
+                        "path": display_path(path),
+                        "diff": unified_diff(&content, &updated, &display_path(path), 3),
+                    }));
+                }
+            }
+            Ok(truncated)
+        };
+
+        for root in &roots {
+            if truncated || walker.files_capped {
+                break;
+            }
+            let exclude = ExcludeSet::new(root, args.exclude.as_deref())?;
+            walker.walk(root, &exclude, |path| {
+                truncated = preview_file(path, &mut changes)?;
+                Ok(!truncated)
+            })?;
+        }
+
+        let mut response = json!({
+            "pattern": args.pattern,
+            "replacement": args.replacement,
+            "regex": args.regex,
+            "case_sensitive": case_sensitive,
+            "files_changed": files_changed,
+            "lines_changed": changes.len(),
+            "replacements": replacements,
+            "truncated": truncated,
+            "files_scanned": walker.files_scanned,
+            "files_capped": walker.files_capped,
+            "changes": changes,
+        });
+        if args.return_diff {
+            response["diffs"] = json!(diffs);
+        }
+        Ok(response)
+    };
+
+    Tool::new(
+        "preview_replace",
+        "Show every line a search-and-replace would change, with the original and rewritten text, without writing anything",
+        schema,
+        Box::new(handler),
+    )
+    .resolves_paths()
+}
+
+fn replace_in_files_tool() -> Tool {
+    let schema = json!({
+        "type": "object",
+        "properties": {
+            "paths": {
+                "type": "array",
+                "items": {"type": "string", "minLength": 1},
+                "minItems": 1,
+                "description": "Exactly the files to edit, e.g. from an earlier `search_pattern` with `files_only`; nothing else is read or walked",
+            },
+            "pattern": {
+                "type": "string",
+                "minLength": 1,
+                "description": "Needle to replace, matched within single lines. If `regex` is true it is treated as a regular expression.",
+            },
+            "replacement": {
+                "type": "string",
+                "description": "Text each match becomes. With `regex`, `$1` or `${name}` insert capture groups and `$$` is a literal `$`",
+            },
+            "regex": {
+                "type": "boolean",
+                "description": "Interpret pattern as a Rust regular expression",
+                "default": false,
+            },
+            "case_sensitive": {
+                "type": "boolean",
+                "description": "Control case sensitivity (default true)",
+            },
+            "dry_run": {
+                "type": "boolean",
+                "description": "Report what would change without writing any file",
+                "default": false,
+            },
+            "return_diff": {
+                "type": "boolean",
+                "description": "Include a unified diff per changed file as `diff`",
+                "default": false,
+            }
+        },
+        "required": ["paths", "pattern", "replacement"],
+        "examples": [
+            {"paths": ["src/lib.rs", "src/main.rs"], "pattern": "old_name", "replacement": "new_name"},
+            {"paths": ["app/views.py"], "pattern": "print\\((.*)\\)", "replacement": "log.info($1)", "regex": true, "dry_run": true, "return_diff": true}
+        ],
+        "x-positional-order": [
+            "paths", "pattern", "replacement", "regex", "case_sensitive", "dry_run", "return_diff"
+        ],
+        "additionalProperties": false
+    });
+
+    #[derive(Deserialize)]
+    struct Params {
+        paths: Vec<String>,
+        pattern: String,
+        replacement: String,
+        #[serde(default)]
+        regex: bool,
+        #[serde(default)]
+        case_sensitive: Option<bool>,
+        #[serde(default)]
+        dry_run: bool,
+        #[serde(default)]
+        return_diff: bool,
+    }
+
+    let handler = move |params| -> Result<Value> {
+        let args: Params =
+            serde_json::from_value(params).context("Invalid arguments for replace_in_files")?;
+        let case_sensitive = args.case_sensitive.unwrap_or(true);
+        let source = if args.regex {
+            args.pattern.clone()
+        } else {
+            regex::escape(&args.pattern)
+        };
+        let matcher = RegexBuilder::new(&source)
+            .case_insensitive(!case_sensitive)
+            .build()
+            .with_context(|| format!("Failed to compile regex pattern '{}'", args.pattern))?;
+        reject_empty_match(&matcher, &args.pattern)?;
+        let replace = |line: &str| -> String {
+            if args.regex {
+                matcher
+                    .replace_all(line, args.replacement.as_str())
+                    .into_owned()
+            } else {
+                matcher
+                    .replace_all(line, NoExpand(&args.replacement))
+                    .into_owned()
+            }
+        };
+
+        // Every path is checked before anything is written, so a typo does
+        // not leave the edit half applied.
+        let mut paths = Vec::new();
+        for raw in &args.paths {
+            let path = resolve_path(raw)?;
+            if !path.is_file() {
+                anyhow::bail!("{} is not a file", path.display());
+            }
+            if !paths.contains(&path) {
+                paths.push(path);
+            }
+        }
+
+        let mut files = Vec::new();
+        let mut files_changed = 0usize;
+        let mut total = 0usize;
+        for path in &paths {
+            let Some(content) = read_searchable(path, None)? else {
+                files.push(json!({
+                    "path": display_path(path),
+                    "status": "skipped",
+                    "reason": "not UTF-8 text",
+                }));
+                continue;
+            };
+
+            let mut replacements = 0usize;
+            let mut lines = Vec::new();
+            let updated = content
+                .split_inclusive('\n')
+                .enumerate()
+                .map(|(line_idx, line)| {
+                    let body = line.trim_end_matches(['\n', '\r']);
+                    let count = matcher.find_iter(body).count();
+                    if count == 0 {
+                        return line.to_string();
+                    }
+                    replacements += count;
+                    lines.push(line_idx + 1);
+                    format!("{}{}", replace(body), &line[body.len()..])
+                })
+                .collect::<String>();
+
+            let mut result = json!({
+                "path": display_path(path),
+                "status": if replacements > 0 { "changed" } else { "unchanged" },
+                "replacements": replacements,
+                "lines": lines,
+            });
+            if replacements > 0 {
+                files_changed += 1;
+                total += replacements;
+                if !args.dry_run {
+                    fs::write(path, &updated)
+                        .with_context(|| format!("Failed to write {}", path.display()))?;
+                    journal::record_edit(
+                        "replace_in_files",
+                        path,
+                        Vec::new(),
+                        Some(format!(
+                            "replaced {} ({replacements} replacements)",
+                            args.pattern
+                        )),
+                    );
+                }
+                if args.return_diff {
+                    result["diff"] =
+                        json!(unified_diff(&content, &updated, &display_path(path), 3));
+                }
+            }
+            files.push(result);
+        }
+
+        Ok(json!({
+            "pattern": args.pattern,
+            "replacement": args.replacement,
+            "regex": args.regex,
+            "case_sensitive": case_sensitive,
+            "dry_run": args.dry_run,
+            "files_changed": files_changed,
+            "replacements": total,
+            "files": files,
+        }))
+    };
+
+    Tool::new(
+        "replace_in_files",
+        "Search and replace within an explicit list of files, without walking any directory, reporting the outcome per file. Preview a directory-wide replacement with `preview_replace`",
+        schema,
+        Box::new(handler),
+    )
+    .mutating()
+    .resolves_paths()
+}
+
+#[derive(Debug, Deserialize)]
+struct FileStatsParams {
+    path: String,
+}
+
+fn file_stats_tool() -> Tool {
+    let schema = json!({
+        "type": "object",
+        "properties": {
+            "path": {
+                "type": "string",
+                "description": "File to measure, or directory to aggregate per language",
+            }
+        },
+        "required": ["path"],
+        "examples": [{"path": "src"}],
+        "x-positional-order": ["path"],
+        "additionalProperties": false
+    });
+
+    let handler = move |params| -> Result<Value> {
+        let args: FileStatsParams =
+            serde_json::from_value(params).context("Invalid arguments for file_stats")?;
+        let path = resolve_path(&args.path)?;
+
+        if path.is_file() {
+            let content = fs::read_to_string(&path)
+                .with_context(|| format!("Failed to read {}", path.display()))?;
+            let stats = count_line_stats(&content, &path);
+            return Ok(json!({
+                "path": display_path(&path),
+                "language": Language::from_path(&path).map(|lang| lang.as_str()),
+                "stats": stats,
+            }));
+        }
+
+        let mut totals = LineStats::default();
+        let mut per_language: HashMap<String, LineStats> = HashMap::new();
+        let mut skipped_files = 0usize;
+
+        for entry in WalkDir::new(&path)
+            .follow_links(false)
+            .into_iter()
+            .filter_entry(allow_entry)
+            .filter_map(|e| e.ok())
+            .filter(|e| e.file_type().is_file())
+        {
+            let content = match fs::read_to_string(entry.path()) {
+                Ok(content) => content,
+                // Binary and unreadable files are left out of the totals.
+                Err(_) => {
+                    skipped_files += 1;
+                    continue;
+                }
+            };
+
+            let stats = count_line_stats(&content, entry.path());
+            let language = entry
+                .path()
+                .extension()
+                .and_then(|ext| language_from_extension(&ext.to_string_lossy().to_lowercase()))
+                .unwrap_or_else(|| String::from("Other"));
+
+            totals.add(&stats);
+            per_language.entry(language).or_default().add(&stats);
+        }
+
+        let mut languages = per_language
+            .into_iter()
+            .map(|(language, stats)| {
+                let mut value = json!(stats);
+                value["language"] = json!(language);
+                value
+            })
+            .collect::<Vec<_>>();
+        languages.sort_by_key(|value| std::cmp::Reverse(value["lines"].as_u64().unwrap_or(0)));
+
+        Ok(json!({
+            "path": display_path(&path),
+            "totals": totals,
+            "languages": languages,
+            "skipped_files": skipped_files,
+        }))
+    };
+
+    Tool::new(
+        "file_stats",
+        "Report line, byte, blank and rough comment/code counts for a file or directory",
+        schema,
+        Box::new(handler),
+    )
+    .resolves_paths()
+}
+
+/// Line counters reported by `file_stats`. Comment and code counts are only
+/// present when the file's language has known comment markers.
+#[derive(Debug, Default, Serialize)]
+struct LineStats {
+    files: usize,
+    bytes: usize,
+    lines: usize,
+    blank_lines: usize,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    comment_lines: Option<usize>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    code_lines: Option<usize>,
+}
+
+impl LineStats {
+    fn add(&mut self, other: &LineStats) {
+        self.files += other.files;
+        self.bytes += other.bytes;
+        self.lines += other.lines;
+        self.blank_lines += other.blank_lines;
+        self.comment_lines = sum_optional(self.comment_lines, other.comment_lines);
+        self.code_lines = sum_optional(self.code_lines, other.code_lines);
+    }
+}
+
+fn sum_optional(a: Option<usize>, b: Option<usize>) -> Option<usize> {
+    match (a, b) {
+        (Some(a), Some(b)) => Some(a + b),
+        (a, b) => a.or(b),
+    }
+}
+
+/// Line counts for `content`. A line is a comment when every non-blank byte
+/// lies inside a comment found by [`lexical_spans_with`] with the markers for
+/// `path`, so markers inside strings do not count.
+fn count_line_stats(content: &str, path: &Path) -> LineStats {
+    let mut stats = LineStats {
+        files: 1,
+        bytes: content.len(),
+        ..LineStats::default()
+    };
+    let comments = comment_syntax(path)
+        .zip(Language::from_path(path))
+        .map(|(syntax, language)| {
+            let mut mask = vec![false; content.len()];
+            for span in lexical_spans_with(content, language, &syntax) {
+                if span.kind == SpanKind::Comment {
+                    mask[span.range].fill(true);
+                }
+            }
+            mask
+        });
+    let in_comment = |index: usize| {
+        comments
+            .as_ref()
+            .is_some_and(|mask| mask.get(index).copied().unwrap_or(false))
+    };
+    let mut comment_lines = 0usize;
+    let mut code_lines = 0usize;
+
+    let mut offset = 0;
+    for line in content.split_inclusive('\n') {
+        let start = offset;
+        offset += line.len();
+        stats.lines += 1;
+
+        if line.trim().is_empty() {
+            // Blank lines inside a block comment belong to the comment.
+            if in_comment(start) {
+                comment_lines += 1;
+            } else {
+                stats.blank_lines += 1;
+            }
+            continue;
+        }
+
+        let code = line
+            .bytes()
+            .enumerate()
+            .any(|(index, byte)| !byte.is_ascii_whitespace() && !in_comment(start + index));
+        if code {
+            code_lines += 1;
+        } else {
+            comment_lines += 1;
+        }
+    }
+
+    if comments.is_some() {
+        stats.comment_lines = Some(comment_lines);
+        stats.code_lines = Some(code_lines);
+    }
+    stats
+}
+
+fn detect_style_tool() -> Tool {
+    let schema = json!({
+        "type": "object",
+        "properties": {
+            "path": {"type": "string", "description": "File to inspect"}
+        },
+        "required": ["path"],
+        "examples": [{"path": "src/main.rs"}],
+        "x-positional-order": ["path"],
+        "additionalProperties": false
+    });
+
+    #[derive(Deserialize)]
+    struct Params {
+        path: String,
+    }
+
+    let handler = move |params| -> Result<Value> {
+        let args: Params =
+            serde_json::from_value(params).context("Invalid arguments for detect_style")?;
+        let path = resolve_path(&args.path)?;
+        if !path.is_file() {
+            anyhow::bail!("{} is not a file", path.display());
+        }
+        let content = fs::read_to_string(&path)
+            .with_context(|| format!("Failed to read {} as UTF-8 text", path.display()))?;
+
+        let mut response = json!({"path": display_path(&path)});
+        if let (Some(response), Value::Object(style)) =
+            (response.as_object_mut(), json!(detect_style(&content)))
+        {
+            response.extend(style);
+        }
+        Ok(response)
+    };
+
+    Tool::new(
+        "detect_style",
+        "Report a file's predominant line ending, indentation style and size, and whether it ends with a newline",
+        schema,
+        Box::new(handler),
+    )
+    .resolves_paths()
+}
+
+/// Whitespace conventions of a file, as reported by `detect_style`.
+#[derive(Debug, Serialize)]
+struct FileStyle {
+    /// `lf`, `crlf`, `mixed`, or `none` for a single unterminated line.
+    line_ending: &'static str,
+    lf_lines: usize,
+    crlf_lines: usize,
+    /// Predominant indentation: `tabs`, `spaces`, or `none` when no line is
+    /// indented.
+    indent_style: &'static str,
+    /// Spaces per level when indenting with spaces and a step is evident.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    indent_size: Option<usize>,
+    tab_indented_lines: usize,
+    space_indented_lines: usize,
+    /// Whether both tab- and space-indented lines occur.
+    mixed_indentation: bool,
+    trailing_newline: bool,
+}
+
+fn detect_style(content: &str) -> FileStyle {
+    let (lf_lines, crlf_lines) = line_ending_counts(content);
+    let line_ending = match (lf_lines, crlf_lines) {
+        (0, 0) => "none",
+        (_, 0) => "lf",
+        (0, _) => "crlf",
+        _ => "mixed",
+    };
+
+    let mut tab_indented_lines = 0usize;
+    let mut space_indented_lines = 0usize;
+    // How often each increase in leading spaces occurs between consecutive
+    // space-indented (or unindented) lines.
+    let mut steps: HashMap<usize, usize> = HashMap::new();
+    let mut previous_width = 0usize;
+    for line in content.lines() {
+        let trimmed = line.trim_start();
+        // Block comment continuations (` * ...`) are aligned rather than
+        // indented, so they say nothing about the indentation unit.
+        if trimmed.is_empty() || trimmed.starts_with('*') {
+            continue;
+        }
+        match line.as_bytes()[0] {
+            b'\t' => {
+                tab_indented_lines += 1;
+                continue;
+            }
+            b' ' => space_indented_lines += 1,
+            _ => {}
+        }
+        let width = line.len() - trimmed.len();
+        if line[..width].contains('\t') {
+            continue;
+        }
+        if width > previous_width && width - previous_width <= 8 {
+            *steps.entry(width - previous_width).or_default() += 1;
+        }
+        previous_width = width;
+    }
+
+    let indent_style = if tab_indented_lines == 0 && space_indented_lines == 0 {
+        "none"
+    } else if tab_indented_lines >= space_indented_lines {
+        "tabs"
+    } else {
+        "spaces"
+    };
+    let indent_size = (indent_style == "spaces")
+        .then(|| {
+            steps
+                .into_iter()
+                .max_by_key(|&(step, count)| (count, std::cmp::Reverse(step)))
+                .map(|(step, _)| step)
+        })
+        .flatten();
+
+    FileStyle {
+        line_ending,
+        lf_lines,
+        crlf_lines,
+        indent_style,
+        indent_size,
+        tab_indented_lines,
+        space_indented_lines,
+        mixed_indentation: tab_indented_lines > 0 && space_indented_lines > 0,
+        trailing_newline: content.ends_with('\n'),
+    }
+}
+
+/// `http`, `https` and `ftp` URLs, ending before whitespace, quotes or
+/// brackets; trailing punctuation is trimmed separately.
+static URL_RE: Lazy<Regex> =
+    Lazy::new(|| Regex::new(r#"\b(?:https?|ftp)://[^\s"'`<>()\[\]{}]+"#).unwrap());
+
+static TODO_RE: Lazy<Regex> = Lazy::new(|| Regex::new(r"\b(TODO|FIXME)\b").unwrap());
+
+fn extract_literals_tool() -> Tool {
+    let schema = json!({
+        "type": "object",
+        "properties": {
+            "path": {"type": "string", "description": "File to scan"},
+            "max_results": {
+                "type": "integer",
+                "minimum": 1,
+                "description": "Maximum entries returned in each of `strings`, `urls` and `todos` (default 500)",
+            }
+        },
+        "required": ["path"],
+        "examples": [{"path": "src/config.rs"}, {"path": "app.py", "max_results": 50}],
+        "x-positional-order": ["path", "max_results"],
+        "additionalProperties": false
+    });
+
+    #[derive(Deserialize)]
+    struct Params {
+        path: String,
+        #[serde(default)]
+        max_results: Option<usize>,
+    }
+
+    let handler = move |params| -> Result<Value> {
+        let args: Params =
+            serde_json::from_value(params).context("Invalid arguments for extract_literals")?;
+        let path = resolve_path(&args.path)?;
+        if !path.is_file() {
+            anyhow::bail!("{} is not a file", path.display());
+        }
+        let content = fs::read_to_string(&path)
+            .with_context(|| format!("Failed to read {} as UTF-8 text", path.display()))?;
+        let language = Language::from_path(&path).unwrap_or(Language::Generic);
+        let max_results = args.max_results.unwrap_or(500);
+
+        let line_starts = std::iter::once(0)
+            .chain(content.match_indices('\n').map(|(index, _)| index + 1))
+            .collect::<Vec<_>>();
+        // 1-based line and character column of a byte offset.
+        let position = |offset: usize| {
+            let line = line_starts.partition_point(|start| *start <= offset);
+            let column = content[line_starts[line - 1]..offset].chars().count() + 1;
+            (line, column)
+        };
+
+        let mut strings = Vec::new();
+        let mut todos = Vec::new();
+        for span in lexical_spans(&content, language) {
+            match span.kind {
+                // Rust `'x'` is a character, not a string.
+                SpanKind::String
+                    if language == Language::Rust
+                        && content[span.range.start..].starts_with('\'') => {}
+                SpanKind::String => {
+                    let (line, column) = position(span.range.start);
+                    strings.push(json!({
+                        "line": line,
+                        "column": column,
+                        "value": &content[span.interior.clone()],
+                    }));
+                }
+                SpanKind::Comment => {
+                    let comment = &content[span.range.clone()];
+                    for found in TODO_RE.find_iter(comment) {
+                        let rest = &comment[found.start()..];
+                        let text = rest.lines().next().unwrap_or(rest);
+                        let text = text.trim_end().trim_end_matches("*/").trim_end();
+                        todos.push(json!({
+                            "line": position(span.range.start + found.start()).0,
+                            "tag": found.as_str(),
+                            "text": text,
+                        }));
+                    }
+                }
+            }
+        }
+
+        let mut urls = URL_RE
+            .find_iter(&content)
+            .map(|found| {
+                let url = found
+                    .as_str()
+                    .trim_end_matches(['.', ',', ';', ':', '!', '?']);
+                let (line, column) = position(found.start());
+                json!({"line": line, "column": column, "url": url})
+            })
+            .collect::<Vec<_>>();
+
+        let totals = json!({
+            "strings": strings.len(),
+            "urls": urls.len(),
+            "todos": todos.len(),
+        });
+        let truncated = [&strings, &urls, &todos]
+            .iter()
+            .any(|list| list.len() > max_results);
+        for list in [&mut strings, &mut urls, &mut todos] {
+            list.truncate(max_results);
+        }
+
+        Ok(json!({
+            "path": display_path(&path),
+            "language": language.as_str(),
+            "totals": totals,
+            "truncated": truncated,
+            "strings": strings,
+            "urls": urls,
+            "todos": todos,
+        }))
+    };
+
+    Tool::new(
+        "extract_literals",
+        "List a file's string literals, URL-looking tokens and TODO/FIXME comments with their line numbers",
+        schema,
+        Box::new(handler),
+    )
+    .resolves_paths()
+}
+
+fn changed_since_tool() -> Tool {
+    let schema = json!({
+        "type": "object",
+        "properties": {
+            "since": {
+                "type": "string",
+                "description": "RFC3339 timestamp; files modified strictly after it are reported",
+            },
+            "path": {
+                "type": "string",
+                "description": "Root directory to walk. Defaults to current working directory.",
+            },
+            "max_results": {
+                "type": "integer",
+                "minimum": 1,
+                "description": "Maximum number of files to return, newest first (default 500)",
+            }
+        },
+        "required": ["since"],
+        "examples": [{"since": "2024-01-01T00:00:00Z", "path": "src"}],
+        "x-positional-order": ["since", "path", "max_results"],
+        "additionalProperties": false
+    });
+
+    #[derive(Deserialize)]
+    struct Params {
+        since: String,
+        #[serde(default)]
+        path: Option<String>,
+        #[serde(default)]
+        max_results: Option<usize>,
+    }
+
+    let handler = move |params| -> Result<Value> {
+        let args: Params =
+            serde_json::from_value(params).context("Invalid arguments for changed_since")?;
+        let since = OffsetDateTime::parse(&args.since, &Rfc3339)
+            .with_context(|| format!("'{}' is not an RFC3339 timestamp", args.since))?;
+        let since = SystemTime::from(since);
+        let root = match &args.path {
+            Some(path) => resolve_path(path)?,
+            None => base_dir()?,
+        };
+        let max_results = args.max_results.unwrap_or(500);
+        // Captured before walking so edits made during the scan show up in
+        // the next call that uses it as `since`.
+        let checked_at = format_system_time(SystemTime::now());
+
+        let mut changed = Vec::new();
+        for entry in WalkDir::new(&root)
+            .follow_links(false)
+            .into_iter()
+            .filter_entry(allow_entry)
+            .filter_map(|e| e.ok())
+            .filter(|e| e.file_type().is_file())
+        {
+            let Some(modified) = entry.metadata().ok().and_then(|m| m.modified().ok()) else {
+                continue;
+            };
+            if modified > since {
+                changed.push((entry.into_path(), modified));
+            }
+        }
+
+        changed.sort_by_key(|(_, modified)| std::cmp::Reverse(*modified));
+        let total = changed.len();
+        let files = changed
+            .into_iter()
+            .take(max_results)
+            .map(|(path, modified)| {
+                json!({
+                    "path": display_path(&path),
+                    "modified": format_system_time(modified),
+                })
+            })
+            .collect::<Vec<_>>();
+
+        Ok(json!({
+            "root": display_path(&root),
+            "since": args.since,
+            "checked_at": checked_at,
+            "count": total,
+            "truncated": total > files.len(),
+            "files": files,
+        }))
+    };
+
+    Tool::new(
+        "changed_since",
+        "List files modified after a timestamp, newest first, to refresh project knowledge incrementally",
+        schema,
+        Box::new(handler),
+    )
+    .resolves_paths()
+}
+
+fn format_system_time(time: SystemTime) -> String {
+    OffsetDateTime::from(time)
+        .format(&Rfc3339)
+        .unwrap_or_else(|_| "1970-01-01T00:00:00Z".to_string())
 }
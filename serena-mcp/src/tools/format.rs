@@ -0,0 +1,175 @@
+use std::env;
+use std::fs;
+use std::io::{ErrorKind, Read, Write};
+use std::path::Path;
+use std::process::{Command, Stdio};
+use std::thread;
+use std::time::{Duration, Instant};
+
+use serde::Serialize;
+
+use crate::tools::symbols::Language;
+
+/// How long a formatter may run before it is killed, unless overridden in
+/// seconds by `SERENA_FORMAT_TIMEOUT_SECS`.
+const DEFAULT_FORMAT_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// Interval between checks on a running formatter.
+const POLL_INTERVAL: Duration = Duration::from_millis(10);
+
+fn format_timeout() -> Duration {
+    env::var("SERENA_FORMAT_TIMEOUT_SECS")
+        .ok()
+        .and_then(|secs| secs.trim().parse().ok())
+        .map_or(DEFAULT_FORMAT_TIMEOUT, Duration::from_secs)
+}
+
+/// What happened when a file was run through its language's formatter.
+#[derive(Debug, Serialize)]
+pub(crate) struct FormatReport {
+    /// Formatter command used; `None` when the language has none.
+    pub(crate) formatter: Option<&'static str>,
+    /// `formatted`, `unsupported`, `not_installed` or `failed`.
+    pub(crate) status: &'static str,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub(crate) message: Option<String>,
+}
+
+/// Format `content` (the new text of `path`) with rustfmt, prettier or
+/// black, reading from stdin so nothing touches disk. Formatters run in the
+/// file's directory so they pick up project configuration. Returns the
+/// formatted text, or `None` when the formatter is missing, rejects the
+/// input or is killed for running too long, alongside a report of what
+/// happened.
+pub(crate) fn format_source(
+    language: Language,
+    path: &Path,
+    content: &str,
+) -> (Option<String>, FormatReport) {
+    let file_name = path.to_string_lossy().to_string();
+    let (formatter, args) = match language {
+        Language::Rust => {
+            let mut args = vec!["--emit".to_string(), "stdout".to_string()];
+            if let Some(edition) = cargo_edition(path) {
+                args.extend(["--edition".to_string(), edition]);
+            }
+            ("rustfmt", args)
+        }
+        Language::Javascript | Language::Typescript => {
+            ("prettier", vec!["--stdin-filepath".to_string(), file_name])
+        }
+        Language::Python => (
+            "black",
+            vec![
+                "--quiet".to_string(),
+                "--stdin-filename".to_string(),
+                file_name,
+                "-".to_string(),
+            ],
+        ),
+        _ => {
+            return (
+                None,
+                FormatReport {
+                    formatter: None,
+                    status: "unsupported",
+                    message: None,
+                },
+            );
+        }
+    };
+
+    let report = |status, message| FormatReport {
+        formatter: Some(formatter),
+        status,
+        message,
+    };
+    let mut command = Command::new(formatter);
+    command
+        .args(&args)
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped());
+    if let Some(dir) = path.parent().filter(|dir| dir.is_dir()) {
+        command.current_dir(dir);
+    }
+    let mut child = match command.spawn() {
+        Ok(child) => child,
+        Err(err) if err.kind() == ErrorKind::NotFound => {
+            return (None, report("not_installed", None));
+        }
+        Err(err) => return (None, report("failed", Some(err.to_string()))),
+    };
+
+    // Writing and reading on other threads keeps a formatter that streams
+    // output before reading all input from deadlocking on full pipes.
+    let input = content.to_string();
+    let stdin = child.stdin.take();
+    let writer = thread::spawn(move || {
+        if let Some(mut stdin) = stdin {
+            let _ = stdin.write_all(input.as_bytes());
+        }
+    });
+    let stdout = read_pipe(child.stdout.take());
+    let stderr = read_pipe(child.stderr.take());
+
+    let timeout = format_timeout();
+    let deadline = Instant::now() + timeout;
+    let status = loop {
+        match child.try_wait() {
+            Ok(Some(status)) => break status,
+            Ok(None) if Instant::now() >= deadline => {
+                let _ = child.kill();
+                let _ = child.wait();
+                // Processes the formatter started may still hold the pipes,
+                // so the I/O threads are left to finish on their own.
+                let message =
+                    format!("{formatter} did not finish within {timeout:?} and was killed");
+                return (None, report("failed", Some(message)));
+            }
+            Ok(None) => thread::sleep(POLL_INTERVAL),
+            Err(err) => return (None, report("failed", Some(err.to_string()))),
+        }
+    };
+    let _ = writer.join();
+    let stdout = stdout.join().unwrap_or_default();
+    let stderr = stderr.join().unwrap_or_default();
+
+    if !status.success() {
+        let stderr = String::from_utf8_lossy(&stderr).trim().to_string();
+        return (None, report("failed", Some(stderr)));
+    }
+    match String::from_utf8(stdout) {
+        Ok(formatted) => (Some(formatted), report("formatted", None)),
+        Err(_) => (
+            None,
+            report("failed", Some("formatter output is not UTF-8".to_string())),
+        ),
+    }
+}
+
+/// Drain `pipe` on its own thread.
+fn read_pipe(pipe: Option<impl Read + Send + 'static>) -> thread::JoinHandle<Vec<u8>> {
+    thread::spawn(move || {
+        let mut bytes = Vec::new();
+        if let Some(mut pipe) = pipe {
+            let _ = pipe.read_to_end(&mut bytes);
+        }
+        bytes
+    })
+}
+
+/// `edition` of the nearest `Cargo.toml` above `path`, which rustfmt needs
+/// when reading from stdin.
+fn cargo_edition(path: &Path) -> Option<String> {
+    let manifest = path
+        .ancestors()
+        .skip(1)
+        .map(|dir| dir.join("Cargo.toml"))
+        .find(|manifest| manifest.is_file())?;
+    fs::read_to_string(manifest).ok()?.lines().find_map(|line| {
+        let value = line.trim().strip_prefix("edition")?.trim_start();
+        let value = value.strip_prefix('=')?.trim();
+        Some(value.trim_matches(['"', '\'']).to_string())
+    })
+}
@@ -0,0 +1,426 @@
+use std::collections::{BTreeMap, HashSet};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::UNIX_EPOCH;
+
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+use serde_json::{Value, json};
+use time::{OffsetDateTime, format_description::well_known::Rfc3339};
+
+use crate::notifications::ProgressReporter;
+use crate::path_style::display_path;
+use crate::tool::{Tool, ToolRegistry};
+use crate::tools::symbols::{ParsedFile, symbol_name_matches};
+use crate::tools::{
+    DEFAULT_SEARCH_MAX_DEPTH, DEFAULT_SEARCH_MAX_FILES, ExcludeSet, FileWalker, WalkFilter,
+    base_dir, resolve_path, state_dir, writable_state_dir,
+};
+
+/// Bumped whenever the on-disk layout changes; older indexes are rebuilt.
+const INDEX_VERSION: u32 = 1;
+
+pub fn register(registry: &mut ToolRegistry) {
+    registry.register(build_symbol_index_tool());
+    registry.register(query_symbol_index_tool());
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+struct SymbolIndex {
+    version: u32,
+    root: String,
+    built_at: String,
+    /// Keyed by path relative to `root`.
+    files: BTreeMap<String, IndexedFile>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct IndexedFile {
+    /// Source modification time in nanoseconds since the Unix epoch.
+    mtime_ns: u64,
+    language: String,
+    symbols: Vec<IndexedSymbol>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct IndexedSymbol {
+    name: String,
+    kind: String,
+    line: usize,
+    column: usize,
+    signature: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    container: Option<String>,
+}
+
+/// Location of the persisted index for the project rooted at `root`. Nothing
+/// is created; see [`create_index_dir`].
+fn index_path(root: &Path) -> Result<PathBuf> {
+    Ok(state_dir()?
+        .join("index")
+        .join(format!("{}.json", project_hash(root))))
+}
+
+/// Create the directory [`index_path`] lives in, ahead of writing an index.
+fn create_index_dir() -> Result<()> {
+    let dir = writable_state_dir()?.join("index");
+    fs::create_dir_all(&dir)
+        .with_context(|| format!("Failed to create index dir at {}", dir.display()))
+}
+
+/// Stable FNV-1a hash of the project root, used as the index file name.
+fn project_hash(root: &Path) -> String {
+    let mut hash: u64 = 0xcbf2_9ce4_8422_2325;
+    for byte in root.to_string_lossy().bytes() {
+        hash ^= u64::from(byte);
+        hash = hash.wrapping_mul(0x0100_0000_01b3);
+    }
+    format!("{hash:016x}")
+}
+
+fn project_root(path: Option<&str>) -> Result<PathBuf> {
+    let root = match path {
+        Some(path) => resolve_path(path)?,
+        None => base_dir()?,
+    };
+    if !root.is_dir() {
+        anyhow::bail!("Project root {} is not a directory", root.display());
+    }
+    root.canonicalize()
+        .with_context(|| format!("Failed to canonicalize {}", root.display()))
+}
+
+fn mtime_ns(path: &Path) -> Option<u64> {
+    let modified = fs::metadata(path).ok()?.modified().ok()?;
+    let nanos = modified.duration_since(UNIX_EPOCH).ok()?.as_nanos();
+    u64::try_from(nanos).ok()
+}
+
+fn load_index(path: &Path) -> Result<Option<SymbolIndex>> {
+    if !path.exists() {
+        return Ok(None);
+    }
+    let bytes = fs::read(path)
+        .with_context(|| format!("Failed to read symbol index at {}", path.display()))?;
+    let index: SymbolIndex = serde_json::from_slice(&bytes)
+        .with_context(|| format!("Failed to parse symbol index at {}", path.display()))?;
+    Ok(Some(index))
+}
+
+fn index_file(path: &Path, mtime_ns: u64) -> Result<Option<IndexedFile>> {
+    let Some(parsed) = ParsedFile::from_path(path)? else {
+        return Ok(None);
+    };
+    let symbols = parsed
+        .symbols
+        .iter()
+        .map(|symbol| IndexedSymbol {
+            name: symbol.name.clone(),
+            kind: symbol.kind.clone(),
+            line: symbol.line,
+            column: symbol.column,
+            signature: symbol.signature.clone(),
+            container: symbol.enclosing.clone(),
+        })
+        .collect();
+    Ok(Some(IndexedFile {
+        mtime_ns,
+        language: parsed.language.as_str().to_string(),
+        symbols,
+    }))
+}
+
+fn build_symbol_index_tool() -> Tool {
+    let schema = json!({
+        "type": "object",
+        "properties": {
+            "path": {
+                "type": "string",
+                "description": "Project root to index. Defaults to current working directory.",
+            },
+            "incremental": {
+                "type": "boolean",
+                "description": "Reuse entries from the existing index for files whose modification time is unchanged",
+                "default": true,
+            },
+            "max_depth": {
+                "type": "integer",
+                "minimum": 1,
+                "description": "Maximum directory depth to descend (default 16)",
+            },
+            "max_files": {
+                "type": "integer",
+                "minimum": 1,
+                "description": "Stop after scanning this many files (default 10000)",
+            },
+            "exclude": {
+                "type": "array",
+                "items": {"type": "string"},
+                "description": "Glob patterns (relative to the project root) for files or directories to skip",
+            }
+        },
+        "examples": [{"path": ".", "exclude": ["target/**"]}],
+        "x-positional-order": ["path", "incremental", "max_depth", "max_files", "exclude"],
+        "additionalProperties": false
+    });
+
+    #[derive(Deserialize)]
+    struct Params {
+        #[serde(default)]
+        path: Option<String>,
+        #[serde(default = "default_true")]
+        incremental: bool,
+        #[serde(default)]
+        max_depth: Option<usize>,
+        #[serde(default)]
+        max_files: Option<usize>,
+        #[serde(default)]
+        exclude: Option<Vec<String>>,
+    }
+
+    let handler = move |params| -> Result<Value> {
+        let args: Params =
+            serde_json::from_value(params).context("Invalid arguments for build_symbol_index")?;
+        let root = project_root(args.path.as_deref())?;
+        let path = index_path(&root)?;
+        let max_depth = args.max_depth.unwrap_or(DEFAULT_SEARCH_MAX_DEPTH);
+        let max_files = args.max_files.unwrap_or(DEFAULT_SEARCH_MAX_FILES);
+        let exclude = ExcludeSet::new(&root, args.exclude.as_deref())?;
+
+        let mut previous = if args.incremental {
+            // A corrupt or outdated index is simply rebuilt from scratch.
+            load_index(&path)
+                .ok()
+                .flatten()
+                .filter(|index| index.version == INDEX_VERSION)
+                .map(|index| index.files)
+                .unwrap_or_default()
+        } else {
+            BTreeMap::new()
+        };
+
+        let mut files = BTreeMap::new();
+        let mut files_reused = 0usize;
+        let mut files_parsed = 0usize;
+        let mut progress = ProgressReporter::new("build_symbol_index");
+        let mut walker = FileWalker::new(WalkFilter::Default, max_depth, max_files);
+        walker.walk(&root, &exclude, |path| {
+            let Some(mtime) = mtime_ns(path) else {
+                return Ok(true);
+            };
+            let relative = path
+                .strip_prefix(&root)
+                .unwrap_or(path)
+                .to_string_lossy()
+                .to_string();
+
+            if let Some(cached) = previous.remove(&relative)
+                && cached.mtime_ns == mtime
+            {
+                files.insert(relative, cached);
+                files_reused += 1;
+            } else if let Some(indexed) = index_file(path, mtime)? {
+                files.insert(relative, indexed);
+                files_parsed += 1;
+            }
+            progress.file_visited();
+            Ok(true)
+        })?;
+
+        // Entries never visited belong to files that were deleted, are now
+        // excluded, or lie beyond the file cap; only the first two count as
+        // removed.
+        let files_removed = previous
+            .keys()
+            .map(|relative| root.join(relative))
+            .filter(|path| !path.is_file() || exclude.is_excluded(path))
+            .count();
+        let symbol_count: usize = files.values().map(|file| file.symbols.len()).sum();
+        let built_at = OffsetDateTime::now_utc()
+            .format(&Rfc3339)
+            .context("Failed to format timestamp")?;
+        let index = SymbolIndex {
+            version: INDEX_VERSION,
+            root: root.to_string_lossy().to_string(),
+            built_at: built_at.clone(),
+            files,
+        };
+        let payload = serde_json::to_vec(&index).context("Failed to serialise symbol index")?;
+        create_index_dir()?;
+        fs::write(&path, payload)
+            .with_context(|| format!("Failed to write symbol index at {}", path.display()))?;
+
+        Ok(json!({
+            "project_root": display_path(&root),
+            "index_path": path.to_string_lossy(),
+            "files_indexed": index.files.len(),
+            "files_reused": files_reused,
+            "files_parsed": files_parsed,
+            "files_removed": files_removed,
+            "files_capped": walker.files_capped,
+            "symbol_count": symbol_count,
+            "built_at": built_at,
+        }))
+    };
+
+    Tool::new(
+        "build_symbol_index",
+        "Index every symbol in the project and persist the result for query_symbol_index",
+        schema,
+        Box::new(handler),
+    )
+    .mutating()
+    .resolves_paths()
+}
+
+fn query_symbol_index_tool() -> Tool {
+    let schema = json!({
+        "type": "object",
+        "properties": {
+            "name": {
+                "type": "string",
+                "description": "Symbol name or pattern to search for",
+            },
+            "path": {
+                "type": "string",
+                "description": "Project root whose index to query. Defaults to current working directory.",
+            },
+            "match_substring": {
+                "type": "boolean",
+                "description": "Allow substring matches instead of exact matches",
+                "default": true,
+            },
+            "case_sensitive": {
+                "type": "boolean",
+                "description": "Whether matching is case sensitive",
+                "default": false,
+            },
+            "kinds": {
+                "type": "array",
+                "items": {"type": "string"},
+                "description": "Restrict to specific symbol kinds (e.g. function, class)",
+            },
+            "max_results": {
+                "type": "integer",
+                "minimum": 1,
+                "description": "Maximum number of results to return (default 50)",
+            }
+        },
+        "required": ["name"],
+        "examples": [{"name": "parse", "kinds": ["function"]}],
+        "x-positional-order": [
+            "name", "path", "match_substring", "case_sensitive", "kinds", "max_results"
+        ],
+        "additionalProperties": false
+    });
+
+    #[derive(Deserialize)]
+    struct Params {
+        name: String,
+        #[serde(default)]
+        path: Option<String>,
+        #[serde(default = "default_true")]
+        match_substring: bool,
+        #[serde(default)]
+        case_sensitive: bool,
+        #[serde(default)]
+        kinds: Option<Vec<String>>,
+        #[serde(default)]
+        max_results: Option<usize>,
+    }
+
+    let handler = move |params| -> Result<Value> {
+        let args: Params =
+            serde_json::from_value(params).context("Invalid arguments for query_symbol_index")?;
+        let root = project_root(args.path.as_deref())?;
+        let path = index_path(&root)?;
+        let Some(index) = load_index(&path)? else {
+            anyhow::bail!(
+                "No symbol index for {}; run build_symbol_index first",
+                root.display()
+            );
+        };
+        if index.version != INDEX_VERSION {
+            anyhow::bail!(
+                "Symbol index for {} is outdated; run build_symbol_index again",
+                root.display()
+            );
+        }
+
+        let max_results = args.max_results.unwrap_or(50);
+        let kind_filter: Option<HashSet<String>> = args
+            .kinds
+            .as_ref()
+            .map(|kinds| kinds.iter().map(|s| s.to_lowercase()).collect());
+
+        let mut matches = Vec::new();
+        let mut stale_files = Vec::new();
+        let mut truncated = false;
+        for (relative, file) in &index.files {
+            let absolute = root.join(relative);
+            let mut stale = None;
+            for symbol in &file.symbols {
+                if let Some(filter) = &kind_filter
+                    && !filter.contains(&symbol.kind.to_lowercase())
+                {
+                    continue;
+                }
+                if !symbol_name_matches(
+                    &symbol.name,
+                    &args.name,
+                    args.match_substring,
+                    args.case_sensitive,
+                ) {
+                    continue;
+                }
+                if matches.len() >= max_results {
+                    truncated = true;
+                    break;
+                }
+                // Only stat files that actually contribute a match.
+                let stale = *stale.get_or_insert_with(|| {
+                    let stale = mtime_ns(&absolute) != Some(file.mtime_ns);
+                    if stale {
+                        stale_files.push(display_path(&absolute));
+                    }
+                    stale
+                });
+                matches.push(json!({
+                    "name": symbol.name,
+                    "kind": symbol.kind,
+                    "path": display_path(&absolute),
+                    "line": symbol.line,
+                    "column": symbol.column,
+                    "signature": symbol.signature,
+                    "container": symbol.container,
+                    "language": file.language,
+                    "stale": stale,
+                }));
+            }
+            if truncated {
+                break;
+            }
+        }
+
+        Ok(json!({
+            "query": args.name,
+            "count": matches.len(),
+            "truncated": truncated,
+            "index_built_at": index.built_at,
+            "stale_files": stale_files,
+            "matches": matches,
+        }))
+    };
+
+    Tool::new(
+        "query_symbol_index",
+        "Search the persisted symbol index without re-walking the project; matches in files changed since the last build are flagged stale",
+        schema,
+        Box::new(handler),
+    )
+    .resolves_paths()
+}
+
+fn default_true() -> bool {
+    true
+}
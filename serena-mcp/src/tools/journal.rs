@@ -0,0 +1,192 @@
+use std::fs::{self, OpenOptions};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use anyhow::{Context, Result};
+use log::warn;
+use once_cell::sync::Lazy;
+use serde::{Deserialize, Serialize};
+use serde_json::{Value, json};
+use time::{OffsetDateTime, format_description::well_known::Rfc3339};
+
+use crate::path_style::display_path;
+use crate::tool::{Tool, ToolRegistry};
+use crate::tools::{resolve_path, state_file, writable_state_file};
+
+const JOURNAL_FILE: &str = "edit_journal.jsonl";
+
+/// Past this size the journal is rewritten with only its newer half.
+const MAX_JOURNAL_BYTES: u64 = 1024 * 1024;
+
+/// Identifies this server process, so edits from earlier sessions can be
+/// told apart from the current one.
+static SESSION_ID: Lazy<String> = Lazy::new(|| {
+    let started = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|elapsed| elapsed.as_secs())
+        .unwrap_or_default();
+    format!("{started}-{}", std::process::id())
+});
+
+pub fn register(registry: &mut ToolRegistry) {
+    registry.register(recent_edits_tool());
+}
+
+/// One successful mutation, as stored one JSON object per line.
+#[derive(Debug, Serialize, Deserialize)]
+struct JournalEntry {
+    session: String,
+    at: String,
+    tool: String,
+    path: PathBuf,
+    /// Symbols the edit targeted; empty for whole-file writes.
+    #[serde(default)]
+    symbols: Vec<String>,
+    /// Short description of the edit, e.g. the line range or rename.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    detail: Option<String>,
+}
+
+/// Append an edit made by `tool` to the session journal. Failures are logged
+/// rather than returned so a journal problem never fails the edit itself.
+pub(crate) fn record_edit(tool: &str, path: &Path, symbols: Vec<String>, detail: Option<String>) {
+    let entry = JournalEntry {
+        session: SESSION_ID.clone(),
+        at: OffsetDateTime::now_utc()
+            .format(&Rfc3339)
+            .unwrap_or_else(|_| "1970-01-01T00:00:00Z".to_string()),
+        tool: tool.to_string(),
+        path: path.to_path_buf(),
+        symbols,
+        detail,
+    };
+    if let Err(err) = append(&entry) {
+        warn!(
+            "Failed to record {tool} edit of {} in the edit journal: {err:#}",
+            path.display()
+        );
+    }
+}
+
+fn append(entry: &JournalEntry) -> Result<()> {
+    let path = writable_state_file(JOURNAL_FILE)?;
+    let mut line = serde_json::to_string(entry).context("Failed to serialise journal entry")?;
+    line.push('\n');
+    let mut file = OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(&path)
+        .with_context(|| format!("Failed to open edit journal at {}", path.display()))?;
+    file.write_all(line.as_bytes())
+        .with_context(|| format!("Failed to write edit journal at {}", path.display()))?;
+
+    if file.metadata()?.len() > MAX_JOURNAL_BYTES {
+        let content = fs::read_to_string(&path)
+            .with_context(|| format!("Failed to read edit journal at {}", path.display()))?;
+        let lines = content.lines().collect::<Vec<_>>();
+        let mut kept = lines[lines.len() / 2..].join("\n");
+        kept.push('\n');
+        fs::write(&path, kept)
+            .with_context(|| format!("Failed to trim edit journal at {}", path.display()))?;
+    }
+    Ok(())
+}
+
+/// Every readable journal entry, oldest first. Malformed lines (e.g. from
+/// an interrupted write) are skipped.
+fn load() -> Result<Vec<JournalEntry>> {
+    let path = state_file(JOURNAL_FILE)?;
+    if !path.exists() {
+        return Ok(Vec::new());
+    }
+    let content = fs::read_to_string(&path)
+        .with_context(|| format!("Failed to read edit journal at {}", path.display()))?;
+    Ok(content
+        .lines()
+        .filter_map(|line| serde_json::from_str(line).ok())
+        .collect())
+}
+
+fn recent_edits_tool() -> Tool {
+    let schema = json!({
+        "type": "object",
+        "properties": {
+            "limit": {
+                "type": "integer",
+                "minimum": 1,
+                "description": "Maximum number of edits to return, newest first",
+                "default": 20,
+            },
+            "path": {
+                "type": "string",
+                "description": "Only edits of this file, or of files under this directory",
+            },
+            "all_sessions": {
+                "type": "boolean",
+                "description": "Include edits made by earlier server sessions",
+                "default": false,
+            }
+        },
+        "examples": [{"limit": 10}, {"path": "src", "all_sessions": true}],
+        "x-positional-order": ["limit", "path", "all_sessions"],
+        "additionalProperties": false
+    });
+
+    #[derive(Deserialize)]
+    struct Params {
+        #[serde(default)]
+        limit: Option<usize>,
+        #[serde(default)]
+        path: Option<String>,
+        #[serde(default)]
+        all_sessions: bool,
+    }
+
+    let handler = move |params| -> Result<Value> {
+        let args: Params =
+            serde_json::from_value(params).context("Invalid arguments for recent_edits")?;
+        let filter = args.path.as_deref().map(resolve_path).transpose()?;
+        let limit = args.limit.unwrap_or(20);
+
+        let matching = load()?
+            .into_iter()
+            .rev()
+            .filter(|entry| args.all_sessions || entry.session == *SESSION_ID)
+            .filter(|entry| {
+                filter
+                    .as_deref()
+                    .is_none_or(|filter| entry.path.starts_with(filter))
+            })
+            .collect::<Vec<_>>();
+        let edits = matching
+            .iter()
+            .take(limit)
+            .map(|entry| {
+                json!({
+                    "at": entry.at,
+                    "tool": entry.tool,
+                    "path": display_path(&entry.path),
+                    "symbols": entry.symbols,
+                    "detail": entry.detail,
+                    "session": entry.session,
+                })
+            })
+            .collect::<Vec<_>>();
+
+        Ok(json!({
+            "session": *SESSION_ID,
+            "total": matching.len(),
+            "count": edits.len(),
+            "edits": edits,
+        }))
+    };
+
+    Tool::new(
+        "recent_edits",
+        "List the most recent successful replace_symbol_body, rename_symbol and write_file edits from this session's journal",
+        schema,
+        Box::new(handler),
+    )
+    .resolves_paths()
+}
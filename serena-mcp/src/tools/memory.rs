@@ -1,19 +1,25 @@
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::env;
 use std::fs;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 
 use anyhow::{Context, Result};
+use log::{info, warn};
 use serde::{Deserialize, Serialize};
 use serde_json::{Value, json};
-use time::{OffsetDateTime, format_description::well_known::Rfc3339};
+use time::{Duration, OffsetDateTime, format_description::well_known::Rfc3339};
 
 use crate::tool::{Tool, ToolRegistry};
-use crate::tools::state_file;
+use crate::tools::{is_read_only, state_file, writable_state_dir};
 
 pub fn register(registry: &mut ToolRegistry) {
     registry.register(write_memory_tool());
+    registry.register(append_memory_tool());
     registry.register(read_memory_tool());
     registry.register(list_memories_tool());
     registry.register(delete_memory_tool());
+    registry.register(compact_memories_tool());
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -28,24 +34,56 @@ struct MemoryEntry {
     created_at: String,
     #[serde(default)]
     updated_at: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    expires_at: Option<String>,
 }
 
 impl MemoryEntry {
+    /// When the entry was last written: `updated_at`, else `created_at`.
+    fn last_changed(&self) -> Option<OffsetDateTime> {
+        let stamp = self.updated_at.as_deref().unwrap_or(&self.created_at);
+        OffsetDateTime::parse(stamp, &Rfc3339).ok()
+    }
+
+    fn is_expired(&self, now: OffsetDateTime) -> bool {
+        self.expires_at
+            .as_deref()
+            .and_then(|expires| OffsetDateTime::parse(expires, &Rfc3339).ok())
+            .is_some_and(|expires| expires <= now)
+    }
+
     fn matches(&self, filter: &MemoryFilter) -> bool {
-        if let Some(id) = &filter.id {
-            if &self.id != id {
-                return false;
-            }
+        if let Some(id) = &filter.id
+            && &self.id != id
+        {
+            return false;
         }
 
-        if let Some(namespace) = &filter.namespace {
-            if &self.namespace != namespace {
+        if let Some(namespace) = &filter.namespace
+            && &self.namespace != namespace
+        {
+            return false;
+        }
+
+        if !filter.tags.is_empty() {
+            let has_tag = |tag: &String| self.tags.contains(tag);
+            let tag_match = match filter.tag_match {
+                TagMatch::Any => filter.tags.iter().any(has_tag),
+                TagMatch::All => filter.tags.iter().all(has_tag),
+            };
+            if !tag_match {
                 return false;
             }
         }
 
-        if let Some(tag) = &filter.tag {
-            if !self.tags.iter().any(|t| t == tag) {
+        if let Some(key) = &filter.metadata_key {
+            let value = self.metadata.get(key);
+            let matched = match &filter.metadata_value {
+                None => value.is_some(),
+                Some(Value::Null) => value.is_none_or(Value::is_null),
+                Some(expected) => value == Some(expected),
+            };
+            if !matched {
                 return false;
             }
         }
@@ -56,11 +94,9 @@ impl MemoryEntry {
             let metadata_match = self
                 .metadata
                 .as_object()
-                .and_then(|obj| {
-                    Some(
-                        obj.values()
-                            .any(|value| value.to_string().to_lowercase().contains(&needle)),
-                    )
+                .map(|obj| {
+                    obj.values()
+                        .any(|value| value.to_string().to_lowercase().contains(&needle))
                 })
                 .unwrap_or(false);
 
@@ -73,53 +109,313 @@ impl MemoryEntry {
     }
 }
 
+/// Env var selecting the memory store layout: `single` keeps every entry in
+/// `memories.json`, `per-namespace` stores each namespace in
+/// `memories/<namespace>.json` so a change only rewrites its namespace. When
+/// unset, the per-namespace layout is used once its directory exists.
+const MEMORY_LAYOUT_ENV: &str = "SERENA_MEMORY_LAYOUT";
+
+enum MemoryLayout {
+    Single(PathBuf),
+    PerNamespace(PathBuf),
+}
+
 struct MemoryStore {
-    path: PathBuf,
+    layout: MemoryLayout,
+    /// Namespace files as last read or written, keyed by file stem, so `save`
+    /// can skip namespaces whose content did not change.
+    loaded: RefCell<HashMap<String, Vec<u8>>>,
 }
 
 impl MemoryStore {
     fn new() -> Result<Self> {
-        let path = state_file("memories.json")?;
-        if !path.exists() {
-            fs::write(&path, b"[]")
-                .with_context(|| format!("Failed to initialise memory store at {path:?}"))?;
+        let single = state_file("memories.json")?;
+        let dir = state_file("memories")?;
+        let per_namespace = per_namespace_layout(&dir)?;
+
+        // A read-only server reads whatever exists and never initialises or
+        // migrates the store.
+        let read_only = is_read_only();
+        if !per_namespace || (read_only && !dir.is_dir()) {
+            if dir.is_dir() {
+                warn!(
+                    "Ignoring per-namespace memories in {} because {MEMORY_LAYOUT_ENV}=single",
+                    dir.display()
+                );
+            }
+            if !single.exists() && !read_only {
+                writable_state_dir()?;
+                fs::write(&single, b"[]")
+                    .with_context(|| format!("Failed to initialise memory store at {single:?}"))?;
+            }
+            return Ok(Self {
+                layout: MemoryLayout::Single(single),
+                loaded: RefCell::default(),
+            });
+        }
+
+        if !read_only {
+            writable_state_dir()?;
+            fs::create_dir_all(&dir)
+                .with_context(|| format!("Failed to create memory store at {dir:?}"))?;
+        }
+        let store = Self {
+            layout: MemoryLayout::PerNamespace(dir),
+            loaded: RefCell::default(),
+        };
+        if single.exists() && !read_only {
+            store.migrate_from(&single)?;
+        }
+        Ok(store)
+    }
+
+    /// Split a legacy single-file store into namespace files and set the
+    /// old file aside as `memories.json.migrated`.
+    fn migrate_from(&self, single: &Path) -> Result<()> {
+        let mut entries = self.load()?;
+        let legacy = read_entries(single)?;
+        info!(
+            "Migrating {} memories from {} to per-namespace files",
+            legacy.len(),
+            single.display()
+        );
+        // An id present in both stores keeps whichever copy changed last.
+        for entry in legacy {
+            match entries.iter_mut().find(|existing| existing.id == entry.id) {
+                Some(existing) if entry.last_changed() > existing.last_changed() => {
+                    *existing = entry
+                }
+                Some(_) => {}
+                None => entries.push(entry),
+            }
         }
-        Ok(Self { path })
+        self.save(&entries)?;
+        let migrated = single.with_extension("json.migrated");
+        fs::rename(single, &migrated)
+            .with_context(|| format!("Failed to move {} aside", single.display()))
     }
 
     fn load(&self) -> Result<Vec<MemoryEntry>> {
-        let bytes = fs::read(&self.path)
-            .with_context(|| format!("Failed to read memory store at {}", self.path.display()))?;
-        if bytes.is_empty() {
-            return Ok(Vec::new());
+        let dir = match &self.layout {
+            MemoryLayout::Single(path) if !path.exists() => return Ok(Vec::new()),
+            MemoryLayout::Single(path) => return read_entries(path),
+            MemoryLayout::PerNamespace(dir) => dir,
+        };
+
+        let mut files = namespace_files(dir)?;
+        files.sort();
+        let mut loaded = self.loaded.borrow_mut();
+        let mut entries = Vec::new();
+        for (stem, path) in files {
+            let bytes = fs::read(&path)
+                .with_context(|| format!("Failed to read memory store at {}", path.display()))?;
+            if !bytes.is_empty() {
+                entries.extend(
+                    serde_json::from_slice::<Vec<MemoryEntry>>(&bytes).with_context(|| {
+                        format!("Failed to parse memory store at {}", path.display())
+                    })?,
+                );
+            }
+            loaded.insert(stem, bytes);
         }
-        let entries = serde_json::from_slice(&bytes)
-            .with_context(|| format!("Failed to parse memory store at {}", self.path.display()))?;
         Ok(entries)
     }
 
     fn save(&self, entries: &[MemoryEntry]) -> Result<()> {
-        let payload =
-            serde_json::to_vec_pretty(entries).context("Failed to serialise memory store")?;
-        fs::write(&self.path, payload)
-            .with_context(|| format!("Failed to write memory store at {}", self.path.display()))
+        writable_state_dir()?;
+        let dir = match &self.layout {
+            MemoryLayout::Single(path) => {
+                let payload = serde_json::to_vec_pretty(entries)
+                    .context("Failed to serialise memory store")?;
+                return fs::write(path, payload).with_context(|| {
+                    format!("Failed to write memory store at {}", path.display())
+                });
+            }
+            MemoryLayout::PerNamespace(dir) => dir,
+        };
+
+        let mut namespaces: HashMap<String, Vec<&MemoryEntry>> = HashMap::new();
+        for entry in entries {
+            namespaces
+                .entry(namespace_file_stem(&entry.namespace))
+                .or_default()
+                .push(entry);
+        }
+
+        let mut loaded = self.loaded.borrow_mut();
+        for (stem, group) in &namespaces {
+            let payload =
+                serde_json::to_vec_pretty(group).context("Failed to serialise memory store")?;
+            if loaded.get(stem) == Some(&payload) {
+                continue;
+            }
+            let path = dir.join(format!("{stem}.json"));
+            fs::write(&path, &payload)
+                .with_context(|| format!("Failed to write memory store at {}", path.display()))?;
+            loaded.insert(stem.clone(), payload);
+        }
+
+        let emptied = loaded
+            .keys()
+            .filter(|stem| !namespaces.contains_key(*stem))
+            .cloned()
+            .collect::<Vec<_>>();
+        for stem in emptied {
+            let path = dir.join(format!("{stem}.json"));
+            fs::remove_file(&path)
+                .with_context(|| format!("Failed to remove {}", path.display()))?;
+            loaded.remove(&stem);
+        }
+        Ok(())
+    }
+
+    /// Total size of the store's files in bytes.
+    fn size_bytes(&self) -> u64 {
+        let file_len = |path: &Path| fs::metadata(path).map(|m| m.len()).unwrap_or(0);
+        match &self.layout {
+            MemoryLayout::Single(path) => file_len(path),
+            MemoryLayout::PerNamespace(dir) => namespace_files(dir)
+                .unwrap_or_default()
+                .iter()
+                .map(|(_, path)| file_len(path))
+                .sum(),
+        }
     }
 }
 
+/// Whether memories live in per-namespace files under `dir`, per
+/// [`MEMORY_LAYOUT_ENV`] or, when unset, whether `dir` exists.
+fn per_namespace_layout(dir: &Path) -> Result<bool> {
+    match env::var(MEMORY_LAYOUT_ENV).as_deref() {
+        Ok("per-namespace") => Ok(true),
+        Ok("single") => Ok(false),
+        Ok(other) => anyhow::bail!(
+            "Unknown {MEMORY_LAYOUT_ENV} value '{other}'; expected `single` or `per-namespace`"
+        ),
+        Err(_) => Ok(dir.is_dir()),
+    }
+}
+
+/// Name of the memory layout the memory tools would use.
+pub(crate) fn memory_layout() -> Result<&'static str> {
+    let per_namespace = per_namespace_layout(&state_file("memories")?)?;
+    Ok(if per_namespace {
+        "per-namespace"
+    } else {
+        "single"
+    })
+}
+
+fn read_entries(path: &Path) -> Result<Vec<MemoryEntry>> {
+    let bytes = fs::read(path)
+        .with_context(|| format!("Failed to read memory store at {}", path.display()))?;
+    if bytes.is_empty() {
+        return Ok(Vec::new());
+    }
+    let entries = serde_json::from_slice(&bytes)
+        .with_context(|| format!("Failed to parse memory store at {}", path.display()))?;
+    Ok(entries)
+}
+
+/// `(stem, path)` of every namespace file in `dir`.
+fn namespace_files(dir: &Path) -> Result<Vec<(String, PathBuf)>> {
+    let mut files = Vec::new();
+    for entry in
+        fs::read_dir(dir).with_context(|| format!("Failed to list memory store {dir:?}"))?
+    {
+        let path = entry?.path();
+        if path.extension().is_some_and(|ext| ext == "json")
+            && let Some(stem) = path.file_stem()
+        {
+            files.push((stem.to_string_lossy().to_string(), path));
+        }
+    }
+    Ok(files)
+}
+
+/// File stem for `namespace`: ASCII alphanumerics, `-` and `_` are kept and
+/// every other byte becomes `%XX`, so distinct namespaces never share a file.
+/// The empty namespace is `%`, which escaping never produces.
+fn namespace_file_stem(namespace: &str) -> String {
+    if namespace.is_empty() {
+        return "%".to_string();
+    }
+    namespace
+        .bytes()
+        .map(|byte| match byte {
+            b'a'..=b'z' | b'A'..=b'Z' | b'0'..=b'9' | b'-' | b'_' => (byte as char).to_string(),
+            _ => format!("%{byte:02X}"),
+        })
+        .collect()
+}
+
 #[derive(Debug, Default)]
 struct MemoryFilter {
     id: Option<String>,
     namespace: Option<String>,
-    tag: Option<String>,
+    tags: Vec<String>,
+    tag_match: TagMatch,
+    metadata_key: Option<String>,
+    /// `Some(Value::Null)` matches a null or missing value.
+    metadata_value: Option<Value>,
     query: Option<String>,
 }
 
+/// How a multi-tag filter combines its tags.
+#[derive(Debug, Default, Clone, Copy, Deserialize)]
+#[serde(rename_all = "lowercase")]
+enum TagMatch {
+    #[default]
+    Any,
+    All,
+}
+
+/// A single tag or a list of tags; the string form is kept for backward
+/// compatibility with the original single-tag filter.
+#[derive(Debug, Deserialize)]
+#[serde(untagged)]
+enum TagList {
+    One(String),
+    Many(Vec<String>),
+}
+
+impl TagList {
+    fn into_vec(self) -> Vec<String> {
+        match self {
+            TagList::One(tag) => vec![tag],
+            TagList::Many(tags) => tags,
+        }
+    }
+}
+
+/// Sort order of `list_memories` results.
+#[derive(Debug, Default, Clone, Copy, Deserialize)]
+#[serde(rename_all = "lowercase")]
+enum MemoryOrder {
+    #[default]
+    Recent,
+    Id,
+    Namespace,
+}
+
+/// Namespace of memories written without one.
+const DEFAULT_NAMESPACE: &str = "default";
+/// Upper bound for `ttl_seconds` (100 years), well inside the range of
+/// timestamps `time` can represent.
+const MAX_TTL_SECONDS: i64 = 100 * 365 * 24 * 60 * 60;
+
+/// Trim a `namespace` argument. Schemas require a non-whitespace character,
+/// so blank names never create or query "ghost" namespaces.
+fn trim_namespace(namespace: Option<String>) -> Option<String> {
+    namespace.map(|namespace| namespace.trim().to_string())
+}
+
 fn write_memory_tool() -> Tool {
     let schema = json!({
         "type": "object",
         "properties": {
             "content": {"type": "string", "description": "Free-form content to store."},
-            "namespace": {"type": "string", "description": "Logical namespace for grouping memories.", "default": "default"},
+            "namespace": {"type": "string", "pattern": "\\S", "description": "Logical namespace for grouping memories; surrounding whitespace is trimmed and blank names are rejected.", "default": "default"},
             "tags": {
                 "type": "array",
                 "items": {"type": "string"},
@@ -132,9 +428,17 @@ fn write_memory_tool() -> Tool {
             "id": {
                 "type": "string",
                 "description": "Override the generated identifier or update an existing entry",
+            },
+            "ttl_seconds": {
+                "type": ["integer", "null"],
+                "minimum": 0,
+                "maximum": MAX_TTL_SECONDS,
+                "description": "Expire the memory after this many seconds; expired entries are hidden and removed by compact_memories. When updating, null or 0 clears an existing expiry and omitting it keeps the current one",
             }
         },
         "required": ["content"],
+        "examples": [{"content": "Build with cargo build --workspace", "namespace": "project", "tags": ["build"]}],
+        "x-positional-order": ["content", "namespace", "tags", "metadata", "id", "ttl_seconds"],
         "additionalProperties": false
     });
 
@@ -149,6 +453,16 @@ fn write_memory_tool() -> Tool {
         metadata: Option<Value>,
         #[serde(default)]
         id: Option<String>,
+        /// `None` when omitted, `Some(None)` for an explicit `null`.
+        #[serde(default, deserialize_with = "explicit_ttl")]
+        ttl_seconds: Option<Option<i64>>,
+    }
+
+    fn explicit_ttl<'de, D>(deserializer: D) -> Result<Option<Option<i64>>, D::Error>
+    where
+        D: serde::Deserializer<'de>,
+    {
+        Option::<i64>::deserialize(deserializer).map(Some)
     }
 
     let handler = move |params| -> Result<Value> {
@@ -157,11 +471,26 @@ fn write_memory_tool() -> Tool {
         let store = MemoryStore::new()?;
         let mut entries = store.load()?;
 
-        let namespace = args.namespace.unwrap_or_else(|| "default".to_string());
+        let namespace =
+            trim_namespace(args.namespace).unwrap_or_else(|| DEFAULT_NAMESPACE.to_string());
         let metadata = args
             .metadata
             .unwrap_or_else(|| Value::Object(Default::default()));
         let timestamp = now_string();
+        // Outer `None` leaves an existing expiry untouched; `null` or 0 clears it.
+        let expires_at = match args.ttl_seconds {
+            None => None,
+            Some(None | Some(0)) => Some(None),
+            Some(Some(ttl)) => {
+                if !(1..=MAX_TTL_SECONDS).contains(&ttl) {
+                    anyhow::bail!("ttl_seconds must be between 0 and {MAX_TTL_SECONDS}");
+                }
+                let expires = OffsetDateTime::now_utc()
+                    .checked_add(Duration::seconds(ttl))
+                    .context("ttl_seconds is too large")?;
+                Some(Some(expires.format(&Rfc3339)?))
+            }
+        };
 
         let (entry, action) = if let Some(id) = args.id {
             match entries.iter_mut().find(|entry| entry.id == id) {
@@ -171,6 +500,9 @@ fn write_memory_tool() -> Tool {
                     existing.tags = args.tags;
                     existing.metadata = metadata;
                     existing.updated_at = Some(timestamp.clone());
+                    if let Some(expires_at) = expires_at {
+                        existing.expires_at = expires_at;
+                    }
                     (existing.clone(), "updated")
                 }
                 None => {
@@ -182,6 +514,7 @@ fn write_memory_tool() -> Tool {
                         metadata,
                         created_at: timestamp.clone(),
                         updated_at: Some(timestamp.clone()),
+                        expires_at: expires_at.flatten(),
                     };
                     entries.push(entry.clone());
                     (entry, "created")
@@ -196,6 +529,7 @@ fn write_memory_tool() -> Tool {
                 metadata,
                 created_at: timestamp.clone(),
                 updated_at: None,
+                expires_at: expires_at.flatten(),
             };
             entries.push(entry.clone());
             (entry, "created")
@@ -214,6 +548,81 @@ fn write_memory_tool() -> Tool {
         schema,
         Box::new(handler),
     )
+    .mutating()
+}
+
+fn append_memory_tool() -> Tool {
+    let schema = json!({
+        "type": "object",
+        "properties": {
+            "id": {
+                "type": "string",
+                "description": "Identifier of the memory to extend",
+            },
+            "content": {
+                "type": "string",
+                "description": "Text to append to the existing content",
+            },
+            "separator": {
+                "type": "string",
+                "description": "Inserted between the existing content and the new text when the entry is not empty",
+                "default": "\n",
+            }
+        },
+        "required": ["id", "content"],
+        "examples": [{"id": "decision-log", "content": "Switched the index to FNV hashing", "separator": "\n- "}],
+        "x-positional-order": ["id", "content", "separator"],
+        "additionalProperties": false
+    });
+
+    #[derive(Deserialize)]
+    struct Params {
+        id: String,
+        content: String,
+        #[serde(default)]
+        separator: Option<String>,
+    }
+
+    let handler = move |params| -> Result<Value> {
+        let args: Params =
+            serde_json::from_value(params).context("Invalid arguments for append_memory")?;
+        let store = MemoryStore::new()?;
+        let mut entries = store.load()?;
+
+        let now = OffsetDateTime::now_utc();
+        let Some(entry) = entries
+            .iter_mut()
+            .find(|entry| entry.id == args.id && !entry.is_expired(now))
+        else {
+            anyhow::bail!(
+                "No memory with id '{}'; create it with write_memory first",
+                args.id
+            );
+        };
+
+        if !entry.content.is_empty() {
+            entry
+                .content
+                .push_str(args.separator.as_deref().unwrap_or("\n"));
+        }
+        entry.content.push_str(&args.content);
+        entry.updated_at = Some(now_string());
+        let entry = entry.clone();
+
+        store.save(&entries)?;
+        Ok(json!({
+            "memory": entry,
+            "action": "appended",
+        }))
+    };
+
+    Tool::new(
+        "append_memory",
+        "Append text to an existing memory entry",
+        schema,
+        Box::new(handler),
+    )
+    .mutating()
 }
 
 fn read_memory_tool() -> Tool {
@@ -221,14 +630,43 @@ fn read_memory_tool() -> Tool {
         "type": "object",
         "properties": {
             "id": {"type": "string"},
-            "namespace": {"type": "string"},
-            "tag": {"type": "string"},
+            "namespace": {"type": "string", "pattern": "\\S", "description": "Only memories in this namespace (trimmed; must not be blank)"},
+            "tag": {
+                "anyOf": [
+                    {"type": "string"},
+                    {"type": "array", "items": {"type": "string"}}
+                ],
+                "description": "Tag or list of tags to filter by",
+            },
+            "tag_match": {
+                "type": "string",
+                "enum": ["any", "all"],
+                "default": "any",
+                "description": "Require any or all of the given tags",
+            },
+            "metadata_key": {
+                "type": "string",
+                "description": "Only return memories whose metadata contains this key",
+            },
+            "metadata_value": {
+                "description": "Exact JSON value `metadata_key` must hold; `null` matches memories where it is null or missing",
+            },
             "query": {
                 "type": "string",
                 "description": "Substring to search within content or metadata",
             },
-            "limit": {"type": "integer", "minimum": 1, "description": "Maximum number of memories to return"}
+            "limit": {"type": "integer", "minimum": 1, "description": "Maximum number of memories to return"},
+            "fields": fields_schema()
         },
+        "dependentRequired": {"metadata_value": ["metadata_key"]},
+        "examples": [
+            {"namespace": "project", "tag": ["build", "ci"], "tag_match": "any"},
+            {"namespace": "project", "fields": ["tags", "updated_at"]}
+        ],
+        "x-positional-order": [
+            "id", "namespace", "tag", "tag_match", "metadata_key", "metadata_value", "query",
+            "limit", "fields"
+        ],
         "additionalProperties": false
     });
 
@@ -239,11 +677,27 @@ fn read_memory_tool() -> Tool {
         #[serde(default)]
         namespace: Option<String>,
         #[serde(default)]
-        tag: Option<String>,
+        tag: Option<TagList>,
+        #[serde(default)]
+        tag_match: TagMatch,
+        #[serde(default)]
+        metadata_key: Option<String>,
+        /// `None` when omitted, `Some(Value::Null)` for an explicit `null`.
+        #[serde(default, deserialize_with = "explicit_value")]
+        metadata_value: Option<Value>,
         #[serde(default)]
         query: Option<String>,
         #[serde(default)]
         limit: Option<usize>,
+        #[serde(default)]
+        fields: Option<Vec<String>>,
+    }
+
+    fn explicit_value<'de, D>(deserializer: D) -> Result<Option<Value>, D::Error>
+    where
+        D: serde::Deserializer<'de>,
+    {
+        Value::deserialize(deserializer).map(Some)
     }
 
     let handler = move |params| -> Result<Value> {
@@ -254,21 +708,25 @@ fn read_memory_tool() -> Tool {
 
         let filter = MemoryFilter {
             id: args.id,
-            namespace: args.namespace,
-            tag: args.tag,
+            namespace: trim_namespace(args.namespace),
+            tags: args.tag.map(TagList::into_vec).unwrap_or_default(),
+            tag_match: args.tag_match,
+            metadata_key: args.metadata_key,
+            metadata_value: args.metadata_value,
             query: args.query,
         };
         let limit = args.limit.unwrap_or(20);
 
+        let now = OffsetDateTime::now_utc();
         let filtered = entries
             .into_iter()
-            .filter(|entry| entry.matches(&filter))
+            .filter(|entry| !entry.is_expired(now) && entry.matches(&filter))
             .take(limit)
             .collect::<Vec<_>>();
 
         Ok(json!({
             "count": filtered.len(),
-            "memories": filtered,
+            "memories": project_fields(&filtered, args.fields.as_deref())?,
         }))
     };
 
@@ -284,10 +742,35 @@ fn list_memories_tool() -> Tool {
     let schema = json!({
         "type": "object",
         "properties": {
-            "namespace": {"type": "string"},
-            "limit": {"type": "integer", "minimum": 1},
-            "offset": {"type": "integer", "minimum": 0}
+            "namespace": {"type": "string", "pattern": "\\S", "description": "Only memories in this namespace (trimmed; must not be blank)"},
+            "limit": {"type": "integer", "minimum": 1, "description": "Page size (default 50)"},
+            "offset": {"type": "integer", "minimum": 0},
+            "all": {
+                "type": "boolean",
+                "description": "Return every matching memory instead of a page; cannot be combined with `limit` or `offset`",
+                "default": false
+            },
+            "order": {
+                "type": "string",
+                "enum": ["recent", "id", "namespace"],
+                "description": "`recent`: newest `created_at` first; `id`: by id; `namespace`: by namespace, then id. `id` and `namespace` give a stable order suited to diffing dumps",
+                "default": "recent"
+            },
+            "group_by_namespace": {
+                "type": "boolean",
+                "description": "Return `namespaces: [{ namespace, count, latest_updated }]` instead of entries",
+                "default": false
+            },
+            "fields": fields_schema()
         },
+        "examples": [
+            {"group_by_namespace": true},
+            {"all": true, "order": "namespace"},
+            {"fields": ["namespace", "tags"], "limit": 100}
+        ],
+        "x-positional-order": [
+            "namespace", "limit", "offset", "all", "order", "group_by_namespace", "fields"
+        ],
         "additionalProperties": false
     });
 
@@ -299,22 +782,56 @@ fn list_memories_tool() -> Tool {
         limit: Option<usize>,
         #[serde(default)]
         offset: Option<usize>,
+        #[serde(default)]
+        all: bool,
+        #[serde(default)]
+        order: MemoryOrder,
+        #[serde(default)]
+        group_by_namespace: bool,
+        #[serde(default)]
+        fields: Option<Vec<String>>,
     }
 
     let handler = move |params| -> Result<Value> {
         let args: Params =
             serde_json::from_value(params).context("Invalid arguments for list_memories")?;
+        if args.all && (args.limit.is_some() || args.offset.is_some()) {
+            anyhow::bail!("`all` cannot be combined with `limit` or `offset`");
+        }
+        if args.group_by_namespace && args.fields.is_some() {
+            anyhow::bail!("`fields` cannot be combined with `group_by_namespace`");
+        }
         let store = MemoryStore::new()?;
         let mut entries = store.load()?;
 
-        if let Some(namespace) = args.namespace {
+        let now = OffsetDateTime::now_utc();
+        entries.retain(|entry| !entry.is_expired(now));
+        if let Some(namespace) = trim_namespace(args.namespace) {
             entries.retain(|entry| entry.namespace == namespace);
         }
 
-        entries.sort_by(|a, b| b.created_at.cmp(&a.created_at));
+        if args.group_by_namespace {
+            let namespaces = namespace_stats(&entries);
+            return Ok(json!({
+                "count": namespaces.len(),
+                "namespaces": namespaces,
+            }));
+        }
+
+        match args.order {
+            MemoryOrder::Recent => entries.sort_by(|a, b| b.created_at.cmp(&a.created_at)),
+            MemoryOrder::Id => entries.sort_by(|a, b| a.id.cmp(&b.id)),
+            MemoryOrder::Namespace => {
+                entries.sort_by(|a, b| a.namespace.cmp(&b.namespace).then_with(|| a.id.cmp(&b.id)))
+            }
+        }
 
         let offset = args.offset.unwrap_or(0);
-        let limit = args.limit.unwrap_or(50);
+        let limit = if args.all {
+            usize::MAX
+        } else {
+            args.limit.unwrap_or(50)
+        };
         let slice = entries
             .into_iter()
             .skip(offset)
@@ -323,7 +840,7 @@ fn list_memories_tool() -> Tool {
 
         Ok(json!({
             "count": slice.len(),
-            "memories": slice,
+            "memories": project_fields(&slice, args.fields.as_deref())?,
         }))
     };
 
@@ -335,6 +852,68 @@ fn list_memories_tool() -> Tool {
     )
 }
 
+/// Members of a serialised [`MemoryEntry`] that `fields` can select.
+const MEMORY_FIELDS: &[&str] = &[
+    "id",
+    "namespace",
+    "content",
+    "tags",
+    "metadata",
+    "created_at",
+    "updated_at",
+    "expires_at",
+];
+
+fn fields_schema() -> Value {
+    json!({
+        "type": "array",
+        "items": {"type": "string", "enum": MEMORY_FIELDS},
+        "description": "Return only these fields of each memory (`id` is always included), e.g. [\"tags\"] to browse before fetching full content",
+    })
+}
+
+/// `entries` serialised with only `id` and the requested `fields`; every
+/// field when `fields` is `None`.
+fn project_fields(entries: &[MemoryEntry], fields: Option<&[String]>) -> Result<Vec<Value>> {
+    entries
+        .iter()
+        .map(|entry| {
+            let mut value = serde_json::to_value(entry).context("Failed to serialise memory")?;
+            if let (Some(fields), Some(object)) = (fields, value.as_object_mut()) {
+                object.retain(|name, _| name == "id" || fields.iter().any(|field| field == name));
+            }
+            Ok(value)
+        })
+        .collect()
+}
+
+/// Per-namespace entry counts and the newest `updated_at` (falling back to
+/// `created_at`), largest namespaces first.
+fn namespace_stats(entries: &[MemoryEntry]) -> Vec<Value> {
+    let mut stats: HashMap<&str, (usize, &str)> = HashMap::new();
+    for entry in entries {
+        let updated = entry.updated_at.as_deref().unwrap_or(&entry.created_at);
+        let slot = stats.entry(&entry.namespace).or_insert((0, updated));
+        slot.0 += 1;
+        if updated > slot.1 {
+            slot.1 = updated;
+        }
+    }
+
+    let mut stats: Vec<_> = stats.into_iter().collect();
+    stats.sort_by(|a, b| b.1.0.cmp(&a.1.0).then_with(|| a.0.cmp(b.0)));
+    stats
+        .into_iter()
+        .map(|(namespace, (count, latest_updated))| {
+            json!({
+                "namespace": namespace,
+                "count": count,
+                "latest_updated": latest_updated,
+            })
+        })
+        .collect()
+}
+
 fn delete_memory_tool() -> Tool {
     let schema = json!({
         "type": "object",
@@ -345,6 +924,8 @@ fn delete_memory_tool() -> Tool {
             }
         },
         "required": ["id"],
+        "examples": [{"id": "build-notes"}],
+        "x-positional-order": ["id"],
         "additionalProperties": false
     });
 
@@ -376,6 +957,50 @@ fn delete_memory_tool() -> Tool {
         schema,
         Box::new(handler),
     )
+    .mutating()
+}
+
+fn compact_memories_tool() -> Tool {
+    let schema = json!({
+        "type": "object",
+        "properties": {},
+        "examples": [{}],
+        "additionalProperties": false
+    });
+
+    let handler = move |_params| -> Result<Value> {
+        let store = MemoryStore::new()?;
+        let bytes_before = store.size_bytes();
+        let mut entries = store.load()?;
+        let count_before = entries.len();
+
+        let now = OffsetDateTime::now_utc();
+        entries.retain(|entry| !entry.is_expired(now));
+        let expired = count_before - entries.len();
+        entries.sort_by(|a, b| {
+            a.namespace
+                .cmp(&b.namespace)
+                .then_with(|| a.created_at.cmp(&b.created_at))
+                .then_with(|| a.id.cmp(&b.id))
+        });
+
+        store.save(&entries)?;
+        let bytes_after = store.size_bytes();
+
+        Ok(json!({
+            "expired_removed": expired,
+            "before": {"count": count_before, "bytes": bytes_before},
+            "after": {"count": entries.len(), "bytes": bytes_after},
+        }))
+    };
+
+    Tool::new(
+        "compact_memories",
+        "Drop expired memories and rewrite the store sorted by namespace and creation time",
+        schema,
+        Box::new(handler),
+    )
+    .mutating()
 }
 
 fn now_string() -> String {
@@ -1,50 +1,197 @@
+mod ctags;
+mod diff;
+mod editorconfig;
 mod files;
+mod format;
+mod index;
+mod journal;
 mod memory;
+mod refactor;
 mod symbols;
+mod syntax;
 mod workflow;
 
 use std::{
+    cell::{Cell, RefCell},
+    collections::HashSet,
     env, fs,
     path::{Path, PathBuf},
 };
 
 use anyhow::{Context, Result};
+use globset::{GlobBuilder, GlobSet, GlobSetBuilder};
+use log::error;
+use regex::Regex;
+use serde::Deserialize;
+use serde_json::{Value, json};
+use walkdir::{DirEntry, WalkDir};
 
+use crate::cancellation;
 use crate::tool::ToolRegistry;
 
+pub(crate) use memory::memory_layout;
+
 /// Build a tool registry populated with the implemented tool families.
 pub fn build_registry() -> ToolRegistry {
     let mut registry = ToolRegistry::new();
 
+    ctags::register(&mut registry);
     files::register(&mut registry);
+    index::register(&mut registry);
+    journal::register(&mut registry);
     memory::register(&mut registry);
+    refactor::register(&mut registry);
     symbols::register(&mut registry);
     workflow::register(&mut registry);
 
+    let invalid = registry.invalid_schemas();
+    if !invalid.is_empty() {
+        error!(
+            "Argument schemas of {} tool(s) are not valid JSON Schema and will not be enforced: {}",
+            invalid.len(),
+            invalid.join(", ")
+        );
+    }
+
     registry
 }
 
-/// Resolve the directory used to persist mutable tool state.
+/// Resolve the directory used to persist mutable tool state. The directory
+/// may not exist yet; writers go through [`writable_state_dir`].
 pub(crate) fn state_dir() -> Result<PathBuf> {
     if let Ok(dir) = env::var("SERENA_STATE_DIR") {
-        let path = PathBuf::from(dir);
-        fs::create_dir_all(&path)
-            .with_context(|| format!("Failed to create state dir at {path:?}"))?;
-        return Ok(path);
+        return Ok(PathBuf::from(dir));
     }
 
     let home = env::var("HOME").context("HOME environment variable is not set")?;
-    let path = Path::new(&home).join(".serena-mcp");
+    Ok(Path::new(&home).join(".serena-mcp"))
+}
+
+/// [`state_dir`], created when missing. Fails in read-only mode, where
+/// nothing may be persisted.
+pub(crate) fn writable_state_dir() -> Result<PathBuf> {
+    if is_read_only() {
+        anyhow::bail!("State is not persisted in read-only mode");
+    }
+    let path = state_dir()?;
     fs::create_dir_all(&path).with_context(|| format!("Failed to create state dir at {path:?}"))?;
     Ok(path)
 }
 
+/// Environment variable [`state_dir`] was resolved from.
+pub(crate) fn state_dir_source() -> &'static str {
+    if env::var_os("SERENA_STATE_DIR").is_some() {
+        "SERENA_STATE_DIR"
+    } else {
+        "HOME"
+    }
+}
+
 /// Convenience helper for working with stable state files.
 pub(crate) fn state_file(name: &str) -> Result<PathBuf> {
     Ok(state_dir()?.join(name))
 }
 
-/// Expand `~` and resolve relative paths against the current directory.
+/// [`state_file`] for a caller about to write it; see [`writable_state_dir`].
+pub(crate) fn writable_state_file(name: &str) -> Result<PathBuf> {
+    Ok(writable_state_dir()?.join(name))
+}
+
+thread_local! {
+    static READ_ONLY: Cell<bool> = const { Cell::new(false) };
+}
+
+/// Restores the previous read-only flag when dropped.
+pub(crate) struct ReadOnlyScope {
+    previous: bool,
+}
+
+impl Drop for ReadOnlyScope {
+    fn drop(&mut self) {
+        READ_ONLY.with(|flag| flag.set(self.previous));
+    }
+}
+
+/// Treat tool state on this thread as read-only until the scope is dropped.
+pub(crate) fn scoped_read_only(read_only: bool) -> ReadOnlyScope {
+    ReadOnlyScope {
+        previous: READ_ONLY.with(|flag| flag.replace(read_only)),
+    }
+}
+
+/// Whether the current call runs on a read-only server, so tools must not
+/// persist state.
+pub(crate) fn is_read_only() -> bool {
+    READ_ONLY.with(Cell::get)
+}
+
+/// Optional argument on path-taking tools that overrides the directory
+/// relative paths resolve against for that call only.
+pub(crate) const CWD_KEY: &str = "cwd";
+
+thread_local! {
+    static CALL_CWD: RefCell<Option<PathBuf>> = const { RefCell::new(None) };
+}
+
+/// Restores the previous per-call working directory when dropped.
+pub(crate) struct CwdScope {
+    previous: Option<PathBuf>,
+}
+
+impl Drop for CwdScope {
+    fn drop(&mut self) {
+        CALL_CWD.with(|cwd| *cwd.borrow_mut() = self.previous.take());
+    }
+}
+
+/// Resolve relative paths on this thread against `dir` (or the process
+/// working directory when `None`) until the scope is dropped.
+pub(crate) fn scoped_base_dir(dir: Option<PathBuf>) -> CwdScope {
+    CwdScope {
+        previous: CALL_CWD.with(|current| current.replace(dir)),
+    }
+}
+
+/// Resolve relative paths on this thread against `cwd` until the scope is
+/// dropped; `None` keeps the current base. The process working directory is
+/// never changed.
+pub(crate) fn scoped_cwd(cwd: Option<&str>) -> Result<CwdScope> {
+    let dir = match cwd {
+        Some(cwd) => {
+            let dir = resolve_path(cwd)?;
+            if !dir.is_dir() {
+                anyhow::bail!("cwd {} is not a directory", dir.display());
+            }
+            Some(dir)
+        }
+        None => CALL_CWD.with(|current| current.borrow().clone()),
+    };
+    Ok(scoped_base_dir(dir))
+}
+
+/// Resolve the `--project` flag to the canonical directory tools default to.
+/// Only paths are supported; `~` and relative paths resolve against the
+/// process working directory.
+pub fn project_root(project: &str) -> Result<PathBuf> {
+    let path = resolve_path(project)?;
+    if !path.is_dir() {
+        anyhow::bail!("Project {project} is not a directory");
+    }
+    path.canonicalize()
+        .with_context(|| format!("Failed to resolve project {}", path.display()))
+}
+
+/// Directory relative paths resolve against: the call's `cwd` argument when
+/// given, otherwise the activated project (`--project`), otherwise the
+/// process working directory.
+pub(crate) fn base_dir() -> Result<PathBuf> {
+    if let Some(dir) = CALL_CWD.with(|cwd| cwd.borrow().clone()) {
+        return Ok(dir);
+    }
+    env::current_dir().context("Failed to read the current directory")
+}
+
+/// Expand `~` and resolve relative paths against [`base_dir`].
 pub(crate) fn resolve_path(path: &str) -> Result<PathBuf> {
     if path.trim().is_empty() {
         anyhow::bail!("Path cannot be empty");
@@ -59,6 +206,305 @@ pub(crate) fn resolve_path(path: &str) -> Result<PathBuf> {
     if candidate.is_absolute() {
         Ok(candidate)
     } else {
-        Ok(env::current_dir()?.join(candidate))
+        Ok(base_dir()?.join(candidate))
+    }
+}
+
+/// A search tool's `path`: one file or directory, or a list of roots that
+/// are searched in order and reported per match.
+#[derive(Debug, Deserialize)]
+#[serde(untagged)]
+pub(crate) enum SearchRoots {
+    One(String),
+    Many(Vec<String>),
+}
+
+impl SearchRoots {
+    /// Resolved roots of `path`, or the base directory when it is absent.
+    pub(crate) fn resolve(path: Option<&Self>) -> Result<Vec<PathBuf>> {
+        match path {
+            None => Ok(vec![base_dir()?]),
+            Some(SearchRoots::One(path)) => Ok(vec![resolve_path(path)?]),
+            Some(SearchRoots::Many(paths)) => paths.iter().map(|path| resolve_path(path)).collect(),
+        }
+    }
+
+    pub(crate) fn is_many(path: Option<&Self>) -> bool {
+        matches!(path, Some(SearchRoots::Many(_)))
+    }
+}
+
+/// Schema for a [`SearchRoots`] argument.
+pub(crate) fn search_roots_schema(description: &str) -> Value {
+    json!({
+        "anyOf": [
+            {"type": "string"},
+            {"type": "array", "items": {"type": "string", "minLength": 1}, "minItems": 1}
+        ],
+        "description": description,
+    })
+}
+
+/// Directory depth recursive searches descend to unless a caller overrides it.
+pub(crate) const DEFAULT_SEARCH_MAX_DEPTH: usize = 16;
+
+/// Files a recursive search visits before stopping, matches or not.
+pub(crate) const DEFAULT_SEARCH_MAX_FILES: usize = 10_000;
+
+/// Lines of context shown before and after each search match.
+#[derive(Debug, Clone, Copy)]
+pub(crate) struct ContextWindow {
+    pub(crate) before: usize,
+    pub(crate) after: usize,
+}
+
+impl ContextWindow {
+    /// `context_before`/`context_after` override the symmetric
+    /// `context_lines`, which defaults to 2.
+    pub(crate) fn new(lines: Option<usize>, before: Option<usize>, after: Option<usize>) -> Self {
+        let lines = lines.unwrap_or(2);
+        Self {
+            before: before.unwrap_or(lines),
+            after: after.unwrap_or(lines),
+        }
+    }
+
+    /// First and last line index of the window around `line_idx`, clipped to
+    /// a file of `line_count` lines.
+    pub(crate) fn bounds(&self, line_idx: usize, line_count: usize) -> (usize, usize) {
+        let start = line_idx.saturating_sub(self.before);
+        let end = usize::min(line_idx + self.after, line_count.saturating_sub(1));
+        (start, end.max(line_idx))
+    }
+}
+
+/// Directory names every recursive walk skips.
+pub(crate) const DEFAULT_IGNORED_DIRS: [&str; 9] = [
+    ".git",
+    "target",
+    "node_modules",
+    "venv",
+    ".venv",
+    "dist",
+    "build",
+    ".pytest_cache",
+    "__pycache__",
+];
+
+/// Shared walk filter that skips VCS metadata, dependency caches, build output
+/// and other hidden directories. The walk root itself is always allowed.
+pub(crate) fn allow_entry(entry: &DirEntry) -> bool {
+    allow_entry_ignoring(entry, &[])
+}
+
+/// [`allow_entry`] that additionally skips files and directories whose name is
+/// listed in `extra`.
+pub(crate) fn allow_entry_ignoring(entry: &DirEntry, extra: &[String]) -> bool {
+    if entry.depth() == 0 {
+        return true;
+    }
+    if let Some(name) = entry.file_name().to_str() {
+        if entry.file_type().is_dir() && DEFAULT_IGNORED_DIRS.contains(&name) {
+            return false;
+        }
+        if name.starts_with('.') && entry.file_type().is_dir() {
+            return false;
+        }
+        if extra.iter().any(|ignored| ignored == name) {
+            return false;
+        }
+    }
+    true
+}
+
+/// Lowercase an optional extension list and drop leading dots.
+pub(crate) fn normalize_extensions(extensions: Option<&[String]>) -> Option<HashSet<String>> {
+    extensions.map(|exts| {
+        exts.iter()
+            .map(|ext| ext.trim_start_matches('.').to_lowercase())
+            .collect()
+    })
+}
+
+/// Whether `path` has one of `extensions`; always true without a filter.
+pub(crate) fn has_allowed_extension(path: &Path, extensions: Option<&HashSet<String>>) -> bool {
+    let Some(extensions) = extensions else {
+        return true;
+    };
+    path.extension()
+        .map(|ext| extensions.contains(&ext.to_string_lossy().to_lowercase()))
+        .unwrap_or(false)
+}
+
+/// Compiled `exclude` globs, matched against paths relative to a walk root.
+/// A path is excluded when it or any of its parent directories matches, so
+/// both `generated` and `generated/**` skip that subtree. Patterns without a
+/// `/` match at any depth, like `.gitignore` entries.
+pub(crate) struct ExcludeSet {
+    root: PathBuf,
+    globs: GlobSet,
+}
+
+impl ExcludeSet {
+    pub(crate) fn new(root: &Path, patterns: Option<&[String]>) -> Result<Self> {
+        let mut builder = GlobSetBuilder::new();
+        for pattern in patterns.unwrap_or_default() {
+            let pattern = pattern.trim().trim_end_matches('/');
+            if pattern.is_empty() {
+                continue;
+            }
+            let anchored = if pattern.contains('/') {
+                pattern.trim_start_matches("./").to_string()
+            } else {
+                format!("**/{pattern}")
+            };
+            let glob = GlobBuilder::new(&anchored)
+                .literal_separator(true)
+                .build()
+                .with_context(|| format!("Invalid exclude glob '{pattern}'"))?;
+            builder.add(glob);
+        }
+
+        Ok(Self {
+            root: root.to_path_buf(),
+            globs: builder.build().context("Failed to compile exclude globs")?,
+        })
+    }
+
+    pub(crate) fn is_excluded(&self, path: &Path) -> bool {
+        if self.globs.is_empty() {
+            return false;
+        }
+        let Ok(relative) = path.strip_prefix(&self.root) else {
+            return false;
+        };
+        relative
+            .ancestors()
+            .filter(|ancestor| !ancestor.as_os_str().is_empty())
+            .any(|ancestor| self.globs.is_match(ancestor))
+    }
+}
+
+/// Refuse a replacement pattern that matches the empty string (`x*`), which
+/// would insert the replacement at every position of the text.
+pub(crate) fn reject_empty_match(matcher: &Regex, pattern: &str) -> Result<()> {
+    if matcher.is_match("") {
+        anyhow::bail!(
+            "Pattern '{pattern}' matches the empty string; use one that needs at least one character"
+        );
+    }
+    Ok(())
+}
+
+/// Whether any component of `path` is hidden (starts with a dot).
+pub(crate) fn is_hidden_path(path: &Path) -> bool {
+    path.components().any(|component| match component {
+        std::path::Component::Normal(name) => name.to_string_lossy().starts_with('.'),
+        _ => false,
+    })
+}
+
+/// Which entries a [`FileWalker`] descends into and yields.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum WalkFilter {
+    /// Every file and directory.
+    All,
+    /// Nothing with a hidden path component.
+    SkipHidden,
+    /// [`allow_entry`]: no hidden or [`DEFAULT_IGNORED_DIRS`] directories.
+    Default,
+}
+
+/// Recursive file walk shared by the scanning tools. It applies the depth
+/// and file caps, `exclude` globs and extension filter, checks for
+/// cancellation between files and counts what it skipped. Several roots
+/// walked with one walker share its file cap.
+pub(crate) struct FileWalker<'a> {
+    filter: WalkFilter,
+    max_depth: usize,
+    max_files: usize,
+    extensions: Option<&'a HashSet<String>>,
+    /// Files handed to the visitor.
+    pub(crate) files_scanned: usize,
+    /// Files skipped by an `exclude` glob.
+    pub(crate) files_excluded: usize,
+    /// Whether the walk stopped at `max_files` with files left to visit.
+    pub(crate) files_capped: bool,
+    /// Whether a directory at `max_depth` was not descended into.
+    pub(crate) depth_limited: bool,
+}
+
+impl<'a> FileWalker<'a> {
+    pub(crate) fn new(filter: WalkFilter, max_depth: usize, max_files: usize) -> Self {
+        Self {
+            filter,
+            max_depth,
+            max_files,
+            extensions: None,
+            files_scanned: 0,
+            files_excluded: 0,
+            files_capped: false,
+            depth_limited: false,
+        }
+    }
+
+    /// Only yield files with one of `extensions`; see [`has_allowed_extension`].
+    pub(crate) fn extensions(mut self, extensions: Option<&'a HashSet<String>>) -> Self {
+        self.extensions = extensions;
+        self
+    }
+
+    /// Hand every file under `root` to `visit` in walk order, or `root`
+    /// itself when it is a file, until `visit` returns `false` or the file
+    /// cap is reached.
+    pub(crate) fn walk(
+        &mut self,
+        root: &Path,
+        exclude: &ExcludeSet,
+        mut visit: impl FnMut(&Path) -> Result<bool>,
+    ) -> Result<()> {
+        if self.files_capped {
+            return Ok(());
+        }
+        if root.is_file() {
+            self.files_scanned += 1;
+            visit(root)?;
+            return Ok(());
+        }
+
+        let filter = self.filter;
+        let entries = WalkDir::new(root)
+            .follow_links(false)
+            .max_depth(self.max_depth)
+            .into_iter()
+            .filter_entry(move |entry| match filter {
+                WalkFilter::All => true,
+                WalkFilter::SkipHidden => !is_hidden_path(entry.path()),
+                WalkFilter::Default => allow_entry(entry),
+            })
+            .filter_map(|entry| entry.ok());
+        for entry in entries {
+            if !entry.file_type().is_file() {
+                self.depth_limited |= entry.depth() == self.max_depth && entry.file_type().is_dir();
+                continue;
+            }
+            if exclude.is_excluded(entry.path()) {
+                self.files_excluded += 1;
+                continue;
+            }
+            if !has_allowed_extension(entry.path(), self.extensions) {
+                continue;
+            }
+            if self.files_scanned >= self.max_files {
+                self.files_capped = true;
+                break;
+            }
+            cancellation::checkpoint()?;
+            self.files_scanned += 1;
+            if !visit(entry.path())? {
+                break;
+            }
+        }
+        Ok(())
     }
 }
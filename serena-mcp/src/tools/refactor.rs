@@ -0,0 +1,614 @@
+use std::fs;
+use std::path::{Component, Path, PathBuf};
+
+use anyhow::{Context, Result};
+use regex::{Captures, Regex};
+use serde::Deserialize;
+use serde_json::{Value, json};
+use walkdir::WalkDir;
+
+use crate::path_style::display_path;
+use crate::tool::{Tool, ToolRegistry};
+use crate::tools::symbols::Language;
+use crate::tools::{allow_entry, base_dir, resolve_path};
+
+pub fn register(registry: &mut ToolRegistry) {
+    registry.register(rename_file_and_update_references_tool());
+}
+
+fn rename_file_and_update_references_tool() -> Tool {
+    let schema = json!({
+        "type": "object",
+        "properties": {
+            "from": {"type": "string", "description": "Existing source file to move"},
+            "to": {"type": "string", "description": "Destination path; must not exist"},
+            "root": {
+                "type": "string",
+                "description": "Project root scanned for references. Defaults to current working directory.",
+            },
+            "dry_run": {
+                "type": "boolean",
+                "description": "Report the files that would change without touching disk",
+                "default": false,
+            },
+            "create_dirs": {
+                "type": "boolean",
+                "description": "Create the destination's parent directories when missing",
+                "default": false,
+            }
+        },
+        "required": ["from", "to"],
+        "examples": [{"from": "src/util.rs", "to": "src/helpers.rs", "dry_run": true}],
+        "x-positional-order": ["from", "to", "root", "dry_run", "create_dirs"],
+        "additionalProperties": false
+    });
+
+    #[derive(Deserialize)]
+    struct Params {
+        from: String,
+        to: String,
+        #[serde(default)]
+        root: Option<String>,
+        #[serde(default)]
+        dry_run: bool,
+        #[serde(default)]
+        create_dirs: bool,
+    }
+
+    let handler = move |params| -> Result<Value> {
+        let args: Params = serde_json::from_value(params)
+            .context("Invalid arguments for rename_file_and_update_references")?;
+        let from = normalize_lexically(&resolve_path(&args.from)?);
+        let to = normalize_lexically(&resolve_path(&args.to)?);
+        let root = match &args.root {
+            Some(path) => resolve_path(path)?,
+            None => base_dir()?,
+        };
+
+        if !from.is_file() {
+            anyhow::bail!("{} is not a file", from.display());
+        }
+        if to.exists() {
+            anyhow::bail!("Destination {} already exists", to.display());
+        }
+        let rel_from = from
+            .strip_prefix(&root)
+            .with_context(|| format!("{} is outside {}", from.display(), root.display()))?;
+        let rel_to = to
+            .strip_prefix(&root)
+            .with_context(|| format!("{} is outside {}", to.display(), root.display()))?;
+
+        let mut warnings = Vec::new();
+        let rewriter = match Language::from_path(&from) {
+            Some(Language::Rust) => Rewriter::Rust(RustMove::new(rel_from, rel_to)?),
+            Some(Language::Python) => {
+                Rewriter::Python(PythonMove::new(rel_from, rel_to, &mut warnings)?)
+            }
+            Some(Language::Javascript | Language::Typescript) => Rewriter::Script,
+            _ => {
+                warnings.push(
+                    "References are only rewritten for Rust, Python and JS/TS files".to_string(),
+                );
+                Rewriter::None
+            }
+        };
+
+        let mut updates = Vec::new();
+        for entry in WalkDir::new(&root)
+            .follow_links(false)
+            .into_iter()
+            .filter_entry(allow_entry)
+            .filter_map(|e| e.ok())
+            .filter(|e| e.file_type().is_file())
+        {
+            let path = entry.path();
+            let Some(language) = Language::from_path(path) else {
+                continue;
+            };
+            if !rewriter.applies_to(language) {
+                continue;
+            }
+
+            let content = match fs::read_to_string(path) {
+                Ok(content) => content,
+                Err(err) if err.kind() == std::io::ErrorKind::InvalidData => continue,
+                Err(err) => {
+                    return Err(err).with_context(|| format!("Failed to read {}", path.display()));
+                }
+            };
+
+            let rel = path.strip_prefix(&root).unwrap_or(path);
+            let (updated, replacements) = match &rewriter {
+                Rewriter::Rust(rust) => rust.rewrite(rel, &content),
+                Rewriter::Python(python) => python.rewrite(rel, &content),
+                Rewriter::Script => rewrite_script_imports(path, &from, &to, &content),
+                Rewriter::None => continue,
+            };
+
+            if replacements > 0 {
+                updates.push((path.to_path_buf(), updated, replacements));
+            }
+        }
+
+        if !args.dry_run {
+            if let Some(parent) = to.parent()
+                && !parent.exists()
+            {
+                if !args.create_dirs {
+                    anyhow::bail!(
+                        "Parent directory {} does not exist; pass create_dirs to create it",
+                        parent.display()
+                    );
+                }
+                fs::create_dir_all(parent)
+                    .with_context(|| format!("Failed to create {}", parent.display()))?;
+            }
+            fs::rename(&from, &to).with_context(|| {
+                format!("Failed to rename {} to {}", from.display(), to.display())
+            })?;
+
+            for (path, content, _) in &updates {
+                let target = if *path == from { &to } else { path };
+                fs::write(target, content)
+                    .with_context(|| format!("Failed to write {}", target.display()))?;
+            }
+        }
+
+        let touched = updates
+            .iter()
+            .map(|(path, _, replacements)| {
+                json!({
+                    "path": display_path(path),
+                    "replacements": replacements,
+                })
+            })
+            .collect::<Vec<_>>();
+
+        Ok(json!({
+            "from": display_path(&from),
+            "to": display_path(&to),
+            "dry_run": args.dry_run,
+            "renamed": !args.dry_run,
+            "files_updated": touched.len(),
+            "updates": touched,
+            "warnings": warnings,
+        }))
+    };
+
+    Tool::new(
+        "rename_file_and_update_references",
+        "Move a source file and rewrite module paths/imports that reference it (Rust, Python, JS/TS)",
+        schema,
+        Box::new(handler),
+    )
+    .mutating()
+    .resolves_paths()
+}
+
+enum Rewriter {
+    Rust(RustMove),
+    Python(PythonMove),
+    Script,
+    None,
+}
+
+impl Rewriter {
+    fn applies_to(&self, language: Language) -> bool {
+        match self {
+            Rewriter::Rust(_) => language == Language::Rust,
+            Rewriter::Python(_) => language == Language::Python,
+            Rewriter::Script => matches!(language, Language::Javascript | Language::Typescript),
+            Rewriter::None => false,
+        }
+    }
+}
+
+/// Rewrites for a Rust module move within one crate (`src/` tree).
+struct RustMove {
+    src_root: PathBuf,
+    old: Vec<String>,
+    new: Vec<String>,
+    crate_path: Regex,
+    /// Renames the `mod` declaration and `self::`/`super::` references in
+    /// place, as only the last segment changes.
+    sibling: SiblingRename,
+}
+
+struct SiblingRename {
+    parent: Vec<String>,
+    mod_decl: Regex,
+    bare_path: Regex,
+    super_path: Regex,
+    new_name: String,
+}
+
+impl RustMove {
+    fn new(rel_from: &Path, rel_to: &Path) -> Result<Self> {
+        let (src_root, old) =
+            rust_module_path(rel_from).context("Source file is not inside a crate `src` dir")?;
+        let (new_root, new) =
+            rust_module_path(rel_to).context("Destination is not inside a crate `src` dir")?;
+        if src_root != new_root {
+            anyhow::bail!("Moving Rust modules between crates is not supported");
+        }
+        if old.is_empty() || new.is_empty() {
+            anyhow::bail!("Crate roots (lib.rs/main.rs) cannot be moved");
+        }
+
+        let crate_path = Regex::new(&format!(r"\bcrate::{}\b", regex::escape(&old.join("::"))))?;
+        let (old_parent, old_name) = old.split_at(old.len() - 1);
+        let (new_parent, new_name) = new.split_at(new.len() - 1);
+
+        // The `mod` declaration lives in the parent module; moving it to
+        // another parent would leave the old declaration pointing at a
+        // missing file, so only renames within one parent are supported.
+        if old_parent != new_parent {
+            anyhow::bail!(
+                "Moving a Rust module to a different parent module (`{}` to `{}`) is not supported; rename it within `{}` instead",
+                old.join("::"),
+                new.join("::"),
+                if old_parent.is_empty() {
+                    "the crate root".to_string()
+                } else {
+                    old_parent.join("::")
+                }
+            );
+        }
+        let name = regex::escape(&old_name[0]);
+        let sibling = SiblingRename {
+            parent: old_parent.to_vec(),
+            mod_decl: Regex::new(&format!(r"\bmod\s+{name}\b"))?,
+            bare_path: Regex::new(&format!(r"(^|[^:\w])(self::)?{name}::"))?,
+            super_path: Regex::new(&format!(r"\bsuper::{name}\b"))?,
+            new_name: new_name[0].clone(),
+        };
+
+        Ok(Self {
+            src_root,
+            old,
+            new,
+            crate_path,
+            sibling,
+        })
+    }
+
+    fn rewrite(&self, rel: &Path, content: &str) -> (String, usize) {
+        let Some((src_root, module)) = rust_module_path(rel) else {
+            return (content.to_string(), 0);
+        };
+        if src_root != self.src_root {
+            return (content.to_string(), 0);
+        }
+
+        let mut count = 0usize;
+        let replacement = format!("crate::{}", self.new.join("::"));
+        let mut updated = replace_counting(&self.crate_path, content, &mut count, |_| {
+            replacement.clone()
+        });
+
+        let sibling = &self.sibling;
+        if module == sibling.parent {
+            updated = replace_counting(&sibling.mod_decl, &updated, &mut count, |_| {
+                format!("mod {}", sibling.new_name)
+            });
+            updated = replace_counting(&sibling.bare_path, &updated, &mut count, |caps| {
+                format!(
+                    "{}{}{}::",
+                    &caps[1],
+                    caps.get(2).map(|m| m.as_str()).unwrap_or(""),
+                    sibling.new_name
+                )
+            });
+        } else if module.len() == self.old.len() && module[..module.len() - 1] == sibling.parent {
+            updated = replace_counting(&sibling.super_path, &updated, &mut count, |_| {
+                format!("super::{}", sibling.new_name)
+            });
+        }
+
+        (updated, count)
+    }
+}
+
+/// Split a path relative to the project root into its crate `src` directory
+/// and module path. `lib.rs`/`main.rs` map to the crate root (empty path) and
+/// `foo/mod.rs` maps to `foo`.
+fn rust_module_path(rel: &Path) -> Option<(PathBuf, Vec<String>)> {
+    let components: Vec<String> = rel
+        .components()
+        .map(|c| c.as_os_str().to_string_lossy().to_string())
+        .collect();
+    let src_idx = components.iter().rposition(|c| c == "src")?;
+    let src_root: PathBuf = components[..=src_idx].iter().collect();
+    let mut module = components[src_idx + 1..].to_vec();
+    let file = module.pop()?;
+    let stem = file.strip_suffix(".rs")?;
+    match stem {
+        "lib" | "main" if module.is_empty() => {}
+        "mod" => {}
+        other => module.push(other.to_string()),
+    }
+    Some((src_root, module))
+}
+
+/// Rewrites for a Python module move. Module paths are dotted and relative to
+/// the project root, with a leading `src/` layout directory ignored.
+struct PythonMove {
+    old: String,
+    new: String,
+    old_parent: Option<String>,
+    old_name: String,
+    new_name: String,
+    same_package: bool,
+    old_dir: PathBuf,
+    /// The old dotted path as an import target.
+    qualified: Regex,
+    /// `old.` attribute access outside import statements.
+    attribute: Regex,
+    name: Regex,
+    import_line: Regex,
+    from_line: Regex,
+}
+
+impl PythonMove {
+    fn new(rel_from: &Path, rel_to: &Path, warnings: &mut Vec<String>) -> Result<Self> {
+        let old_parts = python_module_path(rel_from).context("Source is not a Python module")?;
+        let new_parts = python_module_path(rel_to).context("Destination is not a Python module")?;
+        if old_parts.is_empty() || new_parts.is_empty() {
+            anyhow::bail!("Cannot move a top-level package __init__.py");
+        }
+
+        let old = old_parts.join(".");
+        let new = new_parts.join(".");
+        let (old_parent, old_name) = old_parts.split_at(old_parts.len() - 1);
+        let (new_parent, new_name) = new_parts.split_at(new_parts.len() - 1);
+        let same_package = old_parent == new_parent;
+        if !same_package {
+            warnings.push(
+                "Package changed: relative imports inside the moved file were not adjusted"
+                    .to_string(),
+            );
+        }
+
+        Ok(Self {
+            qualified: Regex::new(&format!(r"(^|[^.\w]){}\b", regex::escape(&old)))?,
+            attribute: Regex::new(&format!(r"(^|[^.\w]){}\.", regex::escape(&old)))?,
+            name: Regex::new(&format!(r"\b{}\b", regex::escape(&old_name[0])))?,
+            import_line: Regex::new(r"^(\s*import\s+)(.*)$")?,
+            from_line: Regex::new(r"^(\s*from\s+)(\S+)(\s+import\s+)(.*)$")?,
+            old_parent: (!old_parent.is_empty()).then(|| old_parent.join(".")),
+            old_name: old_name[0].clone(),
+            new_name: new_name[0].clone(),
+            same_package,
+            old_dir: rel_from.parent().map(Path::to_path_buf).unwrap_or_default(),
+            old,
+            new,
+        })
+    }
+
+    /// Rewrite `import`/`from ... import` lines naming the old module and,
+    /// in files that `import` it under its own name, `old.attr` references.
+    /// Other text spelled like the module (locals, strings, comments) is
+    /// left alone.
+    fn rewrite(&self, rel: &Path, content: &str) -> (String, usize) {
+        let mut count = 0usize;
+        let in_same_dir = rel.parent().map(Path::to_path_buf).unwrap_or_default() == self.old_dir;
+        let imports_old = content
+            .lines()
+            .filter_map(|line| self.import_line.captures(line))
+            .any(|caps| self.imports_unaliased(&caps[2]));
+        let mut lines = Vec::new();
+
+        for line in content.split_inclusive('\n') {
+            let (text, newline) = match line.strip_suffix('\n') {
+                Some(text) => (text, "\n"),
+                None => (line, ""),
+            };
+            let rewritten = if let Some(caps) = self.from_line.captures(text) {
+                self.rewrite_from_import(&caps, in_same_dir, &mut count)
+            } else if let Some(caps) = self.import_line.captures(text) {
+                let modules = self.replace_module(&caps[2], &mut count);
+                format!("{}{modules}", &caps[1])
+            } else if imports_old {
+                replace_counting(&self.attribute, text, &mut count, |caps| {
+                    format!("{}{}.", &caps[1], self.new)
+                })
+            } else {
+                text.to_string()
+            };
+            lines.push(format!("{rewritten}{newline}"));
+        }
+
+        (lines.concat(), count)
+    }
+
+    fn rewrite_from_import(
+        &self,
+        caps: &Captures<'_>,
+        in_same_dir: bool,
+        count: &mut usize,
+    ) -> String {
+        let module = &caps[2];
+        let names = &caps[4];
+
+        // `from .foo import x` inside the same package.
+        if self.same_package
+            && in_same_dir
+            && let Some(rest) = module.strip_prefix('.')
+            && (rest == self.old_name || rest.starts_with(&format!("{}.", self.old_name)))
+        {
+            *count += 1;
+            let tail = &rest[self.old_name.len()..];
+            return format!("{}.{}{tail}{}{names}", &caps[1], self.new_name, &caps[3]);
+        }
+
+        let imports_from_parent = self.same_package
+            && (self.old_parent.as_deref() == Some(module) || (in_same_dir && module == "."));
+        if imports_from_parent {
+            let renamed = replace_counting(&self.name, names, count, |_| self.new_name.clone());
+            return format!("{}{module}{}{renamed}", &caps[1], &caps[3]);
+        }
+
+        let module = self.replace_module(module, count);
+        format!("{}{module}{}{names}", &caps[1], &caps[3])
+    }
+
+    /// Whether the module list of an `import` statement binds the old module
+    /// (or a submodule of it) under its dotted name, i.e. without `as`.
+    fn imports_unaliased(&self, modules: &str) -> bool {
+        let modules = modules.split('#').next().unwrap_or_default();
+        modules.split(',').any(|module| {
+            let mut words = module.split_whitespace();
+            let name = words.next().unwrap_or_default();
+            words.next().is_none()
+                && (name == self.old || name.starts_with(&format!("{}.", self.old)))
+        })
+    }
+
+    fn replace_module(&self, text: &str, count: &mut usize) -> String {
+        replace_counting(&self.qualified, text, count, |caps| {
+            format!("{}{}", &caps[1], self.new)
+        })
+    }
+}
+
+fn python_module_path(rel: &Path) -> Option<Vec<String>> {
+    let mut parts: Vec<String> = rel
+        .components()
+        .map(|c| c.as_os_str().to_string_lossy().to_string())
+        .collect();
+    if parts.first().map(String::as_str) == Some("src") {
+        parts.remove(0);
+    }
+    let file = parts.pop()?;
+    let stem = file.strip_suffix(".py")?;
+    if stem != "__init__" {
+        parts.push(stem.to_string());
+    }
+    Some(parts)
+}
+
+static SCRIPT_IMPORT_RE: once_cell::sync::Lazy<Regex> = once_cell::sync::Lazy::new(|| {
+    Regex::new(r#"((?:\bfrom|\bimport|\brequire\(|\bimport\()\s*)(['"])(\.{1,2}/[^'"]*)(['"])"#)
+        .unwrap()
+});
+
+const SCRIPT_EXTENSIONS: [&str; 6] = ["ts", "tsx", "js", "jsx", "mjs", "cjs"];
+
+/// Rewrite relative JS/TS import specifiers that resolve to `from`. The moved
+/// file's own relative imports are re-pointed from its new directory.
+fn rewrite_script_imports(path: &Path, from: &Path, to: &Path, content: &str) -> (String, usize) {
+    let importer_dir = path.parent().unwrap_or(Path::new("/"));
+    let is_moved_file = path == from;
+    let new_importer_dir = if is_moved_file {
+        to.parent().unwrap_or(Path::new("/"))
+    } else {
+        importer_dir
+    };
+
+    let mut count = 0usize;
+    let updated = replace_counting(&SCRIPT_IMPORT_RE, content, &mut count, |caps| {
+        let spec = &caps[3];
+        let target = normalize_lexically(&importer_dir.join(spec));
+        let new_spec = if let Some(style) = script_target_style(&target, from) {
+            let new_target = match style {
+                SpecStyle::WithExtension => to.to_path_buf(),
+                SpecStyle::WithoutExtension => to.with_extension(""),
+                SpecStyle::Directory if to.file_stem().is_some_and(|s| s == "index") => {
+                    to.parent().unwrap_or(to).to_path_buf()
+                }
+                SpecStyle::Directory => to.with_extension(""),
+            };
+            relative_spec(new_importer_dir, &new_target)
+        } else if is_moved_file && new_importer_dir != importer_dir {
+            relative_spec(new_importer_dir, &target)
+        } else {
+            return caps[0].to_string();
+        };
+
+        if new_spec == spec {
+            return caps[0].to_string();
+        }
+        format!("{}{}{new_spec}{}", &caps[1], &caps[2], &caps[4])
+    });
+    (updated, count)
+}
+
+enum SpecStyle {
+    WithExtension,
+    WithoutExtension,
+    Directory,
+}
+
+fn script_target_style(target: &Path, from: &Path) -> Option<SpecStyle> {
+    if target == from {
+        return Some(SpecStyle::WithExtension);
+    }
+    if target == from.with_extension("") {
+        return Some(SpecStyle::WithoutExtension);
+    }
+    let is_index = from.file_stem().is_some_and(|stem| stem == "index")
+        && from
+            .extension()
+            .is_some_and(|ext| SCRIPT_EXTENSIONS.contains(&ext.to_string_lossy().as_ref()));
+    if is_index && from.parent() == Some(target) {
+        return Some(SpecStyle::Directory);
+    }
+    None
+}
+
+/// Relative import specifier from `dir` to `target`, always starting with
+/// `./` or `../`.
+fn relative_spec(dir: &Path, target: &Path) -> String {
+    let dir: Vec<Component<'_>> = dir.components().collect();
+    let target: Vec<Component<'_>> = target.components().collect();
+    let common = dir
+        .iter()
+        .zip(target.iter())
+        .take_while(|(a, b)| a == b)
+        .count();
+
+    let mut parts: Vec<String> = vec!["..".to_string(); dir.len() - common];
+    parts.extend(
+        target[common..]
+            .iter()
+            .map(|c| c.as_os_str().to_string_lossy().to_string()),
+    );
+    let joined = parts.join("/");
+    if joined.starts_with("..") {
+        joined
+    } else if joined.is_empty() {
+        ".".to_string()
+    } else {
+        format!("./{joined}")
+    }
+}
+
+/// Resolve `.` and `..` components without touching the filesystem.
+fn normalize_lexically(path: &Path) -> PathBuf {
+    let mut normalized = PathBuf::new();
+    for component in path.components() {
+        match component {
+            Component::CurDir => {}
+            Component::ParentDir => {
+                normalized.pop();
+            }
+            other => normalized.push(other.as_os_str()),
+        }
+    }
+    normalized
+}
+
+/// `Regex::replace_all` that also tallies the number of replacements made.
+fn replace_counting(
+    regex: &Regex,
+    text: &str,
+    count: &mut usize,
+    mut replacement: impl FnMut(&Captures<'_>) -> String,
+) -> String {
+    regex
+        .replace_all(text, |caps: &Captures<'_>| {
+            *count += 1;
+            replacement(caps)
+        })
+        .into_owned()
+}
@@ -1,27 +1,55 @@
-use std::collections::HashSet;
+use std::collections::{HashMap, HashSet};
+use std::env;
 use std::fs;
-use std::path::Path;
+use std::path::{Path, PathBuf};
+use std::sync::{Mutex, PoisonError};
 
 use anyhow::{Context, Result};
+use log::warn;
 use once_cell::sync::Lazy;
 use regex::{Regex, RegexBuilder};
 use serde::Deserialize;
 use serde_json::{Value, json};
 use walkdir::WalkDir;
 
+use crate::cancellation;
+use crate::notifications::{ProgressReporter, ResultStream};
+use crate::path_style::{display_path, display_path_from};
 use crate::tool::{Tool, ToolRegistry};
-use crate::tools::resolve_path;
+use crate::tools::diff::unified_diff;
+use crate::tools::editorconfig;
+use crate::tools::files::{LineEnding, normalize_whitespace};
+use crate::tools::format;
+use crate::tools::journal;
+use crate::tools::syntax::{
+    check_syntax, is_char_literal, skip_comment_or_raw_string, skip_string, strip_comments,
+};
+use crate::tools::{
+    ContextWindow, DEFAULT_SEARCH_MAX_DEPTH, DEFAULT_SEARCH_MAX_FILES, ExcludeSet, FileWalker,
+    SearchRoots, WalkFilter, allow_entry, base_dir, has_allowed_extension, normalize_extensions,
+    reject_empty_match, resolve_path, search_roots_schema, state_file,
+};
 
 pub fn register(registry: &mut ToolRegistry) {
     registry.register(find_symbol_tool());
+    registry.register(find_duplicate_symbols_tool());
     registry.register(find_referencing_symbols_tool());
     registry.register(get_symbols_overview_tool());
+    registry.register(get_file_outline_tool());
+    registry.register(local_call_graph_tool());
+    registry.register(check_syntax_tool());
     registry.register(rename_symbol_tool());
+    registry.register(rename_local_tool());
     registry.register(replace_symbol_body_tool());
+    registry.register(replace_in_symbol_tool());
+    registry.register(edit_symbol_signature_tool());
+    registry.register(insert_text_tool());
+    registry.register(replace_span_tool());
+    registry.register(list_symbol_kinds_tool());
 }
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
-enum Language {
+pub(crate) enum Language {
     Python,
     Rust,
     Typescript,
@@ -30,29 +58,108 @@ enum Language {
     Java,
     Csharp,
     Generic,
+    /// Configuration and data files; recognised but never yield symbols.
+    Config,
+    /// Documentation and markup; recognised but never yield symbols.
+    Markup,
 }
 
 impl Language {
-    fn from_path(path: &Path) -> Option<Self> {
+    const ALL: [Language; 10] = [
+        Language::Python,
+        Language::Rust,
+        Language::Typescript,
+        Language::Javascript,
+        Language::Go,
+        Language::Java,
+        Language::Csharp,
+        Language::Generic,
+        Language::Config,
+        Language::Markup,
+    ];
+
+    pub(crate) fn from_path(path: &Path) -> Option<Self> {
         let ext = path.extension()?.to_string_lossy().to_lowercase();
-        let lang = match ext.as_str() {
-            "py" => Self::Python,
-            "rs" => Self::Rust,
-            "ts" | "tsx" => Self::Typescript,
-            "js" | "jsx" | "mjs" | "cjs" => Self::Javascript,
-            "go" => Self::Go,
-            "java" | "kt" | "kts" | "scala" => Self::Java,
-            "cs" => Self::Csharp,
-            "swift" | "cpp" | "cc" | "cxx" | "h" | "hpp" | "hh" | "rb" | "php" | "lua" | "zig"
-            | "rsx" | "c" | "dart" | "el" | "erl" | "ex" | "exs" | "hs" | "ml" | "nim" | "sh" => {
-                Self::Generic
+        if let Some(name) = language_override(&ext) {
+            return Self::from_name(name);
+        }
+        Self::ALL
+            .into_iter()
+            .find(|lang| lang.extensions().contains(&ext.as_str()))
+    }
+
+    pub(crate) fn from_name(name: &str) -> Option<Self> {
+        Self::ALL
+            .into_iter()
+            .find(|lang| lang.as_str().eq_ignore_ascii_case(name))
+    }
+
+    /// Human-readable label matching the onboarding summary's naming.
+    pub(crate) fn label(&self) -> &'static str {
+        match self {
+            Language::Python => "Python",
+            Language::Rust => "Rust",
+            Language::Typescript => "TypeScript",
+            Language::Javascript => "JavaScript",
+            Language::Go => "Go",
+            Language::Java => "JVM",
+            Language::Csharp => "C#",
+            Language::Generic => "Generic",
+            Language::Config => "Config",
+            Language::Markup => "Markup",
+        }
+    }
+
+    fn extensions(&self) -> &'static [&'static str] {
+        match self {
+            Language::Python => &["py"],
+            Language::Rust => &["rs"],
+            Language::Typescript => &["ts", "tsx"],
+            Language::Javascript => &["js", "jsx", "mjs", "cjs"],
+            Language::Go => &["go"],
+            Language::Java => &["java", "kt", "kts", "scala"],
+            Language::Csharp => &["cs"],
+            Language::Generic => &[
+                "swift", "cpp", "cc", "cxx", "h", "hpp", "hh", "rb", "php", "lua", "zig", "rsx",
+                "c", "dart", "el", "erl", "ex", "exs", "hs", "ml", "nim", "sh",
+            ],
+            Language::Config => &[
+                "toml",
+                "cfg",
+                "ini",
+                "conf",
+                "properties",
+                "yaml",
+                "yml",
+                "json",
+                "jsonc",
+                "json5",
+            ],
+            Language::Markup => &["md", "mdx", "markdown", "rst", "adoc", "html", "htm", "xml"],
+        }
+    }
+
+    /// Whether files of this language are parsed for symbols at all.
+    pub(crate) fn declares_symbols(&self) -> bool {
+        !matches!(self, Language::Config | Language::Markup)
+    }
+
+    /// Symbol kinds the extractor can emit for this language, in table order.
+    fn symbol_kinds(&self) -> Vec<&'static str> {
+        if *self == Language::Python {
+            return PYTHON_KINDS.to_vec();
+        }
+
+        let mut kinds = Vec::new();
+        for pattern in brace_patterns(*self) {
+            if !kinds.contains(&pattern.kind) {
+                kinds.push(pattern.kind);
             }
-            _ => return None,
-        };
-        Some(lang)
+        }
+        kinds
     }
 
-    fn as_str(&self) -> &'static str {
+    pub(crate) fn as_str(&self) -> &'static str {
         match self {
             Language::Python => "python",
             Language::Rust => "rust",
@@ -62,18 +169,81 @@ impl Language {
             Language::Java => "java",
             Language::Csharp => "csharp",
             Language::Generic => "generic",
+            Language::Config => "config",
+            Language::Markup => "markup",
+        }
+    }
+}
+
+/// Extension overrides loaded once from `languages.json` in the state dir and
+/// the `SERENA_LANG_MAP` env var (`mts=typescript,vue=javascript`). Entries from
+/// the env var win over the file, and both win over the built-in table. Entries
+/// naming an unknown language are logged and ignored.
+static LANGUAGE_OVERRIDES: Lazy<HashMap<String, String>> = Lazy::new(load_language_overrides);
+
+fn load_language_overrides() -> HashMap<String, String> {
+    let mut overrides = HashMap::new();
+    let mut insert = |ext: &str, language: &str| {
+        let ext = ext.trim().trim_start_matches('.').to_lowercase();
+        let language = language.trim();
+        if ext.is_empty() || language.is_empty() {
+            return;
+        }
+        match Language::from_name(language) {
+            Some(known) => {
+                overrides.insert(ext, known.as_str().to_string());
+            }
+            None => warn!("Ignoring language override .{ext}={language}: unknown language"),
+        }
+    };
+
+    if let Ok(path) = state_file("languages.json")
+        && path.exists()
+    {
+        let parsed = fs::read(&path)
+            .map_err(anyhow::Error::from)
+            .and_then(|bytes| Ok(serde_json::from_slice::<HashMap<String, String>>(&bytes)?));
+        match parsed {
+            Ok(map) => map.iter().for_each(|(ext, lang)| insert(ext, lang)),
+            Err(err) => warn!("Ignoring language map at {}: {err}", path.display()),
+        }
+    }
+
+    if let Ok(spec) = env::var("SERENA_LANG_MAP") {
+        for pair in spec.split(',').filter(|pair| !pair.trim().is_empty()) {
+            match pair.split_once('=') {
+                Some((ext, lang)) => insert(ext, lang),
+                None => warn!("Ignoring malformed SERENA_LANG_MAP entry '{pair}'"),
+            }
         }
     }
+
+    overrides
+}
+
+/// Configured language name for a lowercase extension, if any.
+pub(crate) fn language_override(ext: &str) -> Option<&'static str> {
+    LANGUAGE_OVERRIDES.get(ext).map(String::as_str)
 }
 
 #[derive(Debug, Clone)]
-struct FileSymbol {
-    name: String,
-    kind: String,
-    signature: String,
-    line: usize,
-    column: usize,
+pub(crate) struct FileSymbol {
+    pub(crate) name: String,
+    pub(crate) kind: String,
+    pub(crate) signature: String,
+    pub(crate) line: usize,
+    /// 1-based character (not byte) column where the declaration starts,
+    /// after its indentation and any block comment before it, counted like
+    /// `search_pattern` match columns.
+    pub(crate) column: usize,
     body: BodyStyle,
+    /// Container name of the nearest enclosing symbol (see [`container_name`]).
+    pub(crate) enclosing: Option<String>,
+    /// Container names of every enclosing symbol, outermost first.
+    scope: Vec<String>,
+    /// Modifiers written before the name (`pub`, `async`, `export`, ...) plus
+    /// derived ones such as `generator`; see [`symbol_attributes`].
+    pub(crate) attributes: Vec<String>,
 }
 
 #[derive(Debug, Clone)]
@@ -93,47 +263,127 @@ enum BodyStyle {
     None,
 }
 
-struct ParsedFile {
-    language: Language,
+pub(crate) struct ParsedFile {
+    pub(crate) language: Language,
     content: String,
     lines: FileLines,
+    pub(crate) symbols: Vec<FileSymbol>,
+}
+
+/// Symbols of files parsed through [`ParsedFile::from_path_cached`], keyed by
+/// path and reused while the content hash is unchanged.
+static SYMBOL_CACHE: Lazy<Mutex<HashMap<PathBuf, CachedSymbols>>> = Lazy::new(Default::default);
+/// Entries kept before the symbol cache is emptied and refilled.
+const SYMBOL_CACHE_CAPACITY: usize = 4_096;
+
+struct CachedSymbols {
+    language: Language,
+    hash: u64,
     symbols: Vec<FileSymbol>,
 }
 
 impl ParsedFile {
-    fn from_path(path: &Path) -> Result<Option<Self>> {
-        let language = match Language::from_path(path) {
-            Some(lang) => lang,
-            None => return Ok(None),
-        };
+    pub(crate) fn from_path(path: &Path) -> Result<Option<Self>> {
+        Ok(read_source(path)?.map(|(language, content)| Self::parse(language, content)))
+    }
 
-        let metadata = fs::metadata(path)
-            .with_context(|| format!("Failed to read metadata for {}", path.display()))?;
-        if metadata.len() > 2 * 1024 * 1024 {
-            // Skip very large files to keep the tool responsive.
+    /// Like [`ParsedFile::from_path`], but reuses the symbols of an earlier
+    /// call while the file's content hash is unchanged; `refresh` parses
+    /// anyway. The flag is true when cached symbols were used.
+    pub(crate) fn from_path_cached(path: &Path, refresh: bool) -> Result<Option<(Self, bool)>> {
+        let Some((language, content)) = read_source(path)? else {
             return Ok(None);
+        };
+        let hash = content_hash(&content);
+
+        let cached = (!refresh)
+            .then(|| {
+                let cache = SYMBOL_CACHE.lock().unwrap_or_else(PoisonError::into_inner);
+                cache
+                    .get(path)
+                    .filter(|cached| cached.language == language && cached.hash == hash)
+                    .map(|cached| cached.symbols.clone())
+            })
+            .flatten();
+        if let Some(symbols) = cached {
+            let lines = FileLines::new(&content);
+            let parsed = Self {
+                language,
+                content,
+                lines,
+                symbols,
+            };
+            return Ok(Some((parsed, true)));
         }
 
-        let content = match fs::read_to_string(path) {
-            Ok(content) => content,
-            Err(err) if err.kind() == std::io::ErrorKind::InvalidData => return Ok(None),
-            Err(err) => {
-                return Err(err).with_context(|| format!("Failed to read {}", path.display()));
-            }
-        };
+        let parsed = Self::parse(language, content);
+        let mut cache = SYMBOL_CACHE.lock().unwrap_or_else(PoisonError::into_inner);
+        if cache.len() >= SYMBOL_CACHE_CAPACITY {
+            cache.clear();
+        }
+        cache.insert(
+            path.to_path_buf(),
+            CachedSymbols {
+                language,
+                hash,
+                symbols: parsed.symbols.clone(),
+            },
+        );
+        Ok(Some((parsed, false)))
+    }
 
+    fn parse(language: Language, content: String) -> Self {
         let lines = FileLines::new(&content);
-        let symbols = extract_symbols(&content, &lines, language);
+        // Parse comment- and string-free code so commented-out declarations
+        // and braces inside literals are ignored; offsets are unchanged.
+        let symbols = if language.declares_symbols() {
+            let code = strip_comments(&content, language);
+            extract_symbols(&code, &lines, language)
+        } else {
+            Vec::new()
+        };
 
-        Ok(Some(Self {
+        Self {
             language,
             content,
             lines,
             symbols,
-        }))
+        }
+    }
+}
+
+/// Language and content of `path`, or `None` for unrecognised, binary and
+/// very large files.
+fn read_source(path: &Path) -> Result<Option<(Language, String)>> {
+    let language = match Language::from_path(path) {
+        Some(lang) => lang,
+        None => return Ok(None),
+    };
+
+    let metadata = fs::metadata(path)
+        .with_context(|| format!("Failed to read metadata for {}", path.display()))?;
+    if metadata.len() > 2 * 1024 * 1024 {
+        // Skip very large files to keep the tool responsive.
+        return Ok(None);
+    }
+
+    match fs::read_to_string(path) {
+        Ok(content) => Ok(Some((language, content))),
+        Err(err) if err.kind() == std::io::ErrorKind::InvalidData => Ok(None),
+        Err(err) => Err(err).with_context(|| format!("Failed to read {}", path.display())),
     }
 }
 
+/// FNV-1a hash of `content`, folded with its length.
+fn content_hash(content: &str) -> u64 {
+    let mut hash: u64 = 0xcbf2_9ce4_8422_2325 ^ content.len() as u64;
+    for byte in content.bytes() {
+        hash ^= u64::from(byte);
+        hash = hash.wrapping_mul(0x0100_0000_01b3);
+    }
+    hash
+}
+
 struct FileLines {
     records: Vec<LineRecord>,
     starts: Vec<usize>,
@@ -161,10 +411,10 @@ impl FileLines {
             offset += len;
         }
 
-        if !content.ends_with('\n') {
-            if let Some(last) = records.last_mut() {
-                last.end = content.len();
-            }
+        if !content.ends_with('\n')
+            && let Some(last) = records.last_mut()
+        {
+            last.end = content.len();
         }
 
         Self { records, starts }
@@ -185,6 +435,16 @@ impl FileLines {
         &self.records[index].text
     }
 
+    /// 1-based character column `width` bytes into line `index`. Symbols are
+    /// matched on comment-stripped code, where a comment is blanked out byte
+    /// for byte, so the characters are counted on the original line.
+    fn column(&self, index: usize, width: usize) -> usize {
+        let text = self.text(index);
+        text.get(..width)
+            .map_or(width, |prefix| prefix.chars().count())
+            + 1
+    }
+
     fn bounds(&self, index: usize) -> (usize, usize) {
         let record = &self.records[index];
         (record.start, record.end)
@@ -201,83 +461,131 @@ struct LineRecord {
 struct BracePattern {
     regex: &'static Lazy<Regex>,
     kind: &'static str,
+    /// Whether a `{ ... }` block following the match belongs to the symbol.
+    has_body: bool,
 }
 
 const fn brace_pattern(regex: &'static Lazy<Regex>, kind: &'static str) -> BracePattern {
-    BracePattern { regex, kind }
+    BracePattern {
+        regex,
+        kind,
+        has_body: true,
+    }
+}
+
+/// Pattern for single-statement declarations (`const`, `type`, ...) whose
+/// initializer may contain braces that are not a symbol body.
+const fn declaration_pattern(regex: &'static Lazy<Regex>, kind: &'static str) -> BracePattern {
+    BracePattern {
+        regex,
+        kind,
+        has_body: false,
+    }
 }
 
+// Declaration indents are `[ \t]*` rather than `\s*`: under `(?m)^` the
+// latter also swallows preceding blank lines and reports the wrong line.
 static RUST_FN_RE: Lazy<Regex> = Lazy::new(|| {
-    Regex::new(r"(?m)^(?P<indent>\s*)(?:pub(?:\([^)]*\))?\s+)?(?:async\s+)?fn\s+(?P<name>[A-Za-z_][A-Za-z0-9_]*)").unwrap()
+    Regex::new(r"(?m)^(?P<indent>[ \t]*)(?:pub(?:\([^)]*\))?\s+)?(?:const\s+)?(?:async\s+)?(?:unsafe\s+)?fn\s+(?P<name>[A-Za-z_][A-Za-z0-9_]*)").unwrap()
 });
 
 static RUST_STRUCT_RE: Lazy<Regex> = Lazy::new(|| {
     Regex::new(
-        r"(?m)^(?P<indent>\s*)(?:pub(?:\([^)]*\))?\s+)?struct\s+(?P<name>[A-Za-z_][A-Za-z0-9_]*)",
+        r"(?m)^(?P<indent>[ \t]*)(?:pub(?:\([^)]*\))?\s+)?struct\s+(?P<name>[A-Za-z_][A-Za-z0-9_]*)",
     )
     .unwrap()
 });
 
 static RUST_ENUM_RE: Lazy<Regex> = Lazy::new(|| {
     Regex::new(
-        r"(?m)^(?P<indent>\s*)(?:pub(?:\([^)]*\))?\s+)?enum\s+(?P<name>[A-Za-z_][A-Za-z0-9_]*)",
+        r"(?m)^(?P<indent>[ \t]*)(?:pub(?:\([^)]*\))?\s+)?enum\s+(?P<name>[A-Za-z_][A-Za-z0-9_]*)",
     )
     .unwrap()
 });
 
 static RUST_TRAIT_RE: Lazy<Regex> = Lazy::new(|| {
     Regex::new(
-        r"(?m)^(?P<indent>\s*)(?:pub(?:\([^)]*\))?\s+)?trait\s+(?P<name>[A-Za-z_][A-Za-z0-9_]*)",
+        r"(?m)^(?P<indent>[ \t]*)(?:pub(?:\([^)]*\))?\s+)?trait\s+(?P<name>[A-Za-z_][A-Za-z0-9_]*)",
     )
     .unwrap()
 });
 
 static RUST_IMPL_RE: Lazy<Regex> = Lazy::new(|| {
-    Regex::new(r"(?m)^(?P<indent>\s*)impl(?:<[^>]+>)?\s+(?P<name>[A-Za-z_][A-Za-z0-9_:<>]*)")
+    Regex::new(r"(?m)^(?P<indent>[ \t]*)impl(?:<[^>]+>)?\s+(?P<name>[A-Za-z_][A-Za-z0-9_:<>]*)")
         .unwrap()
 });
 
+static RUST_CONST_RE: Lazy<Regex> = Lazy::new(|| {
+    Regex::new(r"(?m)^(?P<indent>[ \t]*)(?:pub(?:\([^)]*\))?\s+)?const\s+(?P<name>[A-Za-z_][A-Za-z0-9_]*)\s*:").unwrap()
+});
+
+static RUST_STATIC_RE: Lazy<Regex> = Lazy::new(|| {
+    Regex::new(r"(?m)^(?P<indent>[ \t]*)(?:pub(?:\([^)]*\))?\s+)?static\s+(?:mut\s+)?(?P<name>[A-Za-z_][A-Za-z0-9_]*)\s*:").unwrap()
+});
+
+static RUST_TYPE_RE: Lazy<Regex> = Lazy::new(|| {
+    Regex::new(
+        r"(?m)^(?P<indent>[ \t]*)(?:pub(?:\([^)]*\))?\s+)?type\s+(?P<name>[A-Za-z_][A-Za-z0-9_]*)",
+    )
+    .unwrap()
+});
+
+static RUST_MOD_RE: Lazy<Regex> = Lazy::new(|| {
+    Regex::new(
+        r"(?m)^(?P<indent>[ \t]*)(?:pub(?:\([^)]*\))?\s+)?mod\s+(?P<name>[A-Za-z_][A-Za-z0-9_]*)",
+    )
+    .unwrap()
+});
+
 static JS_FUNCTION_RE: Lazy<Regex> = Lazy::new(|| {
-    Regex::new(r"(?m)^(?P<indent>\s*)(?:export\s+)?(?:async\s+)?function\s+(?P<name>[A-Za-z_][A-Za-z0-9_]*)").unwrap()
+    Regex::new(r"(?m)^(?P<indent>[ \t]*)(?:export\s+)?(?:default\s+)?(?:async\s+)?function(?:\s*\*\s*|\s+)(?P<name>[A-Za-z_][A-Za-z0-9_]*)").unwrap()
 });
 
 static JS_CLASS_RE: Lazy<Regex> = Lazy::new(|| {
-    Regex::new(r"(?m)^(?P<indent>\s*)(?:export\s+)?class\s+(?P<name>[A-Za-z_][A-Za-z0-9_]*)")
+    Regex::new(r"(?m)^(?P<indent>[ \t]*)(?:export\s+)?class\s+(?P<name>[A-Za-z_][A-Za-z0-9_]*)")
         .unwrap()
 });
 
 static ARROW_FN_RE: Lazy<Regex> = Lazy::new(|| {
-    Regex::new(r"(?m)^(?P<indent>\s*)(?:export\s+)?(?:const|let|var)\s+(?P<name>[A-Za-z_][A-Za-z0-9_]*)\s*=\s*(?:async\s+)?\(?[^\n]*=>").unwrap()
+    Regex::new(r"(?m)^(?P<indent>[ \t]*)(?:export\s+)?(?:const|let|var)\s+(?P<name>[A-Za-z_][A-Za-z0-9_]*)\s*=\s*(?:async\s+)?\(?[^\n]*=>").unwrap()
 });
 
 static GO_FUNC_RE: Lazy<Regex> = Lazy::new(|| {
-    Regex::new(r"(?m)^(?P<indent>\s*)func\s+(?:\([^)]+\)\s*)?(?P<name>[A-Za-z_][A-Za-z0-9_]*)\s*\(")
-        .unwrap()
+    Regex::new(
+        r"(?m)^(?P<indent>[ \t]*)func\s+(?:\([^)]+\)\s*)?(?P<name>[A-Za-z_][A-Za-z0-9_]*)\s*\(",
+    )
+    .unwrap()
 });
 
 static JAVA_CLASS_RE: Lazy<Regex> = Lazy::new(|| {
-    Regex::new(r"(?m)^(?P<indent>\s*)(?:public|protected|private|abstract|final|static|sealed|class|interface|record|enum|\s)+\s*(?:class|interface|record|enum)\s+(?P<name>[A-Za-z_][A-Za-z0-9_]*)").unwrap()
+    Regex::new(r"(?m)^(?P<indent>[ \t]*)(?:public|protected|private|abstract|final|static|sealed|class|interface|record|enum|\s)+\s*(?:class|interface|record|enum)\s+(?P<name>[A-Za-z_][A-Za-z0-9_]*)").unwrap()
 });
 
 static JAVA_METHOD_RE: Lazy<Regex> = Lazy::new(|| {
-    Regex::new(r"(?m)^(?P<indent>\s*)(?:public|protected|private|static|final|synchronized|abstract|default|async|override|mutating|class|\s)+[A-Za-z0-9_<>,\[\]]+\s+(?P<name>[A-Za-z_][A-Za-z0-9_]*)\s*\(").unwrap()
+    Regex::new(r"(?m)^(?P<indent>[ \t]*)(?:public|protected|private|static|final|synchronized|abstract|default|async|override|mutating|class|\s)+[A-Za-z0-9_<>,\[\]]+\s+(?P<name>[A-Za-z_][A-Za-z0-9_]*)\s*\(").unwrap()
 });
 
 static GENERIC_FUNC_RE: Lazy<Regex> = Lazy::new(|| {
-    Regex::new(r"(?m)^(?P<indent>\s*)(?:pub\s+|export\s+|public\s+|private\s+|protected\s+|static\s+|final\s+|async\s+|fn\s+|function\s+|def\s+)*(?:fn|function)\s+(?P<name>[A-Za-z_][A-Za-z0-9_]*)").unwrap()
+    Regex::new(r"(?m)^(?P<indent>[ \t]*)(?:pub\s+|export\s+|public\s+|private\s+|protected\s+|static\s+|final\s+|async\s+|fn\s+|function\s+|def\s+)*(?:fn|function)\s+(?P<name>[A-Za-z_][A-Za-z0-9_]*)").unwrap()
 });
 
 static GENERIC_CLASS_RE: Lazy<Regex> = Lazy::new(|| {
-    Regex::new(r"(?m)^(?P<indent>\s*)(?:export\s+|public\s+|private\s+|protected\s+|abstract\s+|final\s+)*(?:class|struct|enum|trait)\s+(?P<name>[A-Za-z_][A-Za-z0-9_]*)").unwrap()
+    Regex::new(r"(?m)^(?P<indent>[ \t]*)(?:export\s+|public\s+|private\s+|protected\s+|abstract\s+|final\s+)*(?:class|struct|enum|trait)\s+(?P<name>[A-Za-z_][A-Za-z0-9_]*)").unwrap()
 });
 
 static PY_DEF_RE: Lazy<Regex> = Lazy::new(|| {
-    Regex::new(r"(?m)^(?P<indent>\s*)(?:async\s+)?def\s+(?P<name>[A-Za-z_][A-Za-z0-9_]*)\s*\(")
+    Regex::new(r"(?m)^(?P<indent>[ \t]*)(?:async\s+)?def\s+(?P<name>[A-Za-z_][A-Za-z0-9_]*)\s*\(")
         .unwrap()
 });
 
 static PY_CLASS_RE: Lazy<Regex> = Lazy::new(|| {
-    Regex::new(r"(?m)^(?P<indent>\s*)class\s+(?P<name>[A-Za-z_][A-Za-z0-9_]*)").unwrap()
+    Regex::new(r"(?m)^(?P<indent>[ \t]*)class\s+(?P<name>[A-Za-z_][A-Za-z0-9_]*)").unwrap()
+});
+
+/// Module-level ALL_CAPS assignments (optionally annotated), the usual Python
+/// spelling of a constant. Comparisons (`==`) are excluded.
+static PY_CONST_RE: Lazy<Regex> = Lazy::new(|| {
+    Regex::new(r"(?m)^(?P<indent>)(?P<name>[A-Z][A-Z0-9_]*)\s*(?::[^=\n]+)?=[^=]").unwrap()
 });
 
 static RUST_PATTERNS: &[BracePattern] = &[
@@ -286,6 +594,10 @@ static RUST_PATTERNS: &[BracePattern] = &[
     brace_pattern(&RUST_ENUM_RE, "enum"),
     brace_pattern(&RUST_TRAIT_RE, "trait"),
     brace_pattern(&RUST_IMPL_RE, "impl"),
+    brace_pattern(&RUST_MOD_RE, "module"),
+    declaration_pattern(&RUST_CONST_RE, "constant"),
+    declaration_pattern(&RUST_STATIC_RE, "static"),
+    declaration_pattern(&RUST_TYPE_RE, "type_alias"),
 ];
 
 static JS_PATTERNS: &[BracePattern] = &[
@@ -315,27 +627,332 @@ fn brace_patterns(language: Language) -> &'static [BracePattern] {
         Language::Generic => GENERIC_PATTERNS,
         // Fallback for Python handled separately
         Language::Python => &[],
+        Language::Config | Language::Markup => &[],
     }
 }
 
 fn extract_symbols(content: &str, lines: &FileLines, language: Language) -> Vec<FileSymbol> {
-    match language {
+    let mut symbols = match language {
         Language::Python => parse_python_symbols(content, lines),
         _ => parse_brace_symbols(content, lines, language),
+    };
+
+    let parents = enclosing_indices(lines, &symbols);
+    let scopes = (0..symbols.len())
+        .map(|index| {
+            let mut scope = Vec::new();
+            let mut parent = parents[index];
+            while let Some(ancestor) = parent {
+                scope.push(container_name(&symbols[ancestor]));
+                parent = parents[ancestor];
+            }
+            scope.reverse();
+            scope
+        })
+        .collect::<Vec<_>>();
+    for (symbol, scope) in symbols.iter_mut().zip(scopes) {
+        symbol.enclosing = scope.last().cloned();
+        symbol.scope = scope;
+    }
+    let attributes = (0..symbols.len())
+        .map(|index| symbol_attributes(content, lines, language, &symbols, &parents, index))
+        .collect::<Vec<_>>();
+    for (symbol, attributes) in symbols.iter_mut().zip(attributes) {
+        symbol.attributes = attributes;
+    }
+    symbols
+}
+
+/// Modifier keywords each language may write before a declaration's name.
+fn modifier_keywords(language: Language) -> &'static [&'static str] {
+    match language {
+        Language::Rust => &["pub", "const", "async", "unsafe"],
+        Language::Python => &["async"],
+        Language::Typescript | Language::Javascript => &[
+            "export",
+            "default",
+            "declare",
+            "abstract",
+            "public",
+            "private",
+            "protected",
+            "static",
+            "readonly",
+            "async",
+        ],
+        Language::Java | Language::Csharp => &[
+            "public",
+            "private",
+            "protected",
+            "internal",
+            "static",
+            "abstract",
+            "final",
+            "sealed",
+            "virtual",
+            "override",
+            "synchronized",
+            "default",
+            "async",
+        ],
+        Language::Go | Language::Generic | Language::Config | Language::Markup => &[],
+    }
+}
+
+/// Modifiers of `symbols[index]` in source order: keywords from
+/// [`modifier_keywords`] before the name except the declaration keyword
+/// itself (so `pub const X` gives `pub` but `pub const fn` gives `pub` and
+/// `const`), restricted Rust visibility written out (`pub(crate)`), `async`
+/// for JS arrow functions, `exported` for capitalised Go names, and
+/// `generator` for Python functions that `yield` and JS `function*`.
+fn symbol_attributes(
+    content: &str,
+    lines: &FileLines,
+    language: Language,
+    symbols: &[FileSymbol],
+    parents: &[Option<usize>],
+    index: usize,
+) -> Vec<String> {
+    static RESTRICTED_PUB_RE: Lazy<Regex> =
+        Lazy::new(|| Regex::new(r"^pub\s*\(([^)]*)\)").expect("valid visibility regex"));
+    static YIELD_RE: Lazy<Regex> =
+        Lazy::new(|| Regex::new(r"\byield\b").expect("valid yield regex"));
+
+    let symbol = &symbols[index];
+    let signature = symbol.signature.trim_start();
+    let is_ident = |c: char| c.is_alphanumeric() || c == '_';
+    // The name is the first whole-word occurrence; everything before it is
+    // modifiers and the declaration keyword.
+    let name_at = signature
+        .match_indices(symbol.name.as_str())
+        .find(|(at, _)| {
+            !signature[..*at].ends_with(is_ident)
+                && !signature[at + symbol.name.len()..].starts_with(is_ident)
+        })
+        .map_or(signature.len(), |(at, _)| at);
+    let prefix = &signature[..name_at];
+    let rest = &signature[name_at + symbol.name.len().min(signature.len() - name_at)..];
+
+    let keywords = modifier_keywords(language);
+    let mut words = Vec::new();
+    let mut remaining = prefix;
+    while !remaining.is_empty() {
+        if let Some(caps) = RESTRICTED_PUB_RE.captures(remaining) {
+            words.push(format!("pub({})", caps[1].trim()));
+            remaining = &remaining[caps[0].len()..];
+            continue;
+        }
+        let end = remaining
+            .find(|c: char| !is_ident(c))
+            .unwrap_or(remaining.len());
+        if end == 0 {
+            remaining = &remaining[remaining.chars().next().map_or(1, char::len_utf8)..];
+            continue;
+        }
+        words.push(remaining[..end].to_string());
+        remaining = &remaining[end..];
+    }
+    // The last word before the name declares the symbol (`fn`, `const`,
+    // `def`, or a return type); it is never a modifier.
+    words.pop();
+
+    let mut attributes = Vec::new();
+    let mut add = |attribute: String| {
+        if !attributes.contains(&attribute) {
+            attributes.push(attribute);
+        }
+    };
+    for word in words {
+        if word.starts_with("pub(") || keywords.contains(&word.as_str()) {
+            add(word);
+        }
+    }
+
+    match language {
+        Language::Javascript | Language::Typescript => {
+            let arrow = rest.trim_start().strip_prefix('=').map(str::trim_start);
+            if arrow.is_some_and(|value| {
+                value
+                    .strip_prefix("async")
+                    .is_some_and(|after| !after.starts_with(is_ident))
+            }) {
+                add("async".to_string());
+            }
+            if prefix.contains('*') {
+                add("generator".to_string());
+            }
+        }
+        Language::Go if symbol.name.starts_with(char::is_uppercase) => {
+            add("exported".to_string());
+        }
+        Language::Python if symbol.kind == "function" => {
+            if let BodyStyle::Indented { start, end, .. } = symbol.body {
+                // A `yield` inside a nested function or class belongs to it.
+                let nested = (0..symbols.len())
+                    .filter(|&child| parents[child] == Some(index))
+                    .map(|child| {
+                        let from = lines.bounds(symbols[child].line - 1).0;
+                        from..symbol_end_offset(lines, &symbols[child])
+                    })
+                    .collect::<Vec<_>>();
+                let body = &content[start..end.min(content.len())];
+                if YIELD_RE.find_iter(body).any(|found| {
+                    !nested
+                        .iter()
+                        .any(|range| range.contains(&(start + found.start())))
+                }) {
+                    add("generator".to_string());
+                }
+            }
+        }
+        _ => {}
+    }
+    attributes
+}
+
+/// For each symbol, the index of the nearest other symbol whose body range
+/// (signature line through closing brace or last indented line) contains
+/// its signature line.
+fn enclosing_indices(lines: &FileLines, symbols: &[FileSymbol]) -> Vec<Option<usize>> {
+    let mut order = (0..symbols.len()).collect::<Vec<_>>();
+    order.sort_by_key(|&index| (symbols[index].line, symbols[index].column));
+
+    let mut parents = vec![None; symbols.len()];
+    // Open ancestors with the byte offset their body ends at.
+    let mut stack: Vec<(usize, usize)> = Vec::new();
+    for index in order {
+        let start = lines.bounds(symbols[index].line - 1).0;
+        while stack.last().is_some_and(|(end, _)| start >= *end) {
+            stack.pop();
+        }
+        parents[index] = stack.last().map(|(_, parent)| *parent);
+        stack.push((symbol_end_offset(lines, &symbols[index]), index));
     }
+    parents
 }
 
+/// Name that members use for `symbol` as their container: the implementing
+/// type for Rust `impl` blocks (`impl Display for Foo` gives `Foo`), otherwise
+/// the symbol's own name. Generic arguments are dropped.
+fn container_name(symbol: &FileSymbol) -> String {
+    let name = if symbol.kind == "impl" {
+        symbol
+            .signature
+            .split_once(" for ")
+            .and_then(|(_, self_type)| self_type.split_whitespace().next())
+            .unwrap_or(&symbol.name)
+    } else {
+        &symbol.name
+    };
+    name.split(['<', '{']).next().unwrap_or(name).to_string()
+}
+
+/// Whether a symbol enclosed by `enclosing` belongs to `container`. A
+/// path-qualified container such as `crate::cli::Cli` also matches by its last
+/// segment.
+fn container_matches(enclosing: Option<&str>, container: &str, case_sensitive: bool) -> bool {
+    let Some(enclosing) = enclosing else {
+        return false;
+    };
+    let last_segment = enclosing.rsplit("::").next().unwrap_or(enclosing);
+    [enclosing, last_segment].iter().any(|candidate| {
+        if case_sensitive {
+            *candidate == container
+        } else {
+            candidate.eq_ignore_ascii_case(container)
+        }
+    })
+}
+
+/// Fully qualified name of `symbol` declared in `path`, combining the module
+/// path inferred from the file's location with the enclosing containers:
+/// `crate::foo::Bar::method` for Rust, `pkg.module.Class.method` for Python.
+/// Falls back to the bare name, with `false`, when the module path cannot be
+/// determined.
+fn qualified_name(path: &Path, language: Language, symbol: &FileSymbol) -> (String, bool) {
+    let (module, separator) = match language {
+        Language::Rust => (rust_module_path(path), "::"),
+        Language::Python => (python_module_path(path), "."),
+        _ => (None, "."),
+    };
+    let Some(mut segments) = module else {
+        return (symbol.name.clone(), false);
+    };
+    segments.extend(symbol.scope.iter().cloned());
+    segments.push(symbol.name.clone());
+    (segments.join(separator), true)
+}
+
+/// `crate::a::b` for `src/a/b.rs` or `src/a/b/mod.rs` in the crate whose
+/// `Cargo.toml` sits next to the nearest enclosing `src` directory. Files
+/// under `src/bin` are separate crates and are not resolved.
+fn rust_module_path(path: &Path) -> Option<Vec<String>> {
+    let src = path
+        .ancestors()
+        .skip(1)
+        .find(|dir| dir.file_name().is_some_and(|name| name == "src"))?;
+    if !src.parent()?.join("Cargo.toml").is_file() {
+        return None;
+    }
+    let relative = path.strip_prefix(src).ok()?.with_extension("");
+    let mut segments = relative
+        .iter()
+        .map(|segment| segment.to_string_lossy().to_string())
+        .collect::<Vec<_>>();
+    if segments.first().is_some_and(|first| first == "bin") {
+        return None;
+    }
+    match segments.as_slice() {
+        [root] if root == "lib" || root == "main" => segments.clear(),
+        [.., last] if last == "mod" => {
+            segments.pop();
+        }
+        _ => {}
+    }
+    segments.insert(0, "crate".to_string());
+    Some(segments)
+}
+
+/// Dotted module name for a Python file: its stem (dropped for
+/// `__init__.py`) prefixed by every enclosing package directory, i.e. each
+/// ancestor containing an `__init__.py`.
+fn python_module_path(path: &Path) -> Option<Vec<String>> {
+    let mut segments = Vec::new();
+    let stem = path.file_stem()?.to_string_lossy();
+    if stem != "__init__" {
+        segments.push(stem.to_string());
+    }
+    for dir in path.ancestors().skip(1) {
+        if !dir.join("__init__.py").is_file() {
+            break;
+        }
+        segments.push(dir.file_name()?.to_string_lossy().to_string());
+    }
+    segments.reverse();
+    (!segments.is_empty()).then_some(segments)
+}
+
+/// Kinds produced by `parse_python_symbols`; keep in sync when adding detectors.
+const PYTHON_KINDS: &[&str] = &["function", "class", "constant"];
+
 fn parse_python_symbols(content: &str, lines: &FileLines) -> Vec<FileSymbol> {
     let mut symbols = Vec::new();
 
     for caps in PY_DEF_RE.captures_iter(content) {
-        if let Some(symbol) = build_python_symbol(&caps, lines, "function") {
+        if let Some(symbol) = build_python_symbol(&caps, lines, PYTHON_KINDS[0]) {
             symbols.push(symbol);
         }
     }
 
     for caps in PY_CLASS_RE.captures_iter(content) {
-        if let Some(symbol) = build_python_symbol(&caps, lines, "class") {
+        if let Some(symbol) = build_python_symbol(&caps, lines, PYTHON_KINDS[1]) {
+            symbols.push(symbol);
+        }
+    }
+
+    for caps in PY_CONST_RE.captures_iter(content) {
+        if let Some(mut symbol) = build_python_symbol(&caps, lines, PYTHON_KINDS[2]) {
+            symbol.body = BodyStyle::None;
             symbols.push(symbol);
         }
     }
@@ -353,7 +970,7 @@ fn build_python_symbol(
     let indent = caps.name("indent").map(|m| m.as_str()).unwrap_or("");
     let line_idx = lines.line_index(caps.get(0)?.start());
     let line_text = lines.text(line_idx).trim_end().to_string();
-    let column = indent.len() + 1;
+    let column = lines.column(line_idx, indent.len());
     let body = locate_python_body(lines, line_idx, indent);
 
     Some(FileSymbol {
@@ -363,6 +980,9 @@ fn build_python_symbol(
         line: line_idx + 1,
         column,
         body,
+        enclosing: None,
+        scope: Vec::new(),
+        attributes: Vec::new(),
     })
 }
 
@@ -427,9 +1047,14 @@ fn parse_brace_symbols(content: &str, lines: &FileLines, language: Language) ->
                 .name("indent")
                 .map(|m| m.as_str())
                 .unwrap_or_else(|| leading_whitespace(lines.text(line_idx)));
-            let column = indent.len() + 1;
-            let line_end = lines.bounds(line_idx).1;
-            let body = locate_brace_body(content, line_end, indent);
+            let column = lines.column(line_idx, indent.len());
+            // Scan from the end of the declaration match so an opening brace on
+            // the signature line itself is found.
+            let body = if pattern.has_body {
+                locate_brace_body(content, match_range.end(), indent)
+            } else {
+                BodyStyle::None
+            };
 
             symbols.push(FileSymbol {
                 name,
@@ -438,6 +1063,9 @@ fn parse_brace_symbols(content: &str, lines: &FileLines, language: Language) ->
                 line: line_idx + 1,
                 column,
                 body,
+                enclosing: None,
+                scope: Vec::new(),
+                attributes: Vec::new(),
             });
         }
     }
@@ -460,16 +1088,27 @@ fn locate_brace_body(content: &str, search_start: usize, indent: &str) -> BodySt
     }
 }
 
+/// Byte range between the first `{` at or after `index` and its matching
+/// `}`, skipping braces inside string and char literals, comments and raw
+/// strings. `None` when a `;` ends the declaration before any body opens.
 fn find_brace_block(content: &str, mut index: usize) -> Option<(usize, usize)> {
     let bytes = content.as_bytes();
     let len = bytes.len();
 
     while index < len {
+        if let Some(end) = skip_comment_or_raw_string(bytes, index) {
+            index = end;
+            continue;
+        }
         match bytes[index] {
             b'{' => {
                 let mut depth = 1;
                 let mut cursor = index + 1;
                 while cursor < len {
+                    if let Some(end) = skip_comment_or_raw_string(bytes, cursor) {
+                        cursor = end;
+                        continue;
+                    }
                     match bytes[cursor] {
                         b'{' => depth += 1,
                         b'}' => {
@@ -478,7 +1117,11 @@ fn find_brace_block(content: &str, mut index: usize) -> Option<(usize, usize)> {
                                 return Some((index + 1, cursor));
                             }
                         }
-                        b'"' | b'\'' | b'`' => {
+                        b'"' | b'`' => {
+                            cursor = skip_string(bytes, cursor);
+                            continue;
+                        }
+                        b'\'' if is_char_literal(content, cursor) => {
                             cursor = skip_string(bytes, cursor);
                             continue;
                         }
@@ -489,7 +1132,11 @@ fn find_brace_block(content: &str, mut index: usize) -> Option<(usize, usize)> {
                 break;
             }
             b';' => return None,
-            b'"' | b'\'' | b'`' => {
+            b'"' | b'`' => {
+                index = skip_string(bytes, index);
+                continue;
+            }
+            b'\'' if is_char_literal(content, index) => {
                 index = skip_string(bytes, index);
                 continue;
             }
@@ -500,23 +1147,6 @@ fn find_brace_block(content: &str, mut index: usize) -> Option<(usize, usize)> {
     None
 }
 
-fn skip_string(bytes: &[u8], mut index: usize) -> usize {
-    let quote = bytes[index];
-    index += 1;
-    while index < bytes.len() {
-        let b = bytes[index];
-        if b == b'\\' {
-            index += 2;
-            continue;
-        }
-        if b == quote {
-            return index + 1;
-        }
-        index += 1;
-    }
-    bytes.len()
-}
-
 fn compute_inner_indent(content: &str, start: usize, end: usize, base_indent: &str) -> String {
     let slice = &content[start..end];
     for line in slice.lines() {
@@ -558,10 +1188,7 @@ fn find_symbol_tool() -> Tool {
                 "type": "string",
                 "description": "Symbol name or pattern to search for",
             },
-            "path": {
-                "type": "string",
-                "description": "File or directory to inspect. Defaults to current working directory.",
-            },
+            "path": search_roots_schema("File or directory to inspect, or a list of them searched in turn with each match reporting its `root`. Defaults to current working directory."),
             "match_substring": {
                 "type": "boolean",
                 "description": "Allow substring matches instead of exact matches",
@@ -577,18 +1204,87 @@ fn find_symbol_tool() -> Tool {
                 "description": "Include symbol body text when available",
                 "default": false,
             },
+            "include_signature_in_body": {
+                "type": "boolean",
+                "description": "Make `body` the complete symbol text, from the signature line through the end of the body. Implies `include_body`",
+                "default": false,
+            },
+            "include_qualified_name": {
+                "type": "boolean",
+                "description": "Attach `qualified_name` (e.g. `crate::foo::Bar::method`, `pkg.Class.method`) built from the module path and enclosing containers. When the module path cannot be inferred it is the bare name and `qualified_name_resolved` is false",
+                "default": false,
+            },
             "kinds": {
                 "type": "array",
                 "items": {"type": "string"},
                 "description": "Restrict to specific symbol kinds (e.g. function, class)",
             },
+            "attributes": {
+                "type": "array",
+                "items": {"type": "string", "minLength": 1},
+                "description": "Only symbols having all of these attributes, e.g. [\"pub\", \"async\"]. Attributes are declaration modifiers (`pub`, `pub(crate)`, `async`, `const`, `unsafe`, `export`, `default`, `static`, `public`, ...) plus `generator` and, for Go, `exported`",
+            },
             "max_results": {
                 "type": "integer",
                 "minimum": 1,
                 "description": "Maximum number of results to return",
+            },
+            "group_by_file": {
+                "type": "boolean",
+                "description": "Return `files: [{ path, count, symbols }]` sorted by count instead of a flat `matches` list",
+                "default": false,
+            },
+            "compact": {
+                "type": "boolean",
+                "description": "Return `matches` as `\"name (path:line)\"` strings, for existence checks and name lists. Cannot be combined with `include_body`, `include_signature_in_body`, `include_qualified_name` or `group_by_file`",
+                "default": false,
+            },
+            "order_by": {
+                "type": "string",
+                "enum": ["location", "name", "kind"],
+                "description": "Sort matches by path and line, by name (case-insensitive) or grouped by kind, then keep the first `max_results`. Without it matches keep directory walk order",
+            },
+            "max_depth": {
+                "type": "integer",
+                "minimum": 1,
+                "description": "Maximum directory depth to descend (default 16)",
+            },
+            "max_files": {
+                "type": "integer",
+                "minimum": 1,
+                "description": "Stop after scanning this many files (default 10000)",
+            },
+            "exclude": {
+                "type": "array",
+                "items": {"type": "string"},
+                "description": "Glob patterns (relative to the search root) for files or directories to skip, e.g. `*_test.go` or `generated/**`",
+            },
+            "extensions": {
+                "type": "array",
+                "items": {"type": "string"},
+                "description": "Only scan files with these extensions (e.g. [\"rs\", \"toml\"]); other files are skipped without being read",
+            },
+            "stream": {
+                "type": "boolean",
+                "description": "On transports that deliver notifications, send each match as a `partial_result` notification as soon as it is found; the response is then a summary with `streamed` instead of `matches`. Cannot be combined with `order_by` or `group_by_file`",
+                "default": false,
             }
         },
         "required": ["name"],
+        "examples": [
+            {"name": "parse", "path": "src", "kinds": ["function"], "include_body": true, "extensions": ["rs"]},
+            {"name": "Config", "order_by": "kind"},
+            {"name": "", "kinds": ["function"], "attributes": ["pub", "async"]},
+            {"name": "Client", "path": ["crates/api", "crates/cli"], "max_results": 20},
+            {"name": "test_", "compact": true, "max_results": 200},
+            {"name": "handle", "max_results": 5000, "stream": true}
+        ],
+        "x-positional-order": [
+            "name", "path", "match_substring", "case_sensitive", "include_body",
+            "include_signature_in_body", "include_qualified_name", "kinds", "attributes",
+            "max_results", "group_by_file", "compact", "order_by", "max_depth", "max_files",
+            "exclude", "extensions", "stream"
+        ],
         "additionalProperties": false
     });
 
@@ -596,7 +1292,7 @@ fn find_symbol_tool() -> Tool {
     struct Params {
         name: String,
         #[serde(default)]
-        path: Option<String>,
+        path: Option<SearchRoots>,
         #[serde(default = "default_true")]
         match_substring: bool,
         #[serde(default)]
@@ -604,71 +1300,163 @@ fn find_symbol_tool() -> Tool {
         #[serde(default)]
         include_body: Option<bool>,
         #[serde(default)]
+        include_signature_in_body: bool,
+        #[serde(default)]
+        include_qualified_name: bool,
+        #[serde(default)]
         kinds: Option<Vec<String>>,
         #[serde(default)]
+        attributes: Option<Vec<String>>,
+        #[serde(default)]
         max_results: Option<usize>,
+        #[serde(default)]
+        group_by_file: bool,
+        #[serde(default)]
+        compact: bool,
+        #[serde(default)]
+        order_by: Option<SymbolOrder>,
+        #[serde(default)]
+        max_depth: Option<usize>,
+        #[serde(default)]
+        max_files: Option<usize>,
+        #[serde(default)]
+        exclude: Option<Vec<String>>,
+        #[serde(default)]
+        extensions: Option<Vec<String>>,
+        #[serde(default)]
+        stream: bool,
     }
 
     let handler = move |params| -> Result<Value> {
         let args: Params =
             serde_json::from_value(params).context("Invalid arguments for find_symbol")?;
-        let root = match &args.path {
-            Some(path) => resolve_path(path)?,
-            None => std::env::current_dir()?,
-        };
+        if args.stream && (args.order_by.is_some() || args.group_by_file) {
+            anyhow::bail!("`stream` cannot be combined with `order_by` or `group_by_file`");
+        }
+        if args.compact
+            && (args.include_body == Some(true)
+                || args.include_signature_in_body
+                || args.include_qualified_name
+                || args.group_by_file)
+        {
+            anyhow::bail!(
+                "`compact` cannot be combined with `include_body`, `include_signature_in_body`, `include_qualified_name` or `group_by_file`"
+            );
+        }
+        let roots = SearchRoots::resolve(args.path.as_ref())?;
+        let multi_root = SearchRoots::is_many(args.path.as_ref());
 
         let case_sensitive = args.case_sensitive.unwrap_or(false);
-        let include_body = args.include_body.unwrap_or(false);
+        let include_body = args.include_body.unwrap_or(false) || args.include_signature_in_body;
         let max_results = args.max_results.unwrap_or(50);
         let kind_filter: Option<HashSet<String>> = args
             .kinds
             .as_ref()
             .map(|kinds| kinds.iter().map(|s| s.to_lowercase()).collect());
+        let attribute_filter = args.attributes.as_ref().map(|attributes| {
+            attributes
+                .iter()
+                .map(|attribute| attribute.trim().to_lowercase().replace(' ', ""))
+                .collect::<Vec<_>>()
+        });
 
+        let query = SymbolQuery {
+            name: &args.name,
+            match_substring: args.match_substring,
+            case_sensitive,
+            include_body,
+            include_signature_in_body: args.include_signature_in_body,
+            include_qualified_name: args.include_qualified_name,
+            kind_filter: kind_filter.as_ref(),
+            attribute_filter: attribute_filter.as_deref(),
+            // Ordered results are truncated only once everything is sorted.
+            max_results: if args.order_by.is_some() {
+                usize::MAX
+            } else {
+                max_results
+            },
+        };
+        let max_depth = args.max_depth.unwrap_or(DEFAULT_SEARCH_MAX_DEPTH);
+        let max_files = args.max_files.unwrap_or(DEFAULT_SEARCH_MAX_FILES);
         let mut matches = Vec::new();
+        let extensions = normalize_extensions(args.extensions.as_deref());
+        let mut walker =
+            FileWalker::new(WalkFilter::All, max_depth, max_files).extensions(extensions.as_ref());
+        let mut progress = ProgressReporter::new("find_symbol");
+        let mut stream = ResultStream::new("find_symbol", args.stream);
+        let mut root_labels = Vec::new();
+
+        // Roots share the result and file caps; later roots are skipped
+        // once either is reached.
+        for root in &roots {
+            if matches.len() >= query.max_results || walker.files_capped {
+                break;
+            }
+            let exclude = ExcludeSet::new(root, args.exclude.as_deref())?;
+            let root_label = multi_root.then(|| display_path(root));
+            // Tag matches found since `from` with their root and stream them.
+            let mut publish = |matches: &mut [Value], from: usize| {
+                for found in &mut matches[from..] {
+                    if let Some(root) = &root_label {
+                        found["root"] = json!(root);
+                    }
+                    stream.send(if args.compact {
+                        json!(compact_match(found))
+                    } else {
+                        found.clone()
+                    });
+                }
+            };
 
-        if root.is_file() {
-            collect_symbols_for_file(
-                &root,
-                &args.name,
-                args.match_substring,
-                case_sensitive,
-                include_body,
-                kind_filter.as_ref(),
-                max_results,
-                &mut matches,
-            )?;
-        } else {
-            for entry in WalkDir::new(&root)
-                .follow_links(false)
-                .into_iter()
-                .filter_map(|e| e.ok())
-                .filter(|e| e.file_type().is_file())
-            {
-                collect_symbols_for_file(
-                    entry.path(),
-                    &args.name,
-                    args.match_substring,
-                    case_sensitive,
-                    include_body,
-                    kind_filter.as_ref(),
-                    max_results,
-                    &mut matches,
-                )?;
+            walker.walk(root, &exclude, |path| {
+                let from = matches.len();
+                collect_symbols_for_file(path, &query, &mut matches)?;
+                publish(&mut matches, from);
+                progress.file_scanned(matches.len());
+                Ok(matches.len() < query.max_results)
+            })?;
 
-                if matches.len() >= max_results {
-                    break;
-                }
-            }
+            root_labels.extend(root_label);
         }
 
         let truncated = matches.len() >= max_results;
-        Ok(json!({
+        if let Some(order) = args.order_by {
+            order_matches(&mut matches, order);
+            matches.truncate(max_results);
+        }
+        let count = matches.len();
+        let mut response = json!({
             "query": args.name,
-            "count": matches.len(),
+            "count": count,
             "truncated": truncated,
-            "matches": matches,
-        }))
+            "files_scanned": walker.files_scanned,
+            "files_capped": walker.files_capped,
+            "depth_limited": walker.depth_limited,
+            "files_excluded": walker.files_excluded,
+        });
+        if multi_root {
+            let root_counts: Vec<Value> = root_labels
+                .iter()
+                .map(|root| {
+                    let count = matches
+                        .iter()
+                        .filter(|found| found["root"] == *root)
+                        .count();
+                    json!({"root": root, "count": count})
+                })
+                .collect();
+            response["roots"] = json!(root_counts);
+        }
+        if args.compact {
+            response["matches"] = json!(matches.iter().map(compact_match).collect::<Vec<_>>());
+        } else if args.group_by_file {
+            response["files"] = json!(group_matches_by_file(matches));
+        } else {
+            response["matches"] = json!(matches);
+        }
+        stream.finish(&mut response, &["matches"]);
+
+        Ok(response)
     };
 
     Tool::new(
@@ -677,23 +1465,289 @@ fn find_symbol_tool() -> Tool {
         schema,
         Box::new(handler),
     )
+    .resolves_paths()
+    .cacheable()
+}
+
+fn find_duplicate_symbols_tool() -> Tool {
+    let schema = json!({
+        "type": "object",
+        "properties": {
+            "path": {
+                "type": "string",
+                "description": "File or directory to inspect. Defaults to current working directory.",
+            },
+            "same_kind": {
+                "type": "boolean",
+                "description": "Only report names declared at least twice with the same kind, so a struct and its impl block do not count",
+                "default": false,
+            },
+            "kinds": {
+                "type": "array",
+                "items": {"type": "string"},
+                "description": "Only consider symbols of these kinds (e.g. function)",
+            },
+            "case_sensitive": {
+                "type": "boolean",
+                "description": "Treat names differing only in case as distinct",
+                "default": true,
+            },
+            "max_results": {
+                "type": "integer",
+                "minimum": 1,
+                "description": "Maximum number of duplicate groups to return (default 50)",
+            },
+            "max_depth": {
+                "type": "integer",
+                "minimum": 1,
+                "description": "Maximum directory depth to descend (default 16)",
+            },
+            "max_files": {
+                "type": "integer",
+                "minimum": 1,
+                "description": "Stop after scanning this many files (default 10000)",
+            },
+            "exclude": {
+                "type": "array",
+                "items": {"type": "string"},
+                "description": "Glob patterns (relative to the search root) for files or directories to skip",
+            }
+        },
+        "examples": [{"path": "src", "same_kind": true, "kinds": ["function"]}],
+        "x-positional-order": [
+            "path", "same_kind", "kinds", "case_sensitive", "max_results", "max_depth",
+            "max_files", "exclude"
+        ],
+        "additionalProperties": false
+    });
+
+    #[derive(Deserialize)]
+    struct Params {
+        #[serde(default)]
+        path: Option<String>,
+        #[serde(default)]
+        same_kind: bool,
+        #[serde(default)]
+        kinds: Option<Vec<String>>,
+        #[serde(default = "default_true")]
+        case_sensitive: bool,
+        #[serde(default)]
+        max_results: Option<usize>,
+        #[serde(default)]
+        max_depth: Option<usize>,
+        #[serde(default)]
+        max_files: Option<usize>,
+        #[serde(default)]
+        exclude: Option<Vec<String>>,
+    }
+
+    let handler = move |params| -> Result<Value> {
+        let args: Params = serde_json::from_value(params)
+            .context("Invalid arguments for find_duplicate_symbols")?;
+        let root = match &args.path {
+            Some(path) => resolve_path(path)?,
+            None => base_dir()?,
+        };
+
+        let max_results = args.max_results.unwrap_or(50);
+        let max_depth = args.max_depth.unwrap_or(DEFAULT_SEARCH_MAX_DEPTH);
+        let max_files = args.max_files.unwrap_or(DEFAULT_SEARCH_MAX_FILES);
+        let kind_filter: Option<HashSet<String>> = args
+            .kinds
+            .as_ref()
+            .map(|kinds| kinds.iter().map(|s| s.to_lowercase()).collect());
+        let exclude = ExcludeSet::new(&root, args.exclude.as_deref())?;
+
+        // Keyed by name (lowercased unless case sensitive) and, with
+        // `same_kind`, the kind; insertion order is walk order.
+        type GroupKey = (String, Option<String>);
+        let mut groups: Vec<(GroupKey, Vec<Value>)> = Vec::new();
+        let mut group_index: HashMap<GroupKey, usize> = HashMap::new();
+        let mut record = |path: &Path| -> Result<()> {
+            let Some(parsed) = ParsedFile::from_path(path)? else {
+                return Ok(());
+            };
+            for symbol in &parsed.symbols {
+                if let Some(filter) = &kind_filter
+                    && !filter.contains(&symbol.kind)
+                {
+                    continue;
+                }
+                let name = if args.case_sensitive {
+                    symbol.name.clone()
+                } else {
+                    symbol.name.to_lowercase()
+                };
+                let key = (name, args.same_kind.then(|| symbol.kind.clone()));
+                let index = *group_index.entry(key.clone()).or_insert_with(|| {
+                    groups.push((key, Vec::new()));
+                    groups.len() - 1
+                });
+                groups[index].1.push(json!({
+                    "path": display_path(path),
+                    "line": symbol.line,
+                    "name": symbol.name,
+                    "kind": symbol.kind,
+                    "container": symbol.enclosing,
+                }));
+            }
+            Ok(())
+        };
+
+        let mut walker = FileWalker::new(WalkFilter::Default, max_depth, max_files);
+        let mut progress = ProgressReporter::new("find_duplicate_symbols");
+        walker.walk(&root, &exclude, |path| {
+            record(path)?;
+            progress.file_visited();
+            Ok(true)
+        })?;
+
+        let mut duplicates = groups
+            .into_iter()
+            .filter(|(_, locations)| locations.len() > 1)
+            .collect::<Vec<_>>();
+        duplicates.sort_by(|(a_key, a), (b_key, b)| b.len().cmp(&a.len()).then(a_key.cmp(b_key)));
+        let total = duplicates.len();
+        let duplicates = duplicates
+            .into_iter()
+            .take(max_results)
+            .map(|((name, kind), locations)| {
+                let mut kinds = locations
+                    .iter()
+                    .filter_map(|location| location["kind"].as_str())
+                    .collect::<Vec<_>>();
+                kinds.sort_unstable();
+                kinds.dedup();
+                let mut group = json!({
+                    "name": name,
+                    "count": locations.len(),
+                    "kinds": kinds,
+                    "locations": locations,
+                });
+                if let Some(kind) = kind {
+                    group["kind"] = json!(kind);
+                }
+                group
+            })
+            .collect::<Vec<_>>();
+
+        Ok(json!({
+            "path": display_path(&root),
+            "same_kind": args.same_kind,
+            "count": total,
+            "truncated": total > duplicates.len(),
+            "files_scanned": walker.files_scanned,
+            "files_capped": walker.files_capped,
+            "depth_limited": walker.depth_limited,
+            "files_excluded": walker.files_excluded,
+            "duplicates": duplicates,
+        }))
+    };
+
+    Tool::new(
+        "find_duplicate_symbols",
+        "List symbol names declared in more than one place across the project",
+        schema,
+        Box::new(handler),
+    )
+    .resolves_paths()
+    .cacheable()
 }
 
 fn default_true() -> bool {
     true
 }
 
-fn collect_symbols_for_file(
-    path: &Path,
-    query: &str,
+/// `name (path:line)` summary of a `find_symbol` match.
+fn compact_match(found: &Value) -> String {
+    format!(
+        "{} ({}:{})",
+        found["name"].as_str().unwrap_or_default(),
+        found["path"].as_str().unwrap_or_default(),
+        found["line"]
+    )
+}
+
+/// Group flat symbol matches by their `path`, densest files first. Ties keep
+/// walk order.
+fn group_matches_by_file(matches: Vec<Value>) -> Vec<Value> {
+    let mut groups: Vec<(String, Vec<Value>)> = Vec::new();
+    for mut entry in matches {
+        let path = match entry.as_object_mut().and_then(|obj| obj.remove("path")) {
+            Some(Value::String(path)) => path,
+            _ => String::new(),
+        };
+        match groups.iter_mut().find(|(existing, _)| *existing == path) {
+            Some((_, symbols)) => symbols.push(entry),
+            None => groups.push((path, vec![entry])),
+        }
+    }
+
+    groups.sort_by_key(|(_, symbols)| std::cmp::Reverse(symbols.len()));
+    groups
+        .into_iter()
+        .map(|(path, symbols)| {
+            json!({
+                "path": path,
+                "count": symbols.len(),
+                "symbols": symbols,
+            })
+        })
+        .collect()
+}
+
+/// Sort order for `find_symbol` matches.
+#[derive(Debug, Clone, Copy, Deserialize)]
+#[serde(rename_all = "lowercase")]
+enum SymbolOrder {
+    Location,
+    Name,
+    Kind,
+}
+
+/// Sort rendered `find_symbol` matches. Ties fall back to location so the
+/// result is deterministic regardless of walk order.
+fn order_matches(matches: &mut [Value], order: SymbolOrder) {
+    let text = |entry: &Value, key: &str| entry[key].as_str().unwrap_or_default().to_string();
+    let location = |entry: &Value| {
+        (
+            text(entry, "path"),
+            entry["line"].as_u64().unwrap_or_default(),
+            entry["column"].as_u64().unwrap_or_default(),
+        )
+    };
+    match order {
+        SymbolOrder::Location => matches.sort_by_cached_key(location),
+        SymbolOrder::Name => matches.sort_by_cached_key(|entry| {
+            let name = text(entry, "name");
+            (name.to_lowercase(), name, location(entry))
+        }),
+        SymbolOrder::Kind => matches.sort_by_cached_key(|entry| {
+            let name = text(entry, "name");
+            (text(entry, "kind"), name.to_lowercase(), location(entry))
+        }),
+    }
+}
+
+struct SymbolQuery<'a> {
+    name: &'a str,
     match_substring: bool,
     case_sensitive: bool,
     include_body: bool,
-    kind_filter: Option<&HashSet<String>>,
+    include_signature_in_body: bool,
+    include_qualified_name: bool,
+    kind_filter: Option<&'a HashSet<String>>,
+    /// Attributes a symbol must all have, lowercased.
+    attribute_filter: Option<&'a [String]>,
     max_results: usize,
+}
+
+fn collect_symbols_for_file(
+    path: &Path,
+    query: &SymbolQuery<'_>,
     matches: &mut Vec<Value>,
 ) -> Result<()> {
-    if matches.len() >= max_results {
+    if matches.len() >= query.max_results {
         return Ok(());
     }
 
@@ -702,34 +1756,56 @@ fn collect_symbols_for_file(
     };
 
     for symbol in parsed.symbols.iter() {
-        if matches.len() >= max_results {
+        if matches.len() >= query.max_results {
             break;
         }
 
-        if let Some(filter) = kind_filter {
-            if !filter.contains(&symbol.kind.to_lowercase()) {
-                continue;
-            }
+        if let Some(filter) = query.kind_filter
+            && !filter.contains(&symbol.kind.to_lowercase())
+        {
+            continue;
+        }
+
+        if let Some(required) = query.attribute_filter
+            && !required
+                .iter()
+                .all(|attribute| symbol.attributes.contains(attribute))
+        {
+            continue;
         }
 
-        if !symbol_name_matches(&symbol.name, query, match_substring, case_sensitive) {
+        if !symbol_name_matches(
+            &symbol.name,
+            query.name,
+            query.match_substring,
+            query.case_sensitive,
+        ) {
             continue;
         }
 
         let mut entry = json!({
             "name": symbol.name,
             "kind": symbol.kind,
-            "path": path.to_string_lossy(),
+            "path": display_path(path),
             "line": symbol.line,
             "column": symbol.column,
             "signature": symbol.signature,
+            "attributes": symbol.attributes,
             "language": parsed.language.as_str(),
         });
 
-        if include_body {
-            if let Some(body) = extract_body(&parsed.content, &symbol.body) {
-                entry["body"] = json!(body);
-            }
+        if query.include_signature_in_body {
+            entry["body"] = json!(extract_whole_symbol(&parsed, symbol));
+        } else if query.include_body
+            && let Some(body) = extract_body(&parsed.content, &symbol.body)
+        {
+            entry["body"] = json!(body);
+        }
+
+        if query.include_qualified_name {
+            let (qualified, resolved) = qualified_name(path, parsed.language, symbol);
+            entry["qualified_name"] = json!(qualified);
+            entry["qualified_name_resolved"] = json!(resolved);
         }
 
         matches.push(entry);
@@ -738,7 +1814,12 @@ fn collect_symbols_for_file(
     Ok(())
 }
 
-fn symbol_name_matches(symbol: &str, query: &str, substring: bool, case_sensitive: bool) -> bool {
+pub(crate) fn symbol_name_matches(
+    symbol: &str,
+    query: &str,
+    substring: bool,
+    case_sensitive: bool,
+) -> bool {
     if case_sensitive {
         if substring {
             symbol.contains(query)
@@ -776,6 +1857,59 @@ fn extract_body(content: &str, body: &BodyStyle) -> Option<String> {
     }
 }
 
+/// Full text of `symbol` from the start of its signature line through the
+/// line its body ends on; just the signature line for body-less symbols.
+fn extract_whole_symbol(parsed: &ParsedFile, symbol: &FileSymbol) -> String {
+    let start = parsed.lines.bounds(symbol.line - 1).0;
+    let end = symbol_end_offset(&parsed.lines, symbol).min(parsed.content.len());
+    parsed.content[start..end.max(start)]
+        .trim_end_matches(['\n', '\r'])
+        .to_string()
+}
+
+fn list_symbol_kinds_tool() -> Tool {
+    let schema = json!({
+        "type": "object",
+        "properties": {},
+        "examples": [{}],
+        "additionalProperties": false
+    });
+
+    let handler = move |_params| -> Result<Value> {
+        let mut all_kinds: Vec<&str> = Vec::new();
+        let languages = Language::ALL
+            .iter()
+            .map(|language| {
+                let kinds = language.symbol_kinds();
+                for kind in &kinds {
+                    if !all_kinds.contains(kind) {
+                        all_kinds.push(kind);
+                    }
+                }
+                json!({
+                    "language": language.as_str(),
+                    "extensions": language.extensions(),
+                    "kinds": kinds,
+                })
+            })
+            .collect::<Vec<_>>();
+        all_kinds.sort_unstable();
+
+        Ok(json!({
+            "kinds": all_kinds,
+            "languages": languages,
+            "extension_overrides": *LANGUAGE_OVERRIDES,
+        }))
+    };
+
+    Tool::new(
+        "list_symbol_kinds",
+        "List supported languages, their file extensions, and the symbol kinds detected for each",
+        schema,
+        Box::new(handler),
+    )
+}
+
 fn find_referencing_symbols_tool() -> Tool {
     let schema = json!({
         "type": "object",
@@ -785,9 +1919,26 @@ fn find_referencing_symbols_tool() -> Tool {
             "case_sensitive": {"type": "boolean", "default": false},
             "max_results": {"type": "integer", "minimum": 1},
             "context_lines": {"type": "integer", "minimum": 0},
-            "include_hidden": {"type": "boolean", "default": false}
+            "context_before": {"type": "integer", "minimum": 0, "description": "Lines of context before each match (defaults to `context_lines`)"},
+            "context_after": {"type": "integer", "minimum": 0, "description": "Lines of context after each match (defaults to `context_lines`)"},
+            "include_hidden": {"type": "boolean", "default": false},
+            "kind_filter": {
+                "type": "string",
+                "enum": ["definition", "reference"],
+                "description": "Only return matches of this kind. A match is a `definition` when it names a symbol declared on that line in a recognised language; everything else is a `reference`",
+            },
+            "exclude": {
+                "type": "array",
+                "items": {"type": "string"},
+                "description": "Glob patterns (relative to the search root) for files or directories to skip, e.g. `*_test.go` or `generated/**`",
+            }
         },
         "required": ["name"],
+        "examples": [{"name": "ParsedFile", "path": "src", "kind_filter": "reference"}],
+        "x-positional-order": [
+            "name", "path", "case_sensitive", "max_results", "context_lines", "context_before",
+            "context_after", "include_hidden", "kind_filter", "exclude"
+        ],
         "additionalProperties": false
     });
 
@@ -803,7 +1954,15 @@ fn find_referencing_symbols_tool() -> Tool {
         #[serde(default)]
         context_lines: Option<usize>,
         #[serde(default)]
+        context_before: Option<usize>,
+        #[serde(default)]
+        context_after: Option<usize>,
+        #[serde(default)]
         include_hidden: Option<bool>,
+        #[serde(default)]
+        kind_filter: Option<ReferenceKind>,
+        #[serde(default)]
+        exclude: Option<Vec<String>>,
     }
 
     let handler = move |params| -> Result<Value> {
@@ -811,14 +1970,16 @@ fn find_referencing_symbols_tool() -> Tool {
             .context("Invalid arguments for find_referencing_symbols")?;
         let root = match &args.path {
             Some(path) => resolve_path(path)?,
-            None => std::env::current_dir()?,
+            None => base_dir()?,
         };
 
         let case_sensitive = args.case_sensitive.unwrap_or(false);
         let max_results = args.max_results.unwrap_or(50);
-        let context_lines = args.context_lines.unwrap_or(2);
+        let context =
+            ContextWindow::new(args.context_lines, args.context_before, args.context_after);
         let include_hidden = args.include_hidden.unwrap_or(false);
 
+        let exclude = ExcludeSet::new(&root, args.exclude.as_deref())?;
         let mut matches = Vec::new();
 
         let symbol_pattern = RegexBuilder::new(&format!("\\b{}\\b", regex::escape(&args.name)))
@@ -826,66 +1987,87 @@ fn find_referencing_symbols_tool() -> Tool {
             .build()
             .with_context(|| format!("Failed to compile search pattern for '{}'", args.name))?;
 
-        if root.is_file() {
+        let filter = if include_hidden {
+            WalkFilter::All
+        } else {
+            WalkFilter::SkipHidden
+        };
+        let mut walker = FileWalker::new(filter, usize::MAX, usize::MAX);
+        let mut progress = ProgressReporter::new("find_referencing_symbols");
+        walker.walk(&root, &exclude, |path| {
             scan_file_for_references(
-                &root,
+                path,
                 &symbol_pattern,
-                context_lines,
+                context,
                 max_results,
+                args.kind_filter,
                 &mut matches,
             )?;
-        } else {
-            for entry in WalkDir::new(&root)
-                .follow_links(false)
-                .into_iter()
-                .filter_map(|e| e.ok())
-            {
-                if !entry.file_type().is_file() {
-                    continue;
-                }
-                if !include_hidden && is_hidden_path(entry.path()) {
-                    continue;
-                }
-                scan_file_for_references(
-                    entry.path(),
-                    &symbol_pattern,
-                    context_lines,
-                    max_results,
-                    &mut matches,
-                )?;
-                if matches.len() >= max_results {
-                    break;
-                }
-            }
-        }
+            progress.file_scanned(matches.len());
+            Ok(matches.len() < max_results)
+        })?;
 
         Ok(json!({
             "symbol": args.name,
             "count": matches.len(),
             "matches": matches,
+            "files_excluded": walker.files_excluded,
         }))
     };
 
     Tool::new(
         "find_referencing_symbols",
-        "Locate references to a symbol by searching for exact word matches",
+        "Locate references to a symbol by searching for exact word matches, classifying each as its definition or a reference",
         schema,
         Box::new(handler),
     )
+    .resolves_paths()
+    .cacheable()
 }
 
-fn is_hidden_path(path: &Path) -> bool {
-    path.components().any(|component| match component {
-        std::path::Component::Normal(name) => name.to_string_lossy().starts_with('.'),
-        _ => false,
-    })
+/// Whether a reference match is the symbol's declaration or a use of it.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+enum ReferenceKind {
+    Definition,
+    Reference,
+}
+
+impl ReferenceKind {
+    fn as_str(self) -> &'static str {
+        match self {
+            ReferenceKind::Definition => "definition",
+            ReferenceKind::Reference => "reference",
+        }
+    }
+}
+
+/// 1-based lines of `content` declaring a symbol whose whole name matches
+/// `pattern`. `impl` blocks name an existing type rather than declaring one,
+/// so they do not count. Empty for unrecognised languages.
+fn declaration_lines(path: &Path, content: &str, pattern: &Regex) -> HashSet<usize> {
+    let Some(language) = Language::from_path(path).filter(|lang| lang.declares_symbols()) else {
+        return HashSet::new();
+    };
+    let code = strip_comments(content, language);
+    extract_symbols(&code, &FileLines::new(content), language)
+        .into_iter()
+        .filter(|symbol| symbol.kind != "impl")
+        .filter(|symbol| {
+            pattern
+                .find(&symbol.name)
+                .is_some_and(|found| found.len() == symbol.name.len())
+        })
+        .map(|symbol| symbol.line)
+        .collect()
 }
 
 fn scan_file_for_references(
     path: &Path,
     pattern: &Regex,
-    context_lines: usize,
+    window: ContextWindow,
     max_results: usize,
+    kind_filter: Option<ReferenceKind>,
     matches: &mut Vec<Value>,
 ) -> Result<()> {
     if matches.len() >= max_results {
@@ -901,33 +2083,45 @@ fn scan_file_for_references(
     };
 
     let lines: Vec<&str> = content.lines().collect();
+    let declarations = declaration_lines(path, &content, pattern);
 
     for (idx, line) in lines.iter().enumerate() {
-        for capture in pattern.find_iter(line) {
+        for (occurrence, capture) in pattern.find_iter(line).enumerate() {
+            // The declared name is the first mention on its signature line.
+            let kind = if occurrence == 0 && declarations.contains(&(idx + 1)) {
+                ReferenceKind::Definition
+            } else {
+                ReferenceKind::Reference
+            };
+            if kind_filter.is_some_and(|filter| filter != kind) {
+                continue;
+            }
             let column = line[..capture.start()].chars().count() + 1;
             let preview = line.trim_end().to_string();
-            let mut context = Vec::new();
-
-            if context_lines > 0 {
-                let start = idx.saturating_sub(context_lines);
-                let end = usize::min(idx + context_lines, lines.len().saturating_sub(1));
-                for ctx_idx in start..=end {
-                    if ctx_idx == idx {
-                        continue;
-                    }
-                    context.push(json!({
+            let (start, end) = window.bounds(idx, lines.len());
+            let context = lines
+                .iter()
+                .enumerate()
+                .take(end + 1)
+                .skip(start)
+                .filter(|(ctx_idx, _)| *ctx_idx != idx)
+                .map(|(ctx_idx, text)| {
+                    json!({
                         "line": ctx_idx + 1,
-                        "text": lines[ctx_idx].trim_end(),
-                    }));
-                }
-            }
+                        "text": text.trim_end(),
+                    })
+                })
+                .collect::<Vec<_>>();
 
             matches.push(json!({
-                "path": path.to_string_lossy(),
+                "path": display_path(path),
                 "line": idx + 1,
                 "column": column,
+                "kind": kind.as_str(),
                 "preview": preview,
                 "context": context,
+                "context_before": idx - start,
+                "context_after": end - idx,
             }));
 
             if matches.len() >= max_results {
@@ -951,9 +2145,32 @@ fn get_symbols_overview_tool() -> Tool {
                 "type": "integer",
                 "minimum": 1,
                 "description": "Limit number of files when summarising a directory",
+            },
+            "max_depth": {
+                "type": "integer",
+                "minimum": 1,
+                "description": "Maximum directory depth to descend (default 4)",
+            },
+            "extensions": {
+                "type": "array",
+                "items": {"type": "string"},
+                "description": "Only summarise files with these extensions (e.g. [\"rs\"])",
+            },
+            "nested": {
+                "type": "boolean",
+                "description": "Nest symbols under the symbol whose body encloses them (methods under impl blocks or classes)",
+            },
+            "no_cache": {
+                "type": "boolean",
+                "default": false,
+                "description": "Re-parse every file instead of reusing symbols cached for unchanged content",
             }
         },
         "required": ["path"],
+        "examples": [{"path": "src/lib.rs", "nested": true}, {"path": "src", "no_cache": true}],
+        "x-positional-order": [
+            "path", "max_files", "max_depth", "extensions", "nested", "no_cache"
+        ],
         "additionalProperties": false
     });
 
@@ -962,6 +2179,14 @@ fn get_symbols_overview_tool() -> Tool {
         path: String,
         #[serde(default)]
         max_files: Option<usize>,
+        #[serde(default)]
+        max_depth: Option<usize>,
+        #[serde(default)]
+        extensions: Option<Vec<String>>,
+        #[serde(default)]
+        nested: bool,
+        #[serde(default)]
+        no_cache: bool,
     }
 
     let handler = move |params| -> Result<Value> {
@@ -970,12 +2195,10 @@ fn get_symbols_overview_tool() -> Tool {
         let path = resolve_path(&args.path)?;
 
         if path.is_file() {
-            let parsed =
-                ParsedFile::from_path(&path)?.context("Path is not a recognised source file")?;
-            let symbols = parsed
-                .symbols
-                .iter()
-                .map(|symbol| {
+            let (parsed, cached) = ParsedFile::from_path_cached(&path, args.no_cache)?
+                .context("Path is not a recognised source file")?;
+            let symbols = if args.nested {
+                nest_symbols(&parsed.lines, &parsed.symbols, |symbol| {
                     json!({
                         "name": symbol.name,
                         "kind": symbol.kind,
@@ -983,48 +2206,87 @@ fn get_symbols_overview_tool() -> Tool {
                         "signature": symbol.signature,
                     })
                 })
-                .collect::<Vec<_>>();
+            } else {
+                parsed
+                    .symbols
+                    .iter()
+                    .map(|symbol| {
+                        json!({
+                            "name": symbol.name,
+                            "kind": symbol.kind,
+                            "line": symbol.line,
+                            "signature": symbol.signature,
+                        })
+                    })
+                    .collect::<Vec<_>>()
+            };
 
             Ok(json!({
-                "path": path.to_string_lossy(),
+                "path": display_path(&path),
                 "language": parsed.language.as_str(),
-                "symbol_count": symbols.len(),
+                "symbol_count": parsed.symbols.len(),
+                "nested": args.nested,
+                "cached": cached,
                 "symbols": symbols,
             }))
         } else {
             let max_files = args.max_files.unwrap_or(20);
+            let max_depth = args.max_depth.unwrap_or(4);
+            let extensions = normalize_extensions(args.extensions.as_deref());
             let mut summaries = Vec::new();
             let mut total_symbols = 0usize;
+            let mut cache_hits = 0usize;
+            let mut progress = ProgressReporter::new("get_symbols_overview");
 
             for entry in WalkDir::new(&path)
-                .max_depth(4)
+                .max_depth(max_depth)
                 .into_iter()
+                .filter_entry(allow_entry)
                 .filter_map(|e| e.ok())
                 .filter(|e| e.file_type().is_file())
             {
                 if summaries.len() >= max_files {
                     break;
                 }
-                if let Some(parsed) = ParsedFile::from_path(entry.path())? {
+                if !has_allowed_extension(entry.path(), extensions.as_ref()) {
+                    continue;
+                }
+                cancellation::checkpoint()?;
+                progress.file_scanned(total_symbols);
+                if let Some((parsed, cached)) =
+                    ParsedFile::from_path_cached(entry.path(), args.no_cache)?
+                {
                     let count = parsed.symbols.len();
                     total_symbols += count;
-                    summaries.push(json!({
-                        "path": entry.path().strip_prefix(&path).unwrap_or(entry.path()).to_string_lossy(),
-                        "language": parsed.language.as_str(),
-                        "symbol_count": count,
-                        "top_symbols": parsed.symbols.iter().take(5).map(|symbol| json!({
+                    cache_hits += usize::from(cached);
+                    let summary = |symbol: &FileSymbol| {
+                        json!({
                             "name": symbol.name,
                             "kind": symbol.kind,
                             "line": symbol.line,
-                        })).collect::<Vec<_>>(),
+                        })
+                    };
+                    let top_symbols = if args.nested {
+                        let mut roots = nest_symbols(&parsed.lines, &parsed.symbols, summary);
+                        roots.truncate(5);
+                        roots
+                    } else {
+                        parsed.symbols.iter().take(5).map(summary).collect()
+                    };
+                    summaries.push(json!({
+                        "path": display_path_from(entry.path(), &path),
+                        "language": parsed.language.as_str(),
+                        "symbol_count": count,
+                        "top_symbols": top_symbols,
                     }));
                 }
             }
 
             Ok(json!({
-                "path": path.to_string_lossy(),
+                "path": display_path(&path),
                 "files_summarised": summaries.len(),
                 "total_symbols": total_symbols,
+                "cache_hits": cache_hits,
                 "files": summaries,
             }))
         }
@@ -1032,95 +2294,593 @@ fn get_symbols_overview_tool() -> Tool {
 
     Tool::new(
         "get_symbols_overview",
-        "Summarise the symbols declared in a file or directory",
+        "Summarise the symbols declared in a file or directory. Symbols of files whose content is unchanged since an earlier overview are served from an in-memory cache unless `no_cache` is set",
         schema,
         Box::new(handler),
     )
+    .resolves_paths()
+    .cacheable()
 }
 
-fn rename_symbol_tool() -> Tool {
+fn get_file_outline_tool() -> Tool {
     let schema = json!({
         "type": "object",
         "properties": {
-            "path": {"type": "string"},
-            "old_name": {"type": "string"},
-            "new_name": {"type": "string"},
-            "case_sensitive": {"type": "boolean", "default": true},
-            "occurrence": {"type": "integer", "minimum": 1, "description": "Only rename the nth occurrence (1-based)"}
+            "path": {
+                "type": "string",
+                "description": "Source file to outline",
+            }
         },
-        "required": ["path", "old_name", "new_name"],
+        "required": ["path"],
+        "examples": [{"path": "src/lib.rs"}],
+        "x-positional-order": ["path"],
         "additionalProperties": false
     });
 
     #[derive(Deserialize)]
     struct Params {
         path: String,
-        old_name: String,
-        new_name: String,
-        #[serde(default)]
-        case_sensitive: Option<bool>,
-        #[serde(default)]
-        occurrence: Option<usize>,
     }
 
     let handler = move |params| -> Result<Value> {
         let args: Params =
-            serde_json::from_value(params).context("Invalid arguments for rename_symbol")?;
+            serde_json::from_value(params).context("Invalid arguments for get_file_outline")?;
         let path = resolve_path(&args.path)?;
-        let mut content = fs::read_to_string(&path)
-            .with_context(|| format!("Failed to read {}", path.display()))?;
+        if !path.is_file() {
+            anyhow::bail!("{} is not a file", path.display());
+        }
+        let parsed = ParsedFile::from_path(&path)?
+            .with_context(|| format!("{} is not a supported source file", path.display()))?;
 
-        let case_sensitive = args.case_sensitive.unwrap_or(true);
-        let pattern = RegexBuilder::new(&format!("\\b{}\\b", regex::escape(&args.old_name)))
-            .case_insensitive(!case_sensitive)
-            .build()
-            .with_context(|| format!("Failed to compile rename pattern for '{}'", args.old_name))?;
+        let mut symbols = parsed.symbols.iter().collect::<Vec<_>>();
+        symbols.sort_by_key(|symbol| (symbol.line, symbol.column));
+        // Signatures keep their indentation so nesting stays visible.
+        let outline = symbols
+            .into_iter()
+            .map(|symbol| {
+                json!({
+                    "line": symbol.line,
+                    "kind": symbol.kind,
+                    "signature": symbol.signature,
+                })
+            })
+            .collect::<Vec<_>>();
 
-        let mut replacements = 0usize;
+        Ok(json!({
+            "path": display_path(&path),
+            "language": parsed.language.as_str(),
+            "count": outline.len(),
+            "outline": outline,
+        }))
+    };
 
-        if let Some(target) = args.occurrence {
-            let mut new_content = String::with_capacity(content.len());
-            let mut last = 0;
-            for (idx, mat) in pattern.find_iter(&content).enumerate() {
-                if idx + 1 == target {
-                    new_content.push_str(&content[last..mat.start()]);
-                    new_content.push_str(&args.new_name);
-                    last = mat.end();
-                    replacements = 1;
-                    break;
+    Tool::new(
+        "get_file_outline",
+        "List every symbol signature in a file in line order, without bodies",
+        schema,
+        Box::new(handler),
+    )
+    .resolves_paths()
+    .cacheable()
+}
+
+/// Symbol kinds `local_call_graph` treats as callers and callees.
+const CALLABLE_KINDS: &[&str] = &["function", "method"];
+
+/// An identifier followed by an opening parenthesis.
+static CALL_RE: Lazy<Regex> =
+    Lazy::new(|| Regex::new(r"\b(?P<name>[A-Za-z_][A-Za-z0-9_]*)\s*\(").unwrap());
+
+fn local_call_graph_tool() -> Tool {
+    let schema = json!({
+        "type": "object",
+        "properties": {
+            "path": {
+                "type": "string",
+                "description": "Source file to analyse",
+            }
+        },
+        "required": ["path"],
+        "examples": [{"path": "src/tools/symbols.rs"}],
+        "x-positional-order": ["path"],
+        "additionalProperties": false
+    });
+
+    #[derive(Deserialize)]
+    struct Params {
+        path: String,
+    }
+
+    let handler = move |params| -> Result<Value> {
+        let args: Params =
+            serde_json::from_value(params).context("Invalid arguments for local_call_graph")?;
+        let path = resolve_path(&args.path)?;
+        if !path.is_file() {
+            anyhow::bail!("{} is not a file", path.display());
+        }
+        let parsed = ParsedFile::from_path(&path)?
+            .with_context(|| format!("{} is not a supported source file", path.display()))?;
+
+        // Byte range of every callable symbol's body, in the comment- and
+        // string-free code so mentions there are not counted as calls.
+        let code = strip_comments(&parsed.content, parsed.language);
+        let callables = parsed
+            .symbols
+            .iter()
+            .filter(|symbol| CALLABLE_KINDS.contains(&symbol.kind.as_str()))
+            .filter_map(|symbol| match symbol.body {
+                BodyStyle::Braces { start, end, .. } | BodyStyle::Indented { start, end, .. } => {
+                    Some((symbol, start, end))
+                }
+                BodyStyle::None => None,
+            })
+            .collect::<Vec<_>>();
+        let declared = parsed
+            .symbols
+            .iter()
+            .filter(|symbol| CALLABLE_KINDS.contains(&symbol.kind.as_str()))
+            .map(|symbol| (symbol.name.as_str(), symbol.line))
+            .collect::<HashSet<_>>();
+        let names = declared
+            .iter()
+            .map(|(name, _)| *name)
+            .collect::<HashSet<_>>();
+
+        let mut edges = Vec::new();
+        for (caller, start, end) in &callables {
+            for caps in CALL_RE.captures_iter(&code[*start..*end]) {
+                let name = caps.name("name").unwrap();
+                let offset = start + name.start();
+                let line = parsed.lines.line_index(offset) + 1;
+                if !names.contains(name.as_str()) || declared.contains(&(name.as_str(), line)) {
+                    continue;
+                }
+                // Calls inside a nested function belong to that function.
+                let innermost = callables
+                    .iter()
+                    .filter(|(_, start, end)| (*start..*end).contains(&offset))
+                    .min_by_key(|(_, start, end)| end - start)
+                    .map(|(symbol, _, _)| *symbol);
+                if innermost.is_some_and(|symbol| !std::ptr::eq(symbol, *caller)) {
+                    continue;
                 }
+                edges.push(json!({
+                    "caller": caller.name,
+                    "callee": name.as_str(),
+                    "line": line,
+                }));
             }
+        }
+
+        let mut functions = callables
+            .iter()
+            .map(|(symbol, _, _)| symbol.name.as_str())
+            .collect::<Vec<_>>();
+        functions.sort_unstable();
+        functions.dedup();
+
+        Ok(json!({
+            "path": display_path(&path),
+            "language": parsed.language.as_str(),
+            "functions": functions,
+            "edge_count": edges.len(),
+            "edges": edges,
+        }))
+    };
+
+    Tool::new(
+        "local_call_graph",
+        "List which functions in a file call which other functions in the same file, as `{ caller, callee, line }` edges. Name-based and heuristic: calls are identifiers followed by `(` that match a function declared in the file, so cross-file, dynamic and same-named calls are not resolved",
+        schema,
+        Box::new(handler),
+    )
+    .resolves_paths()
+    .cacheable()
+}
 
-            if replacements > 0 {
-                new_content.push_str(&content[last..]);
-                content = new_content;
+fn check_syntax_tool() -> Tool {
+    let schema = json!({
+        "type": "object",
+        "properties": {
+            "path": {
+                "type": "string",
+                "description": "File to check",
             }
-        } else {
-            replacements = pattern.find_iter(&content).count();
-            if replacements > 0 {
-                content = pattern
-                    .replace_all(&content, args.new_name.as_str())
-                    .to_string();
+        },
+        "required": ["path"],
+        "examples": [{"path": "src/tools/symbols.rs"}],
+        "x-positional-order": ["path"],
+        "additionalProperties": false
+    });
+
+    #[derive(Deserialize)]
+    struct Params {
+        path: String,
+    }
+
+    let handler = move |params| -> Result<Value> {
+        let args: Params =
+            serde_json::from_value(params).context("Invalid arguments for check_syntax")?;
+        let path = resolve_path(&args.path)?;
+        if !path.is_file() {
+            anyhow::bail!("{} is not a file", path.display());
+        }
+        let language = Language::from_path(&path)
+            .filter(|language| *language != Language::Markup)
+            .with_context(|| format!("{} is not a supported source file", path.display()))?;
+        let content = fs::read_to_string(&path)
+            .with_context(|| format!("Failed to read {}", path.display()))?;
+
+        let issues = check_syntax(&content, &path, language)
+            .into_iter()
+            .map(|issue| json!({"line": issue.line, "message": issue.message}))
+            .collect::<Vec<_>>();
+
+        Ok(json!({
+            "path": display_path(&path),
+            "language": language.as_str(),
+            "balanced": issues.is_empty(),
+            "issue_count": issues.len(),
+            "issues": issues,
+        }))
+    };
+
+    Tool::new(
+        "check_syntax",
+        "Cheap structural sanity check for a source file, useful before and after edits: brackets must balance and strings and block comments must be terminated, and Python files must indent consistently. Not a full parser; returns `{ balanced, issues: [{ line, message }] }`",
+        schema,
+        Box::new(handler),
+    )
+    .resolves_paths()
+}
+
+/// Arrange `symbols` into a tree: a symbol becomes a child of the nearest
+/// earlier symbol whose body range encloses it, and symbols with no enclosing
+/// body stay at the root. Each node is rendered by `render` and gains a
+/// `children` array.
+fn nest_symbols(
+    lines: &FileLines,
+    symbols: &[FileSymbol],
+    render: impl Fn(&FileSymbol) -> Value,
+) -> Vec<Value> {
+    fn build(
+        index: usize,
+        symbols: &[FileSymbol],
+        children: &[Vec<usize>],
+        render: &impl Fn(&FileSymbol) -> Value,
+    ) -> Value {
+        let mut node = render(&symbols[index]);
+        node["children"] = children[index]
+            .iter()
+            .map(|&child| build(child, symbols, children, render))
+            .collect();
+        node
+    }
+
+    let parents = enclosing_indices(lines, symbols);
+    let mut order = (0..symbols.len()).collect::<Vec<_>>();
+    order.sort_by_key(|&index| (symbols[index].line, symbols[index].column));
+
+    let mut children = vec![Vec::new(); symbols.len()];
+    let mut roots = Vec::new();
+    for index in order {
+        match parents[index] {
+            Some(parent) => children[parent].push(index),
+            None => roots.push(index),
+        }
+    }
+
+    roots
+        .into_iter()
+        .map(|index| build(index, symbols, &children, &render))
+        .collect()
+}
+
+fn rename_symbol_tool() -> Tool {
+    let schema = json!({
+        "type": "object",
+        "properties": {
+            "path": {"type": "string"},
+            "old_name": {"type": "string", "description": "Identifier or qualified path such as `old::Name` or `pkg.mod`"},
+            "new_name": {"type": "string"},
+            "case_sensitive": {"type": "boolean", "default": true},
+            "occurrence": {"type": "integer", "minimum": 1, "description": "Only rename the nth occurrence (1-based)"},
+            "boundaries": {
+                "type": "string",
+                "enum": ["word", "path", "none"],
+                "default": "word",
+                "description": "`word`: no identifier character may touch the match, so `old::Name` also matches inside `crate::old::Name`; `path`: additionally reject matches preceded by `::` or `.`, so only paths starting at `old` are renamed; `none`: plain substring replacement",
+            },
+            "identifier_chars": {
+                "type": "string",
+                "description": "Extra characters that count as part of an identifier for boundary checks (e.g. `-` for CSS or `$` for PHP)",
             }
+        },
+        "required": ["path", "old_name", "new_name"],
+        "examples": [
+            {"path": "src/lib.rs", "old_name": "parse", "new_name": "parse_source"},
+            {"path": "src/lib.rs", "old_name": "old::Name", "new_name": "new::Name", "boundaries": "path"}
+        ],
+        "x-positional-order": [
+            "path", "old_name", "new_name", "case_sensitive", "occurrence", "boundaries",
+            "identifier_chars"
+        ],
+        "additionalProperties": false
+    });
+
+    #[derive(Deserialize)]
+    struct Params {
+        path: String,
+        old_name: String,
+        new_name: String,
+        #[serde(default)]
+        case_sensitive: Option<bool>,
+        #[serde(default)]
+        occurrence: Option<usize>,
+        #[serde(default)]
+        boundaries: RenameBoundaries,
+        #[serde(default)]
+        identifier_chars: String,
+    }
+
+    let handler = move |params| -> Result<Value> {
+        let args: Params =
+            serde_json::from_value(params).context("Invalid arguments for rename_symbol")?;
+        if args.old_name.is_empty() {
+            anyhow::bail!("old_name cannot be empty");
+        }
+        let path = resolve_path(&args.path)?;
+        let content = fs::read_to_string(&path)
+            .with_context(|| format!("Failed to read {}", path.display()))?;
+
+        let case_sensitive = args.case_sensitive.unwrap_or(true);
+        // Boundaries are checked by hand: `\b` misbehaves when the name starts
+        // or ends with `:` or `.`, and cannot express the path rule.
+        let pattern = RegexBuilder::new(&regex::escape(&args.old_name))
+            .case_insensitive(!case_sensitive)
+            .build()
+            .with_context(|| format!("Failed to compile rename pattern for '{}'", args.old_name))?;
+
+        let mut spans = pattern
+            .find_iter(&content)
+            .filter(|mat| {
+                args.boundaries
+                    .accepts(&content, mat.start(), mat.end(), &args.identifier_chars)
+            })
+            .map(|mat| mat.range())
+            .collect::<Vec<_>>();
+        if let Some(target) = args.occurrence {
+            spans = spans.into_iter().skip(target - 1).take(1).collect();
         }
 
+        let replacements = spans.len();
         if replacements > 0 {
-            fs::write(&path, &content)
+            let mut updated = String::with_capacity(content.len());
+            let mut last = 0;
+            for span in spans {
+                updated.push_str(&content[last..span.start]);
+                updated.push_str(&args.new_name);
+                last = span.end;
+            }
+            updated.push_str(&content[last..]);
+            fs::write(&path, &updated)
                 .with_context(|| format!("Failed to write {}", path.display()))?;
+            journal::record_edit(
+                "rename_symbol",
+                &path,
+                vec![args.new_name.clone()],
+                Some(format!(
+                    "renamed {} to {} ({replacements} replacements)",
+                    args.old_name, args.new_name
+                )),
+            );
         }
 
         Ok(json!({
-            "path": path.to_string_lossy(),
+            "path": display_path(&path),
             "replacements": replacements,
         }))
     };
 
     Tool::new(
         "rename_symbol",
-        "Rename symbol occurrences within a single file using word-boundary matching",
+        "Rename identifier or qualified-path occurrences within a single file",
+        schema,
+        Box::new(handler),
+    )
+    .mutating()
+    .resolves_paths()
+}
+
+fn rename_local_tool() -> Tool {
+    let schema = json!({
+        "type": "object",
+        "properties": {
+            "path": {"type": "string"},
+            "symbol": {"type": "string", "description": "Function or method the local is declared in"},
+            "old_name": {"type": "string", "minLength": 1, "description": "Local variable or parameter to rename"},
+            "new_name": {"type": "string", "minLength": 1},
+            "occurrence": {"type": "integer", "minimum": 1, "description": "Which symbol named `symbol` to use when several match (1-based)"},
+            "case_sensitive": {"type": "boolean", "default": true, "description": "Whether the symbol name must match case; `old_name` always does"},
+            "container": {"type": "string", "description": "Only consider symbols declared inside this struct, impl, class or module"},
+            "include_signature": {
+                "type": "boolean",
+                "default": true,
+                "description": "Also rename in the declaration, so parameters are renamed together with their uses",
+            },
+            "return_diff": {"type": "boolean", "default": false, "description": "Include a unified diff of the change as `diff`"},
+            "dry_run": {"type": "boolean", "default": false, "description": "Count the replacements without writing the file"}
+        },
+        "required": ["path", "symbol", "old_name", "new_name"],
+        "examples": [
+            {"path": "src/lib.rs", "symbol": "parse", "old_name": "buf", "new_name": "buffer"},
+            {"path": "app/models.py", "symbol": "save", "container": "User", "old_name": "x", "new_name": "record", "dry_run": true, "return_diff": true}
+        ],
+        "x-positional-order": [
+            "path", "symbol", "old_name", "new_name", "occurrence", "case_sensitive", "container",
+            "include_signature", "return_diff", "dry_run"
+        ],
+        "additionalProperties": false
+    });
+
+    #[derive(Deserialize)]
+    struct Params {
+        path: String,
+        symbol: String,
+        old_name: String,
+        new_name: String,
+        #[serde(default)]
+        occurrence: Option<usize>,
+        #[serde(default)]
+        case_sensitive: Option<bool>,
+        #[serde(default)]
+        container: Option<String>,
+        #[serde(default = "default_true")]
+        include_signature: bool,
+        #[serde(default)]
+        return_diff: bool,
+        #[serde(default)]
+        dry_run: bool,
+    }
+
+    let handler = move |params| -> Result<Value> {
+        let args: Params =
+            serde_json::from_value(params).context("Invalid arguments for rename_local")?;
+        let is_identifier = |name: &str| name.chars().all(|c| c.is_alphanumeric() || c == '_');
+        if !is_identifier(&args.old_name) || !is_identifier(&args.new_name) {
+            anyhow::bail!("old_name and new_name must be plain identifiers");
+        }
+        let path = resolve_path(&args.path)?;
+        let parsed = ParsedFile::from_path(&path)?
+            .with_context(|| format!("{} is not a supported source file", path.display()))?;
+        let (target_index, target) = select_symbol(
+            &parsed.symbols,
+            &args.symbol,
+            args.container.as_deref(),
+            args.occurrence,
+            args.case_sensitive.unwrap_or(true),
+            &path,
+        )?;
+        let (body_start, end) = match target.body {
+            BodyStyle::Braces { start, end, .. } | BodyStyle::Indented { start, end, .. } => {
+                (start, end)
+            }
+            BodyStyle::None => anyhow::bail!(
+                "Symbol '{}' does not have a body to rename locals in",
+                target.name
+            ),
+        };
+        let start = if args.include_signature {
+            parsed.lines.bounds(target.line - 1).0.min(body_start)
+        } else {
+            body_start
+        };
+
+        let scope = &parsed.content[start..end];
+        let occurrences = |name: &str| {
+            scope
+                .match_indices(name)
+                .filter(|(index, _)| {
+                    RenameBoundaries::Word.accepts(scope, *index, index + name.len(), "")
+                })
+                .map(|(index, _)| index)
+                .collect::<Vec<_>>()
+        };
+        let spans = occurrences(&args.old_name);
+        let new_name_in_use = !occurrences(&args.new_name).is_empty();
+
+        let mut lines = spans
+            .iter()
+            .map(|index| parsed.lines.line_index(start + index) + 1)
+            .collect::<Vec<_>>();
+        lines.dedup();
+
+        let mut content = parsed.content.clone();
+        for index in spans.iter().rev() {
+            let at = start + index;
+            content.replace_range(at..at + args.old_name.len(), &args.new_name);
+        }
+        if !spans.is_empty() && !args.dry_run {
+            fs::write(&path, &content)
+                .with_context(|| format!("Failed to write {}", path.display()))?;
+            journal::record_edit(
+                "rename_local",
+                &path,
+                vec![target.name.clone()],
+                Some(format!(
+                    "renamed local {} to {} ({} replacements)",
+                    args.old_name,
+                    args.new_name,
+                    spans.len()
+                )),
+            );
+        }
+
+        let mut response = json!({
+            "path": display_path(&path),
+            "symbol": target.name,
+            "container": target.enclosing,
+            "occurrence": target_index + 1,
+            "replacements": spans.len(),
+            "lines": lines,
+            "new_name_in_use": new_name_in_use,
+            "dry_run": args.dry_run,
+        });
+        if args.return_diff {
+            response["diff"] = json!(unified_diff(
+                &parsed.content,
+                &content,
+                &display_path(&path),
+                3
+            ));
+        }
+        Ok(response)
+    };
+
+    Tool::new(
+        "rename_local",
+        "Rename a local variable or parameter by whole word within one function, leaving same-named identifiers elsewhere in the file untouched. `new_name_in_use` warns when the new name already occurs in that scope",
         schema,
         Box::new(handler),
     )
+    .mutating()
+    .resolves_paths()
+}
+
+/// Which neighbouring characters stop a `rename_symbol` match.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+enum RenameBoundaries {
+    #[default]
+    Word,
+    Path,
+    None,
+}
+
+impl RenameBoundaries {
+    /// Whether `content[start..end]` stands alone. Each side is only checked
+    /// when the match itself begins or ends with an identifier character, so
+    /// `::Name` or `pkg.` behave sensibly.
+    fn accepts(self, content: &str, start: usize, end: usize, extra: &str) -> bool {
+        if self == Self::None {
+            return true;
+        }
+        let is_ident = |c: char| c.is_alphanumeric() || c == '_' || extra.contains(c);
+        let matched = &content[start..end];
+        let before = &content[..start];
+        let after = &content[end..];
+
+        if matched.starts_with(is_ident) && before.ends_with(is_ident) {
+            return false;
+        }
+        if matched.ends_with(is_ident) && after.starts_with(is_ident) {
+            return false;
+        }
+        if self == Self::Path && (before.ends_with("::") || before.ends_with('.')) {
+            return false;
+        }
+        true
+    }
 }
 
 fn replace_symbol_body_tool() -> Tool {
@@ -1129,41 +2889,132 @@ fn replace_symbol_body_tool() -> Tool {
         "properties": {
             "path": {"type": "string"},
             "symbol": {"type": "string", "description": "Symbol name to update"},
+            "name_regex": {
+                "type": "string",
+                "description": "Instead of `symbol`, edit every symbol whose name this regex matches (anchor with ^ and $ for whole names). `new_body` may then use the match's groups as `$1` or `${name}`; write `$$` for a literal `$`. Preview with `dry_run`",
+            },
             "new_body": {"type": "string", "description": "Replacement body content"},
             "occurrence": {"type": "integer", "minimum": 1},
             "case_sensitive": {"type": "boolean", "default": true},
+            "container": {"type": "string", "description": "Only consider symbols declared inside this struct, impl, class or module"},
             "start_line": {"type": "integer", "minimum": 1, "description": "Optional starting line override"},
-            "end_line": {"type": "integer", "minimum": 1, "description": "Optional ending line override"}
+            "end_line": {"type": "integer", "minimum": 1, "description": "Optional ending line override"},
+            "force": {"type": "boolean", "default": false, "description": "Write brace-delimited bodies even when their brackets do not balance"},
+            "normalize": {"type": "boolean", "default": false, "description": "Strip trailing whitespace and carriage returns from `new_body` before inserting it"},
+            "mode": {
+                "type": "string",
+                "enum": ["replace", "prepend", "append"],
+                "default": "replace",
+                "description": "Replace the body, or insert `new_body` before/after the existing statements",
+            },
+            "return_diff": {"type": "boolean", "default": false, "description": "Include a unified diff of the change as `diff`"},
+            "dry_run": {"type": "boolean", "default": false, "description": "Compute the edit without writing the file; combine with `return_diff` to preview it"},
+            "format": {"type": "boolean", "default": false, "description": "Run the edited file through rustfmt (Rust), prettier (JS/TS) or black (Python) when installed, reporting the outcome in `format`; the unformatted edit is kept when the formatter is missing or fails"},
+            "line_ending": {
+                "type": "string",
+                "enum": ["lf", "crlf"],
+                "description": "Line ending for the inserted body. Defaults to the one most lines of the file already use, so editing a CRLF file does not mix in LF lines",
+            }
         },
-        "required": ["path", "symbol", "new_body"],
+        "required": ["path", "new_body"],
+        "examples": [
+            {"path": "src/lib.rs", "symbol": "parse", "new_body": "fn parse() {}", "mode": "replace"},
+            {"path": "src/lib.rs", "symbol": "parse", "new_body": "todo!()", "return_diff": true, "dry_run": true},
+            {"path": "src/lib.rs", "symbol": "parse", "new_body": "let x=1;x", "format": true},
+            {"path": "src/model.rs", "name_regex": "^get_(?P<field>\\w+)$", "new_body": "self.${field}.clone()", "dry_run": true}
+        ],
+        "x-positional-order": [
+            "path", "symbol", "name_regex", "new_body", "occurrence", "case_sensitive",
+            "container", "start_line", "end_line", "force", "normalize", "mode", "return_diff",
+            "dry_run", "format", "line_ending"
+        ],
         "additionalProperties": false
     });
 
     #[derive(Deserialize)]
     struct Params {
         path: String,
-        symbol: String,
+        #[serde(default)]
+        symbol: Option<String>,
+        #[serde(default)]
+        name_regex: Option<String>,
         new_body: String,
         #[serde(default)]
         occurrence: Option<usize>,
         #[serde(default)]
         case_sensitive: Option<bool>,
         #[serde(default)]
+        container: Option<String>,
+        #[serde(default)]
         start_line: Option<usize>,
         #[serde(default)]
         end_line: Option<usize>,
+        #[serde(default)]
+        force: bool,
+        #[serde(default)]
+        normalize: bool,
+        #[serde(default)]
+        mode: BodyEditMode,
+        #[serde(default)]
+        return_diff: bool,
+        #[serde(default)]
+        dry_run: bool,
+        #[serde(default)]
+        format: bool,
+        #[serde(default)]
+        line_ending: Option<LineEnding>,
     }
 
     let handler = move |params| -> Result<Value> {
-        let args: Params =
+        let mut args: Params =
             serde_json::from_value(params).context("Invalid arguments for replace_symbol_body")?;
+        if args.normalize {
+            args.new_body = normalize_whitespace(&args.new_body, LineEnding::Lf);
+        }
         let path = resolve_path(&args.path)?;
         let mut parsed = ParsedFile::from_path(&path)?
             .with_context(|| format!("{} is not a supported source file", path.display()))?;
+        let original = args.return_diff.then(|| parsed.content.clone());
+        let language = parsed.language;
+        let line_ending = args
+            .line_ending
+            .unwrap_or_else(|| LineEnding::detect(&parsed.content));
+        // Format the edited content when asked, write and journal it unless
+        // this is a dry run, and add the dry-run flag, format report and
+        // requested diff to `response`.
+        let finish = |content: &str,
+                      symbols: Vec<String>,
+                      detail: Option<String>,
+                      mut response: Value|
+         -> Result<Value> {
+            let formatted = args
+                .format
+                .then(|| format::format_source(language, &path, content));
+            let content = match &formatted {
+                Some((Some(formatted), _)) => formatted.as_str(),
+                _ => content,
+            };
+            if let Some((_, report)) = &formatted {
+                response["format"] = json!(report);
+            }
+            if !args.dry_run {
+                fs::write(&path, content)
+                    .with_context(|| format!("Failed to write {}", path.display()))?;
+                journal::record_edit("replace_symbol_body", &path, symbols, detail);
+            }
+            response["dry_run"] = json!(args.dry_run);
+            if let Some(original) = &original {
+                response["diff"] = json!(unified_diff(original, content, &display_path(&path), 3));
+            }
+            Ok(response)
+        };
 
         let case_sensitive = args.case_sensitive.unwrap_or(true);
 
         if let (Some(start_line), Some(end_line)) = (args.start_line, args.end_line) {
+            if args.mode != BodyEditMode::Replace {
+                anyhow::bail!("`mode` prepend/append requires a symbol body, not a line range");
+            }
             if start_line > end_line {
                 anyhow::bail!("start_line must be <= end_line");
             }
@@ -1180,102 +3031,965 @@ fn replace_symbol_body_tool() -> Tool {
             let (start_offset, _) = parsed.lines.bounds(start_index);
             let (_, end_offset) = parsed.lines.bounds(end_index);
 
-            let replacement = ensure_trailing_newline(&args.new_body);
+            let replacement = line_ending.apply(&ensure_trailing_newline(&args.new_body));
             parsed
                 .content
                 .replace_range(start_offset..end_offset, &replacement);
 
-            fs::write(&path, &parsed.content)
-                .with_context(|| format!("Failed to write {}", path.display()))?;
-
-            return Ok(json!({
-                "path": path.to_string_lossy(),
-                "mode": "line_range",
-                "start_line": start_line,
-                "end_line": end_line,
-            }));
+            return finish(
+                &parsed.content,
+                Vec::new(),
+                Some(format!("lines {start_line}-{end_line}")),
+                json!({
+                    "path": display_path(&path),
+                    "mode": "line_range",
+                    "start_line": start_line,
+                    "end_line": end_line,
+                }),
+            );
         }
 
-        let mut candidates: Vec<&FileSymbol> = parsed
-            .symbols
-            .iter()
-            .filter(|symbol| symbol_name_matches(&symbol.name, &args.symbol, false, case_sensitive))
-            .collect();
+        // A project `.editorconfig` overrides the indentation found in the file.
+        let configured_unit = editorconfig::indent_unit(&path);
 
-        if candidates.is_empty() {
-            anyhow::bail!(
-                "No symbol named '{}' found in {}",
-                args.symbol,
-                path.display()
-            );
-        }
+        if let Some(pattern) = &args.name_regex {
+            if args.symbol.is_some() || args.occurrence.is_some() {
+                anyhow::bail!("`name_regex` cannot be combined with `symbol` or `occurrence`");
+            }
+            let regex = RegexBuilder::new(pattern)
+                .case_insensitive(!case_sensitive)
+                .build()
+                .with_context(|| format!("Invalid name_regex '{pattern}'"))?;
 
-        candidates.sort_by_key(|symbol| symbol.line);
-        let target_index = match args.occurrence {
-            Some(idx) => {
-                if idx == 0 || idx > candidates.len() {
-                    anyhow::bail!(
-                        "Occurrence {idx} is out of bounds (only {} matches)",
-                        candidates.len()
-                    );
-                }
-                idx - 1
+            let mut candidates = parsed
+                .symbols
+                .iter()
+                .filter(|symbol| regex.is_match(&symbol.name))
+                .filter(|symbol| {
+                    args.container.as_deref().is_none_or(|container| {
+                        container_matches(symbol.enclosing.as_deref(), container, case_sensitive)
+                    })
+                })
+                .collect::<Vec<_>>();
+            if candidates.is_empty() {
+                anyhow::bail!("No symbol matching '{pattern}' found in {}", path.display());
             }
-            None => {
-                if candidates.len() > 1 {
-                    anyhow::bail!(
-                        "Multiple symbols named '{}' found; specify `occurrence` to disambiguate",
-                        args.symbol
-                    );
+            candidates.sort_by_key(|symbol| symbol.line);
+
+            // Edits must not overlap, so a match inside the body of an
+            // earlier match is left to that edit.
+            let mut targets: Vec<(&FileSymbol, usize, usize)> = Vec::new();
+            let mut skipped = Vec::new();
+            for symbol in candidates {
+                let (start, end) = match symbol.body {
+                    BodyStyle::Braces { start, end, .. }
+                    | BodyStyle::Indented { start, end, .. } => (start, end),
+                    BodyStyle::None => {
+                        skipped.push(json!({
+                            "symbol": symbol.name,
+                            "line": symbol.line,
+                            "reason": "no replaceable body",
+                        }));
+                        continue;
+                    }
+                };
+                if let Some((outer, _, _)) = targets
+                    .iter()
+                    .find(|(_, outer_start, outer_end)| start >= *outer_start && end <= *outer_end)
+                {
+                    skipped.push(json!({
+                        "symbol": symbol.name,
+                        "line": symbol.line,
+                        "reason": format!("inside the body of '{}', which is also edited", outer.name),
+                    }));
+                    continue;
                 }
-                0
+                targets.push((symbol, start, end));
             }
-        };
 
-        let target = candidates[target_index];
-        let replacement = ensure_trailing_newline(&args.new_body);
-
-        match &target.body {
-            BodyStyle::Braces {
-                start,
-                end,
-                base_indent,
-                inner_indent,
-            } => {
-                let formatted = format_brace_body(&replacement, base_indent, inner_indent);
-                parsed.content.replace_range(*start..*end, &formatted);
+            let edited = targets
+                .iter()
+                .map(|(symbol, _, _)| {
+                    json!({
+                        "symbol": symbol.name,
+                        "kind": symbol.kind,
+                        "line": symbol.line,
+                        "container": symbol.enclosing,
+                    })
+                })
+                .collect::<Vec<_>>();
+            let mut content = parsed.content.clone();
+            for (symbol, _, _) in targets.iter().rev() {
+                let mut new_body = String::new();
+                if let Some(caps) = regex.captures(&symbol.name) {
+                    caps.expand(&args.new_body, &mut new_body);
+                }
+                edit_symbol_body(
+                    &mut content,
+                    symbol,
+                    &new_body,
+                    args.mode,
+                    args.force,
+                    configured_unit.as_deref(),
+                    line_ending,
+                )?;
             }
-            BodyStyle::Indented {
-                start,
-                end,
-                base_indent,
-                indent_unit,
-            } => {
-                let formatted = format_indented_body(&replacement, base_indent, indent_unit);
-                parsed.content.replace_range(*start..*end, &formatted);
+
+            return finish(
+                &content,
+                targets
+                    .iter()
+                    .map(|(symbol, _, _)| symbol.name.clone())
+                    .collect(),
+                Some(format!("name_regex {pattern}")),
+                json!({
+                    "path": display_path(&path),
+                    "name_regex": pattern,
+                    "edited_count": edited.len(),
+                    "edited": edited,
+                    "skipped": skipped,
+                }),
+            );
+        }
+
+        let symbol = args
+            .symbol
+            .as_deref()
+            .context("Provide `symbol`, `name_regex`, or `start_line` and `end_line`")?;
+        let (target_index, target) = select_symbol(
+            &parsed.symbols,
+            symbol,
+            args.container.as_deref(),
+            args.occurrence,
+            case_sensitive,
+            &path,
+        )?;
+        let mut content = parsed.content.clone();
+        edit_symbol_body(
+            &mut content,
+            target,
+            &args.new_body,
+            args.mode,
+            args.force,
+            configured_unit.as_deref(),
+            line_ending,
+        )?;
+
+        finish(
+            &content,
+            vec![target.name.clone()],
+            None,
+            json!({
+                "path": display_path(&path),
+                "symbol": target.name,
+                "container": target.enclosing,
+                "occurrence": target_index + 1,
+            }),
+        )
+    };
+
+    Tool::new(
+        "replace_symbol_body",
+        "Replace the implementation of a symbol, preserving surrounding formatting. Indentation follows the project's `.editorconfig` (`indent_style`, `indent_size`) when it sets one, otherwise the existing body",
+        schema,
+        Box::new(handler),
+    )
+    .mutating()
+    .resolves_paths()
+}
+
+fn replace_in_symbol_tool() -> Tool {
+    let schema = json!({
+        "type": "object",
+        "properties": {
+            "path": {"type": "string"},
+            "symbol": {"type": "string", "description": "Symbol whose body the substitution is limited to"},
+            "pattern": {
+                "type": "string",
+                "minLength": 1,
+                "description": "Regular expression to replace within the body; use `\\b` for whole words and `(?i)` to ignore case",
+            },
+            "replacement": {
+                "type": "string",
+                "description": "Text each match becomes. `$1` or `${name}` insert capture groups and `$$` is a literal `$`",
+            },
+            "occurrence": {"type": "integer", "minimum": 1},
+            "case_sensitive": {"type": "boolean", "default": true, "description": "Whether the symbol name must match case"},
+            "container": {"type": "string", "description": "Only consider symbols declared inside this struct, impl, class or module"},
+            "return_diff": {"type": "boolean", "default": false, "description": "Include a unified diff of the change as `diff`"},
+            "dry_run": {"type": "boolean", "default": false, "description": "Count the replacements without writing the file"}
+        },
+        "required": ["path", "symbol", "pattern", "replacement"],
+        "examples": [
+            {"path": "src/lib.rs", "symbol": "parse", "pattern": "\\bbuf\\b", "replacement": "buffer"},
+            {"path": "src/lib.rs", "symbol": "render", "container": "Widget", "pattern": "self\\.(\\w+)\\.clone\\(\\)", "replacement": "&self.$1", "dry_run": true, "return_diff": true}
+        ],
+        "x-positional-order": [
+            "path", "symbol", "pattern", "replacement", "occurrence", "case_sensitive",
+            "container", "return_diff", "dry_run"
+        ],
+        "additionalProperties": false
+    });
+
+    #[derive(Deserialize)]
+    struct Params {
+        path: String,
+        symbol: String,
+        pattern: String,
+        replacement: String,
+        #[serde(default)]
+        occurrence: Option<usize>,
+        #[serde(default)]
+        case_sensitive: Option<bool>,
+        #[serde(default)]
+        container: Option<String>,
+        #[serde(default)]
+        return_diff: bool,
+        #[serde(default)]
+        dry_run: bool,
+    }
+
+    let handler = move |params| -> Result<Value> {
+        let args: Params =
+            serde_json::from_value(params).context("Invalid arguments for replace_in_symbol")?;
+        let regex = Regex::new(&args.pattern)
+            .with_context(|| format!("Invalid pattern '{}'", args.pattern))?;
+        reject_empty_match(&regex, &args.pattern)?;
+        let path = resolve_path(&args.path)?;
+        let parsed = ParsedFile::from_path(&path)?
+            .with_context(|| format!("{} is not a supported source file", path.display()))?;
+        let (target_index, target) = select_symbol(
+            &parsed.symbols,
+            &args.symbol,
+            args.container.as_deref(),
+            args.occurrence,
+            args.case_sensitive.unwrap_or(true),
+            &path,
+        )?;
+        let (start, end) = match target.body {
+            BodyStyle::Braces { start, end, .. } | BodyStyle::Indented { start, end, .. } => {
+                (start, end)
             }
             BodyStyle::None => anyhow::bail!(
-                "Symbol '{}' does not have a replaceable body (maybe a declaration without implementation)",
+                "Symbol '{}' does not have a body to replace in (maybe a declaration without implementation)",
                 target.name
             ),
+        };
+
+        let body = &parsed.content[start..end];
+        let mut lines = regex
+            .find_iter(body)
+            .map(|found| parsed.lines.line_index(start + found.start()) + 1)
+            .collect::<Vec<_>>();
+        let replacements = lines.len();
+        lines.dedup();
+
+        let mut content = parsed.content.clone();
+        if replacements > 0 {
+            let replaced = regex.replace_all(body, args.replacement.as_str());
+            content.replace_range(start..end, &replaced);
+            if !args.dry_run {
+                fs::write(&path, &content)
+                    .with_context(|| format!("Failed to write {}", path.display()))?;
+                journal::record_edit(
+                    "replace_in_symbol",
+                    &path,
+                    vec![target.name.clone()],
+                    Some(format!("pattern {}", args.pattern)),
+                );
+            }
+        }
+
+        let mut response = json!({
+            "path": display_path(&path),
+            "symbol": target.name,
+            "container": target.enclosing,
+            "occurrence": target_index + 1,
+            "body_start_line": parsed.lines.line_index(start) + 1,
+            "body_end_line": parsed.lines.line_index(end.saturating_sub(1).max(start)) + 1,
+            "replacements": replacements,
+            "lines": lines,
+            "dry_run": args.dry_run,
+        });
+        if args.return_diff {
+            response["diff"] = json!(unified_diff(
+                &parsed.content,
+                &content,
+                &display_path(&path),
+                3
+            ));
+        }
+        Ok(response)
+    };
+
+    Tool::new(
+        "replace_in_symbol",
+        "Apply a regex substitution only within one symbol's body, e.g. to rename a local variable, leaving the rest of the file untouched",
+        schema,
+        Box::new(handler),
+    )
+    .mutating()
+    .resolves_paths()
+}
+
+/// Rewrite the body of `target` within `content`, which must still have the
+/// offsets `target` was parsed with. `indent_unit` overrides the indentation
+/// derived from the existing body, and the new body's lines end with
+/// `line_ending`.
+fn edit_symbol_body(
+    content: &mut String,
+    target: &FileSymbol,
+    new_body: &str,
+    mode: BodyEditMode,
+    force: bool,
+    indent_unit: Option<&str>,
+    line_ending: LineEnding,
+) -> Result<()> {
+    let replacement = match mode {
+        BodyEditMode::Replace => ensure_trailing_newline(new_body),
+        mode => {
+            let existing = extract_body(content, &target.body).unwrap_or_default();
+            let existing = reindent(existing.trim_end(), "");
+            let addition = reindent(new_body.trim_matches('\n'), "");
+            let combined = if mode == BodyEditMode::Prepend {
+                [addition, existing]
+            } else {
+                [existing, addition]
+            };
+            let combined: Vec<String> = combined
+                .into_iter()
+                .filter(|part| !part.trim().is_empty())
+                .collect();
+            ensure_trailing_newline(&combined.join("\n"))
+        }
+    };
+
+    match &target.body {
+        BodyStyle::Braces {
+            start,
+            end,
+            base_indent,
+            inner_indent,
+        } => {
+            let inner_indent = indent_unit
+                .map(|unit| format!("{base_indent}{unit}"))
+                .unwrap_or_else(|| inner_indent.clone());
+            if !force && let Some(problems) = delimiter_imbalance(&replacement) {
+                anyhow::bail!(
+                    "new_body for '{}' has unbalanced delimiters: {problems}; pass `force: true` to write it anyway",
+                    target.name
+                );
+            }
+            let formatted = format_brace_body(&replacement, base_indent, &inner_indent);
+            content.replace_range(*start..*end, &line_ending.apply(&formatted));
+        }
+        BodyStyle::Indented {
+            start,
+            end,
+            base_indent,
+            indent_unit: derived_unit,
+        } => {
+            let indent_unit = indent_unit.unwrap_or(derived_unit);
+            let formatted = format_indented_body(&replacement, base_indent, indent_unit);
+            content.replace_range(*start..*end, &line_ending.apply(&formatted));
+        }
+        BodyStyle::None => anyhow::bail!(
+            "Symbol '{}' does not have a replaceable body (maybe a declaration without implementation)",
+            target.name
+        ),
+    }
+    Ok(())
+}
+
+/// How `replace_symbol_body` combines `new_body` with the current body.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+enum BodyEditMode {
+    #[default]
+    Replace,
+    Prepend,
+    Append,
+}
+
+fn edit_symbol_signature_tool() -> Tool {
+    let schema = json!({
+        "type": "object",
+        "properties": {
+            "path": {"type": "string"},
+            "symbol": {"type": "string", "description": "Symbol whose signature line is replaced"},
+            "new_signature": {
+                "type": "string",
+                "description": "Replacement declaration line; indentation and a trailing body opener (`{` or `:`) are preserved when omitted"
+            },
+            "occurrence": {"type": "integer", "minimum": 1},
+            "case_sensitive": {"type": "boolean", "default": true}
+        },
+        "required": ["path", "symbol", "new_signature"],
+        "examples": [{"path": "src/lib.rs", "symbol": "parse", "new_signature": "pub fn parse(input: &str) -> Result<Ast>"}],
+        "x-positional-order": ["path", "symbol", "new_signature", "occurrence", "case_sensitive"],
+        "additionalProperties": false
+    });
+
+    #[derive(Deserialize)]
+    struct Params {
+        path: String,
+        symbol: String,
+        new_signature: String,
+        #[serde(default)]
+        occurrence: Option<usize>,
+        #[serde(default)]
+        case_sensitive: Option<bool>,
+    }
+
+    let handler = move |params| -> Result<Value> {
+        let args: Params = serde_json::from_value(params)
+            .context("Invalid arguments for edit_symbol_signature")?;
+        let path = resolve_path(&args.path)?;
+        let mut parsed = ParsedFile::from_path(&path)?
+            .with_context(|| format!("{} is not a supported source file", path.display()))?;
+
+        let new_signature = args.new_signature.trim_end_matches(['\r', '\n']).trim();
+        if new_signature.is_empty() {
+            anyhow::bail!("new_signature must not be empty");
+        }
+        if new_signature.contains('\n') {
+            anyhow::bail!("new_signature must be a single line");
+        }
+
+        let (target_index, target) = select_symbol(
+            &parsed.symbols,
+            &args.symbol,
+            None,
+            args.occurrence,
+            args.case_sensitive.unwrap_or(true),
+            &path,
+        )?;
+
+        let line_idx = target.line - 1;
+        let old_line = parsed.lines.text(line_idx).to_string();
+        let (line_start, _) = parsed.lines.bounds(line_idx);
+        let line_end = line_start + old_line.len();
+
+        let keyword = declaration_keyword(&old_line).with_context(|| {
+            format!(
+                "No declaration keyword found on line {} for '{}'; edit it with replace_span instead",
+                target.line, target.name
+            )
+        })?;
+        if declaration_keyword(new_signature) != Some(keyword) {
+            anyhow::bail!(
+                "new_signature must keep the `{keyword}` declaration keyword of '{}'",
+                target.name
+            );
         }
 
+        // Keep whatever opens the body on the signature line so the body
+        // itself is left untouched.
+        let opener = match &target.body {
+            BodyStyle::Braces { start, .. } if (line_start..line_end).contains(&(start - 1)) => {
+                let brace = start - 1;
+                Some(if new_signature.ends_with('{') {
+                    parsed.content[brace + 1..line_end].to_string()
+                } else {
+                    format!(" {}", &parsed.content[brace..line_end])
+                })
+            }
+            BodyStyle::Indented { .. } if old_line.trim_end().ends_with(':') => {
+                (!new_signature.ends_with(':')).then(|| ":".to_string())
+            }
+            _ => None,
+        };
+
+        let indent = leading_whitespace(&old_line);
+        let replacement = format!("{indent}{new_signature}{}", opener.unwrap_or_default());
+        let occurrence = target_index + 1;
+        let name = target.name.clone();
+        let line = target.line;
+        parsed
+            .content
+            .replace_range(line_start..line_end, &replacement);
+
         fs::write(&path, &parsed.content)
             .with_context(|| format!("Failed to write {}", path.display()))?;
+        journal::record_edit(
+            "edit_symbol_signature",
+            &path,
+            vec![name.clone()],
+            Some(format!("signature on line {line}")),
+        );
 
         Ok(json!({
-            "path": path.to_string_lossy(),
-            "symbol": target.name,
-            "occurrence": target_index + 1,
+            "path": display_path(&path),
+            "symbol": name,
+            "occurrence": occurrence,
+            "line": line,
+            "old_signature": old_line.trim_end(),
+            "new_signature": replacement.trim_end(),
         }))
     };
 
     Tool::new(
-        "replace_symbol_body",
-        "Replace the implementation of a symbol, preserving surrounding formatting",
+        "edit_symbol_signature",
+        "Replace the declaration line of a symbol (parameters, return type) without touching its body",
         schema,
         Box::new(handler),
     )
+    .mutating()
+    .resolves_paths()
+}
+
+/// Keywords that introduce a declaration, checked so a signature edit cannot
+/// silently turn a function into a class or similar.
+const DECLARATION_KEYWORDS: &[&str] = &[
+    "fn",
+    "struct",
+    "enum",
+    "trait",
+    "impl",
+    "mod",
+    "def",
+    "class",
+    "function",
+    "interface",
+    "type",
+    "func",
+    "record",
+    "namespace",
+    "const",
+    "static",
+];
+
+fn declaration_keyword(line: &str) -> Option<&'static str> {
+    line.split(|c: char| !(c.is_alphanumeric() || c == '_'))
+        .find_map(|word| DECLARATION_KEYWORDS.iter().find(|k| **k == word).copied())
+}
+
+fn insert_text_tool() -> Tool {
+    let schema = json!({
+        "type": "object",
+        "properties": {
+            "path": {"type": "string"},
+            "content": {"type": "string", "description": "Text to insert; a trailing newline is added when missing"},
+            "line": {"type": "integer", "minimum": 1, "description": "Insert before this 1-based line (use line count + 1 to append)"},
+            "before_symbol": {"type": "string", "description": "Insert above this symbol, including its attributes/decorators"},
+            "after_symbol": {"type": "string", "description": "Insert after the end of this symbol's body"},
+            "occurrence": {"type": "integer", "minimum": 1, "description": "Disambiguate the anchor symbol (1-based)"},
+            "case_sensitive": {"type": "boolean", "default": true}
+        },
+        "required": ["path", "content"],
+        "examples": [{"path": "src/lib.rs", "content": "mod helpers;\n", "line": 1}],
+        "x-positional-order": [
+            "path", "content", "line", "before_symbol", "after_symbol", "occurrence",
+            "case_sensitive"
+        ],
+        "additionalProperties": false
+    });
+
+    #[derive(Deserialize)]
+    struct Params {
+        path: String,
+        content: String,
+        #[serde(default)]
+        line: Option<usize>,
+        #[serde(default)]
+        before_symbol: Option<String>,
+        #[serde(default)]
+        after_symbol: Option<String>,
+        #[serde(default)]
+        occurrence: Option<usize>,
+        #[serde(default)]
+        case_sensitive: Option<bool>,
+    }
+
+    let handler = move |params| -> Result<Value> {
+        let args: Params =
+            serde_json::from_value(params).context("Invalid arguments for insert_text")?;
+        let path = resolve_path(&args.path)?;
+        let case_sensitive = args.case_sensitive.unwrap_or(true);
+
+        let (mut content, offset, text, anchor) = match (
+            args.line,
+            args.before_symbol.as_deref(),
+            args.after_symbol.as_deref(),
+        ) {
+            (Some(line), None, None) => {
+                let content = fs::read_to_string(&path)
+                    .with_context(|| format!("Failed to read {}", path.display()))?;
+                let lines = FileLines::new(&content);
+                let offset = if line == lines.len() + 1 {
+                    content.len()
+                } else if line <= lines.len() {
+                    lines.bounds(line - 1).0
+                } else {
+                    anyhow::bail!(
+                        "line {line} is outside the file range (1..={})",
+                        lines.len() + 1
+                    );
+                };
+                let text = ensure_trailing_newline(&args.content);
+                (content, offset, text, json!({ "line": line }))
+            }
+            (None, Some(name), None) | (None, None, Some(name)) => {
+                let after = args.after_symbol.is_some();
+                let parsed = ParsedFile::from_path(&path)?.with_context(|| {
+                    format!("{} is not a supported source file", path.display())
+                })?;
+                let (index, symbol) = select_symbol(
+                    &parsed.symbols,
+                    name,
+                    None,
+                    args.occurrence,
+                    case_sensitive,
+                    &path,
+                )?;
+
+                let signature_idx = symbol.line - 1;
+                let indent = leading_whitespace(parsed.lines.text(signature_idx)).to_string();
+                let offset = if after {
+                    symbol_end_offset(&parsed.lines, symbol)
+                } else {
+                    let start_idx = decoration_start(&parsed.lines, signature_idx);
+                    parsed.lines.bounds(start_idx).0
+                };
+                let text = ensure_trailing_newline(&reindent(&args.content, &indent));
+                let anchor = json!({
+                    if after { "after_symbol" } else { "before_symbol" }: symbol.name,
+                    "occurrence": index + 1,
+                });
+                (parsed.content, offset, text, anchor)
+            }
+            _ => anyhow::bail!("Specify exactly one of `line`, `before_symbol`, or `after_symbol`"),
+        };
+
+        // Inserted lines take the file's line ending, and appending to a file
+        // without a trailing newline must not glue them onto the last line.
+        let line_ending = LineEnding::detect(&content);
+        let text = line_ending.apply(&text);
+        let needs_newline =
+            offset == content.len() && !content.is_empty() && !content.ends_with('\n');
+        let start_line = content[..offset].matches('\n').count() + 1 + usize::from(needs_newline);
+        let end_line = start_line + text.matches('\n').count() - 1;
+
+        if needs_newline {
+            let newline = line_ending.as_str();
+            content.insert_str(offset, newline);
+            content.insert_str(offset + newline.len(), &text);
+        } else {
+            content.insert_str(offset, &text);
+        }
+        fs::write(&path, &content)
+            .with_context(|| format!("Failed to write {}", path.display()))?;
+        journal::record_edit(
+            "insert_text",
+            &path,
+            args.before_symbol
+                .iter()
+                .chain(&args.after_symbol)
+                .cloned()
+                .collect(),
+            Some(format!("inserted lines {start_line}-{end_line}")),
+        );
+
+        Ok(json!({
+            "path": display_path(&path),
+            "anchor": anchor,
+            "start_line": start_line,
+            "end_line": end_line,
+        }))
+    };
+
+    Tool::new(
+        "insert_text",
+        "Insert text at a line or before/after a symbol without replacing existing code",
+        schema,
+        Box::new(handler),
+    )
+    .mutating()
+    .resolves_paths()
+}
+
+fn replace_span_tool() -> Tool {
+    let schema = json!({
+        "type": "object",
+        "properties": {
+            "path": {"type": "string"},
+            "start_line": {"type": "integer", "minimum": 1, "description": "1-based line of the first replaced character"},
+            "start_col": {"type": "integer", "minimum": 1, "description": "1-based character column of the first replaced character"},
+            "end_line": {"type": "integer", "minimum": 1, "description": "1-based line the span ends on"},
+            "end_col": {"type": "integer", "minimum": 1, "description": "1-based character column just past the span (exclusive); line length + 1 reaches the end of the line"},
+            "text": {"type": "string", "description": "Replacement text, inserted verbatim"}
+        },
+        "required": ["path", "start_line", "start_col", "end_line", "end_col", "text"],
+        "examples": [
+            {"path": "src/lib.rs", "start_line": 12, "start_col": 18, "end_line": 12, "end_col": 23, "text": "limit"}
+        ],
+        "x-positional-order": ["path", "start_line", "start_col", "end_line", "end_col", "text"],
+        "additionalProperties": false
+    });
+
+    #[derive(Deserialize)]
+    struct Params {
+        path: String,
+        start_line: usize,
+        start_col: usize,
+        end_line: usize,
+        end_col: usize,
+        text: String,
+    }
+
+    let handler = move |params| -> Result<Value> {
+        let args: Params =
+            serde_json::from_value(params).context("Invalid arguments for replace_span")?;
+        if (args.start_line, args.start_col) > (args.end_line, args.end_col) {
+            anyhow::bail!(
+                "Span start {}:{} is after its end {}:{}",
+                args.start_line,
+                args.start_col,
+                args.end_line,
+                args.end_col
+            );
+        }
+        let path = resolve_path(&args.path)?;
+        let mut content = fs::read_to_string(&path)
+            .with_context(|| format!("Failed to read {}", path.display()))?;
+        let lines = FileLines::new(&content);
+        let start = column_offset(&lines, args.start_line, args.start_col)?;
+        let end = column_offset(&lines, args.end_line, args.end_col)?;
+
+        let replaced = content[start..end].to_string();
+        content.replace_range(start..end, &args.text);
+        fs::write(&path, &content)
+            .with_context(|| format!("Failed to write {}", path.display()))?;
+        journal::record_edit(
+            "replace_span",
+            &path,
+            Vec::new(),
+            Some(format!(
+                "replaced {}:{}-{}:{}",
+                args.start_line, args.start_col, args.end_line, args.end_col
+            )),
+        );
+
+        // Where the inserted text now ends, in the same exclusive convention.
+        let (new_end_line, new_end_col) = match args.text.rsplit_once('\n') {
+            Some((before, last)) => (
+                args.start_line + before.matches('\n').count() + 1,
+                last.chars().count() + 1,
+            ),
+            None => (args.start_line, args.start_col + args.text.chars().count()),
+        };
+
+        Ok(json!({
+            "path": display_path(&path),
+            "start_line": args.start_line,
+            "start_col": args.start_col,
+            "end_line": args.end_line,
+            "end_col": args.end_col,
+            "replaced": replaced,
+            "new_end_line": new_end_line,
+            "new_end_col": new_end_col,
+        }))
+    };
+
+    Tool::new(
+        "replace_span",
+        "Replace the exact character span between two line:column positions, e.g. one argument of a call",
+        schema,
+        Box::new(handler),
+    )
+    .mutating()
+    .resolves_paths()
+}
+
+/// Byte offset of the 1-based character `col` on 1-based `line`. Column
+/// `length + 1` is the end of the line, before any `\r\n` or `\n`.
+fn column_offset(lines: &FileLines, line: usize, col: usize) -> Result<usize> {
+    if line == 0 || line > lines.len() {
+        anyhow::bail!(
+            "line {line} is outside the file range (1..={})",
+            lines.len()
+        );
+    }
+    let text = lines.text(line - 1);
+    let text = text.strip_suffix('\r').unwrap_or(text);
+    let length = text.chars().count();
+    if col == 0 || col > length + 1 {
+        anyhow::bail!(
+            "column {col} is outside line {line}, which has {length} characters (columns 1..={})",
+            length + 1
+        );
+    }
+    let within = text
+        .char_indices()
+        .nth(col - 1)
+        .map_or(text.len(), |(offset, _)| offset);
+    Ok(lines.bounds(line - 1).0 + within)
+}
+
+/// Byte offset just past the last line of a symbol (its closing brace or the
+/// final line of an indented block, falling back to the signature line).
+fn symbol_end_offset(lines: &FileLines, symbol: &FileSymbol) -> usize {
+    match &symbol.body {
+        BodyStyle::Braces { end, .. } => lines.bounds(lines.line_index(*end)).1,
+        BodyStyle::Indented { end, .. } => *end,
+        BodyStyle::None => lines.bounds(symbol.line - 1).1,
+    }
+}
+
+/// Walk upwards from a signature over attached attributes, decorators and doc
+/// comments so insertions land above the whole declaration.
+fn decoration_start(lines: &FileLines, signature_idx: usize) -> usize {
+    let mut idx = signature_idx;
+    while idx > 0 {
+        let previous = lines.text(idx - 1).trim_start();
+        if previous.starts_with("#[") || previous.starts_with('@') || previous.starts_with("///") {
+            idx -= 1;
+        } else {
+            break;
+        }
+    }
+    idx
+}
+
+/// Strip the common leading indentation from `text` and prefix each non-empty
+/// line with `indent`.
+fn reindent(text: &str, indent: &str) -> String {
+    let common = text
+        .lines()
+        .filter(|line| !line.trim().is_empty())
+        .map(|line| leading_whitespace(line).len())
+        .min()
+        .unwrap_or(0);
+
+    text.lines()
+        .map(|line| {
+            if line.trim().is_empty() {
+                String::new()
+            } else {
+                format!("{indent}{}", &line[common..])
+            }
+        })
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+/// Pick a single symbol by exact name, using `occurrence` (1-based, in line
+/// order) to disambiguate when several symbols share the name. With a
+/// `container`, only symbols enclosed by that container are considered.
+fn select_symbol<'a>(
+    symbols: &'a [FileSymbol],
+    name: &str,
+    container: Option<&str>,
+    occurrence: Option<usize>,
+    case_sensitive: bool,
+    path: &Path,
+) -> Result<(usize, &'a FileSymbol)> {
+    let mut candidates: Vec<&FileSymbol> = symbols
+        .iter()
+        .filter(|symbol| symbol_name_matches(&symbol.name, name, false, case_sensitive))
+        .filter(|symbol| {
+            container.is_none_or(|container| {
+                container_matches(symbol.enclosing.as_deref(), container, case_sensitive)
+            })
+        })
+        .collect();
+
+    if candidates.is_empty() {
+        match container {
+            Some(container) => anyhow::bail!(
+                "No symbol named '{}' inside '{}' found in {}",
+                name,
+                container,
+                path.display()
+            ),
+            None => anyhow::bail!("No symbol named '{}' found in {}", name, path.display()),
+        }
+    }
+
+    candidates.sort_by_key(|symbol| symbol.line);
+    let target_index = match occurrence {
+        Some(idx) => {
+            if idx == 0 || idx > candidates.len() {
+                anyhow::bail!(
+                    "Occurrence {idx} is out of bounds (only {} matches)",
+                    candidates.len()
+                );
+            }
+            idx - 1
+        }
+        None => {
+            if candidates.len() > 1 {
+                anyhow::bail!(
+                    "Multiple symbols named '{}' found; specify `occurrence` to disambiguate",
+                    name
+                );
+            }
+            0
+        }
+    };
+
+    Ok((target_index, candidates[target_index]))
+}
+
+/// Check that `(`, `[` and `{` pair up in `text`, ignoring string, character
+/// and raw string literals and `//` and `/* */` comments. Returns a
+/// description of every mismatch, with 1-based line numbers relative to
+/// `text`.
+fn delimiter_imbalance(text: &str) -> Option<String> {
+    let bytes = text.as_bytes();
+    let mut stack: Vec<(u8, usize)> = Vec::new();
+    let mut problems = Vec::new();
+    let mut line = 1;
+    let mut index = 0;
+
+    while index < bytes.len() {
+        if let Some(end) = skip_comment_or_raw_string(bytes, index) {
+            line += bytes[index..end].iter().filter(|b| **b == b'\n').count();
+            index = end;
+            continue;
+        }
+        match bytes[index] {
+            b'\n' => line += 1,
+            b'"' | b'`' => {
+                let end = skip_string(bytes, index);
+                line += bytes[index..end].iter().filter(|b| **b == b'\n').count();
+                index = end;
+                continue;
+            }
+            // Only treat `'` as a quote for char literals so Rust lifetimes
+            // (`'a`) do not swallow the rest of the body.
+            b'\''
+                if bytes.get(index + 1) == Some(&b'\\') || bytes.get(index + 2) == Some(&b'\'') =>
+            {
+                index = skip_string(bytes, index);
+                continue;
+            }
+            open @ (b'(' | b'[' | b'{') => stack.push((open, line)),
+            close @ (b')' | b']' | b'}') => {
+                let expected = match close {
+                    b')' => b'(',
+                    b']' => b'[',
+                    _ => b'{',
+                };
+                match stack.pop() {
+                    Some((open, _)) if open == expected => {}
+                    Some((open, open_line)) => problems.push(format!(
+                        "'{}' on line {line} closes '{}' opened on line {open_line}",
+                        close as char, open as char
+                    )),
+                    None => problems.push(format!("unexpected '{}' on line {line}", close as char)),
+                }
+            }
+            _ => {}
+        }
+        index += 1;
+    }
+
+    problems.extend(stack.iter().map(|(open, open_line)| {
+        format!("unclosed '{}' opened on line {open_line}", *open as char)
+    }));
+
+    (!problems.is_empty()).then(|| problems.join(", "))
 }
 
 fn ensure_trailing_newline(body: &str) -> String {
@@ -1297,7 +4011,7 @@ fn format_brace_body(body: &str, base_indent: &str, inner_indent: &str) -> Strin
         for line in trimmed.lines() {
             let line = line.trim_end();
             if line.is_empty() {
-                lines.push(format!("{inner_indent}"));
+                lines.push(inner_indent.to_string());
             } else {
                 lines.push(format!("{inner_indent}{line}"));
             }
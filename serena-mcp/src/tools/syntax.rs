@@ -0,0 +1,620 @@
+use std::ops::Range;
+use std::path::Path;
+
+use crate::tools::symbols::Language;
+
+/// Comment markers for a language or file type.
+pub(crate) struct CommentSyntax {
+    pub(crate) line: &'static [&'static str],
+    pub(crate) block: Option<(&'static str, &'static str)>,
+}
+
+const C_STYLE: CommentSyntax = CommentSyntax {
+    line: &["//"],
+    block: Some(("/*", "*/")),
+};
+
+const HASH: CommentSyntax = CommentSyntax {
+    line: &["#"],
+    block: None,
+};
+
+const MARKUP: CommentSyntax = CommentSyntax {
+    line: &[],
+    block: Some(("<!--", "-->")),
+};
+
+const NONE: CommentSyntax = CommentSyntax {
+    line: &[],
+    block: None,
+};
+
+/// Comment markers shared by every file of `language`. Generic files default
+/// to C-style comments; use [`comment_syntax`] when the path is known.
+pub(crate) fn language_comment_syntax(language: Language) -> CommentSyntax {
+    match language {
+        Language::Python | Language::Config => HASH,
+        Language::Markup => MARKUP,
+        _ => C_STYLE,
+    }
+}
+
+/// Comment markers for `path`, refining the generic, config and markup
+/// buckets by extension.
+pub(crate) fn comment_syntax(path: &Path) -> Option<CommentSyntax> {
+    let language = Language::from_path(path)?;
+    let ext = path.extension()?.to_string_lossy().to_lowercase();
+    let syntax = match (language, ext.as_str()) {
+        (Language::Config, "json") => NONE,
+        (Language::Config, "jsonc" | "json5") => C_STYLE,
+        (Language::Config, "ini" | "cfg" | "conf" | "properties") => CommentSyntax {
+            line: &["#", ";"],
+            block: None,
+        },
+        (Language::Markup, "rst" | "adoc") => NONE,
+        (Language::Generic, _) => generic_comment_syntax(&ext),
+        _ => language_comment_syntax(language),
+    };
+    Some(syntax)
+}
+
+/// Comment markers for the catch-all generic bucket, by extension.
+fn generic_comment_syntax(ext: &str) -> CommentSyntax {
+    match ext {
+        "rb" | "sh" | "bash" | "zsh" | "ex" | "exs" | "nim" | "pl" | "pm" | "r" | "jl" | "tcl"
+        | "cmake" | "ps1" | "cr" | "coffee" => HASH,
+        "php" => CommentSyntax {
+            line: &["//", "#"],
+            block: Some(("/*", "*/")),
+        },
+        "lua" => CommentSyntax {
+            line: &["--"],
+            block: Some(("--[[", "]]")),
+        },
+        "hs" => CommentSyntax {
+            line: &["--"],
+            block: Some(("{-", "-}")),
+        },
+        "ml" => CommentSyntax {
+            line: &[],
+            block: Some(("(*", "*)")),
+        },
+        "el" => CommentSyntax {
+            line: &[";"],
+            block: None,
+        },
+        "erl" => CommentSyntax {
+            line: &["%"],
+            block: None,
+        },
+        _ => C_STYLE,
+    }
+}
+
+/// What a [`LexicalSpan`] covers.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum SpanKind {
+    Comment,
+    String,
+}
+
+/// A comment or string literal found by [`lexical_spans`], as byte ranges of
+/// the scanned content.
+#[derive(Debug, Clone)]
+pub(crate) struct LexicalSpan {
+    pub(crate) kind: SpanKind,
+    /// The whole comment or literal, delimiters included.
+    pub(crate) range: Range<usize>,
+    /// The text between a literal's delimiters; the whole range for
+    /// comments.
+    pub(crate) interior: Range<usize>,
+}
+
+/// Return `content` with comments and string-literal interiors replaced by
+/// spaces. Newlines are kept and every blanked byte becomes one space, so byte
+/// offsets and line numbers match the original; string delimiters stay in
+/// place so empty-looking literals are still visible as `""`.
+pub(crate) fn strip_comments(content: &str, language: Language) -> String {
+    let mut out = content.as_bytes().to_vec();
+    for span in lexical_spans(content, language) {
+        blank(&mut out, span.interior.start, span.interior.end);
+    }
+
+    // Only ASCII delimiters bound the blanked ranges, so whole UTF-8
+    // sequences are replaced and the result stays valid.
+    String::from_utf8(out).unwrap_or_else(|_| content.to_string())
+}
+
+/// Comments and string literals of `content`, in order, using the comment
+/// markers and string forms of `language`.
+pub(crate) fn lexical_spans(content: &str, language: Language) -> Vec<LexicalSpan> {
+    lexical_spans_with(content, language, &language_comment_syntax(language))
+}
+
+/// [`lexical_spans`] with explicit comment markers, e.g. the per-extension
+/// ones from [`comment_syntax`].
+pub(crate) fn lexical_spans_with(
+    content: &str,
+    language: Language,
+    syntax: &CommentSyntax,
+) -> Vec<LexicalSpan> {
+    let bytes = content.as_bytes();
+    let mut spans = Vec::new();
+    let mut push = |kind, range: Range<usize>, interior: Range<usize>| {
+        spans.push(LexicalSpan {
+            kind,
+            range,
+            interior,
+        });
+    };
+    let mut index = 0;
+
+    while index < bytes.len() {
+        let rest = &bytes[index..];
+
+        if let Some(marker) = syntax
+            .line
+            .iter()
+            .find(|marker| rest.starts_with(marker.as_bytes()))
+        {
+            let end = line_end(bytes, index + marker.len());
+            push(SpanKind::Comment, index..end, index..end);
+            index = end;
+            continue;
+        }
+
+        if let Some((open, close)) = syntax.block
+            && rest.starts_with(open.as_bytes())
+        {
+            let end = block_comment_end(bytes, index, open, close, language == Language::Rust);
+            push(SpanKind::Comment, index..end, index..end);
+            index = end;
+            continue;
+        }
+
+        match bytes[index] {
+            b'"' | b'\'' if language == Language::Python && is_triple_quote(rest) => {
+                let quote = &rest[..3];
+                let end = find(bytes, index + 3, quote).map_or(bytes.len(), |pos| pos + 3);
+                let interior_end = end.saturating_sub(3).max(index + 3);
+                push(SpanKind::String, index..end, index + 3..interior_end);
+                index = end;
+            }
+            b'r' if language == Language::Rust && starts_raw_string(bytes, index) => {
+                let end = raw_string_end(bytes, index);
+                let hashes = bytes[index + 1..]
+                    .iter()
+                    .take_while(|b| **b == b'#')
+                    .count();
+                let open_len = 2 + hashes;
+                let close_len = 1 + hashes;
+                let interior_end = if end - index >= open_len + close_len {
+                    end - close_len
+                } else {
+                    end
+                };
+                push(SpanKind::String, index..end, index + open_len..interior_end);
+                index = end;
+            }
+            b'\'' if language == Language::Rust && !is_char_literal(content, index) => {
+                // Lifetime or label, not a quote.
+                index += 1;
+            }
+            b'"' | b'\'' | b'`' => {
+                // Only Rust strings and backtick literals may span lines; for
+                // everything else a stray quote (an apostrophe in a comment
+                // style we do not know) must not swallow following lines.
+                let multiline = bytes[index] == b'`' || language == Language::Rust;
+                let limit = if multiline {
+                    bytes.len()
+                } else {
+                    line_end(bytes, index)
+                };
+                let end = skip_string(&bytes[..limit], index);
+                let closed =
+                    end <= bytes.len() && end > index + 1 && bytes[end - 1] == bytes[index];
+                let interior_end = if closed { end - 1 } else { end };
+                push(SpanKind::String, index..end, index + 1..interior_end);
+                index = end;
+            }
+            _ => index += 1,
+        }
+    }
+    spans
+}
+
+/// Index just past the closing quote of the string starting at `index`,
+/// honouring backslash escapes. Unterminated strings run to the end.
+pub(crate) fn skip_string(bytes: &[u8], index: usize) -> usize {
+    string_end(bytes, index).unwrap_or(bytes.len())
+}
+
+/// Index just past the closing quote of the string starting at `index`, or
+/// `None` when the string is not terminated.
+fn string_end(bytes: &[u8], mut index: usize) -> Option<usize> {
+    let quote = bytes[index];
+    index += 1;
+    while index < bytes.len() {
+        let b = bytes[index];
+        if b == b'\\' {
+            index += 2;
+            continue;
+        }
+        if b == quote {
+            return Some(index + 1);
+        }
+        index += 1;
+    }
+    None
+}
+
+/// A problem reported by [`check_syntax`], on a 1-based line.
+pub(crate) struct SyntaxIssue {
+    pub(crate) line: usize,
+    pub(crate) message: String,
+}
+
+/// Cheap structural check of `content`: brackets must balance and strings
+/// and block comments must be terminated, and Python files must indent
+/// consistently. Not a parser; it catches the damage a bad edit typically
+/// leaves behind.
+pub(crate) fn check_syntax(content: &str, path: &Path, language: Language) -> Vec<SyntaxIssue> {
+    let syntax = comment_syntax(path).unwrap_or_else(|| language_comment_syntax(language));
+    let bytes = content.as_bytes();
+    let mut issues = Vec::new();
+    let mut stack: Vec<(u8, usize)> = Vec::new();
+    // `(offset, line)` of every line that starts a Python logical line: not
+    // inside brackets or a string and not after a backslash continuation.
+    let mut logical_lines = vec![(0, 1)];
+    let mut line = 1;
+    let mut index = 0;
+
+    let newlines = |from: usize, to: usize| bytes[from..to].iter().filter(|b| **b == b'\n').count();
+
+    while index < bytes.len() {
+        let rest = &bytes[index..];
+
+        if let Some(marker) = syntax
+            .line
+            .iter()
+            .find(|marker| rest.starts_with(marker.as_bytes()))
+        {
+            index = line_end(bytes, index + marker.len());
+            continue;
+        }
+
+        if let Some((open, close)) = syntax.block
+            && rest.starts_with(open.as_bytes())
+        {
+            let end = block_comment_end(bytes, index, open, close, language == Language::Rust);
+            if end < index + open.len() + close.len() || !bytes[..end].ends_with(close.as_bytes()) {
+                issues.push(SyntaxIssue {
+                    line,
+                    message: format!("unterminated block comment `{open}`"),
+                });
+            }
+            line += newlines(index, end);
+            index = end;
+            continue;
+        }
+
+        let quote = bytes[index];
+        match quote {
+            b'"' | b'\'' if language == Language::Python && is_triple_quote(rest) => {
+                let end = triple_quote_end(bytes, index);
+                if end.is_none() {
+                    issues.push(SyntaxIssue {
+                        line,
+                        message: "unterminated triple-quoted string".to_string(),
+                    });
+                }
+                let end = end.unwrap_or(bytes.len());
+                line += newlines(index, end);
+                index = end;
+            }
+            b'r' if language == Language::Rust && starts_raw_string(bytes, index) => {
+                let hashes = bytes[index + 1..]
+                    .iter()
+                    .take_while(|b| **b == b'#')
+                    .count();
+                let end = raw_string_end(bytes, index);
+                let closed = end >= index + 3 + 2 * hashes
+                    && bytes[..end].ends_with(&bytes[index + 1..index + 1 + hashes])
+                    && bytes[end - 1 - hashes] == b'"';
+                if !closed {
+                    issues.push(SyntaxIssue {
+                        line,
+                        message: "unterminated raw string".to_string(),
+                    });
+                }
+                line += newlines(index, end);
+                index = end;
+            }
+            b'\'' if language == Language::Rust && !is_char_literal(content, index) => {
+                // Lifetime or label, not a quote.
+                index += 1;
+            }
+            b'\'' | b'`' if language == Language::Config => index += 1,
+            b'"' | b'\'' | b'`' => {
+                // As in `strip_comments`, only Rust strings and backtick
+                // literals may span lines.
+                let multiline = quote == b'`' || language == Language::Rust;
+                let mut limit = bytes.len();
+                if !multiline {
+                    limit = line_end(bytes, index);
+                    // A trailing backslash continues the string on the next line.
+                    while limit < bytes.len() && bytes[..limit].trim_ascii_end().ends_with(b"\\") {
+                        limit = line_end(bytes, limit + 1);
+                    }
+                }
+                let end = string_end(&bytes[..limit], index);
+                if end.is_none() {
+                    issues.push(SyntaxIssue {
+                        line,
+                        message: format!(
+                            "unterminated string literal opened with `{}`",
+                            quote as char
+                        ),
+                    });
+                }
+                let end = end.unwrap_or(limit);
+                line += newlines(index, end);
+                index = end;
+            }
+            b'\n' => {
+                line += 1;
+                index += 1;
+                let continued = bytes[..index - 1].trim_ascii_end().ends_with(b"\\");
+                if stack.is_empty() && !continued {
+                    logical_lines.push((index, line));
+                }
+            }
+            open @ (b'(' | b'[' | b'{') => {
+                stack.push((open, line));
+                index += 1;
+            }
+            close @ (b')' | b']' | b'}') => {
+                let expected = match close {
+                    b')' => b'(',
+                    b']' => b'[',
+                    _ => b'{',
+                };
+                match stack.pop() {
+                    Some((open, _)) if open == expected => {}
+                    Some((open, open_line)) => issues.push(SyntaxIssue {
+                        line,
+                        message: format!(
+                            "`{}` closes `{}` opened on line {open_line}",
+                            close as char, open as char
+                        ),
+                    }),
+                    None => issues.push(SyntaxIssue {
+                        line,
+                        message: format!("unexpected `{}`", close as char),
+                    }),
+                }
+                index += 1;
+            }
+            _ => index += 1,
+        }
+    }
+
+    issues.extend(stack.iter().map(|(open, open_line)| SyntaxIssue {
+        line: *open_line,
+        message: format!("unclosed `{}`", *open as char),
+    }));
+
+    if language == Language::Python {
+        issues.extend(python_indentation_issues(content, &logical_lines));
+    }
+    issues.sort_by_key(|issue| issue.line);
+    issues
+}
+
+/// Indentation problems Python would reject: tabs mixed with spaces, an
+/// indent that does not follow a `:` line, a missing indent after one, and a
+/// dedent to a level no enclosing block uses. `logical_lines` are the
+/// `(offset, line)` starts of logical lines; blank and comment lines are
+/// skipped.
+fn python_indentation_issues(content: &str, logical_lines: &[(usize, usize)]) -> Vec<SyntaxIssue> {
+    let code = strip_comments(content, Language::Python);
+    let mut issues = Vec::new();
+    let mut levels = vec![0];
+    let mut indent_style: Option<(char, usize)> = None;
+    let mut opener: Option<usize> = None;
+
+    for (position, &(start, line)) in logical_lines.iter().enumerate() {
+        let text = content[start..line_end(content.as_bytes(), start)].trim_end_matches('\r');
+        let trimmed = text.trim_start_matches([' ', '\t']);
+        if trimmed.is_empty() || trimmed.starts_with('#') {
+            continue;
+        }
+        let indent = &text[..text.len() - trimmed.len()];
+
+        if indent.contains(' ') && indent.contains('\t') {
+            issues.push(SyntaxIssue {
+                line,
+                message: "indentation mixes tabs and spaces".to_string(),
+            });
+        } else if let Some(style) = indent.chars().next() {
+            match indent_style {
+                None => indent_style = Some((style, line)),
+                Some((first, first_line)) if first != style => issues.push(SyntaxIssue {
+                    line,
+                    message: format!(
+                        "indented with {} but line {first_line} uses {}",
+                        indent_name(style),
+                        indent_name(first)
+                    ),
+                }),
+                Some(_) => {}
+            }
+        }
+
+        let width = indent.len();
+        let current = *levels.last().unwrap_or(&0);
+        if width > current {
+            if opener.is_none() {
+                issues.push(SyntaxIssue {
+                    line,
+                    message: "unexpected indent".to_string(),
+                });
+            }
+            levels.push(width);
+        } else {
+            if let Some(opener_line) = opener {
+                issues.push(SyntaxIssue {
+                    line,
+                    message: format!("expected an indented block after line {opener_line}"),
+                });
+            }
+            while levels.last().is_some_and(|level| *level > width) {
+                levels.pop();
+            }
+            if levels.last() != Some(&width) {
+                issues.push(SyntaxIssue {
+                    line,
+                    message: "unindent does not match any outer indentation level".to_string(),
+                });
+                levels.push(width);
+            }
+        }
+
+        let end = logical_lines
+            .get(position + 1)
+            .map_or(code.len(), |(next, _)| *next);
+        opener = code[start..end].trim_end().ends_with(':').then_some(line);
+    }
+
+    if let Some(opener_line) = opener {
+        issues.push(SyntaxIssue {
+            line: opener_line,
+            message: format!("expected an indented block after line {opener_line}"),
+        });
+    }
+    issues
+}
+
+/// Index just past the end of the Python triple-quoted string starting at
+/// `index`, honouring backslash escapes, or `None` when it is not terminated.
+fn triple_quote_end(bytes: &[u8], index: usize) -> Option<usize> {
+    let quote = &bytes[index..index + 3];
+    let mut cursor = index + 3;
+    while cursor < bytes.len() {
+        if bytes[cursor] == b'\\' {
+            cursor += 2;
+        } else if bytes[cursor..].starts_with(quote) {
+            return Some(cursor + 3);
+        } else {
+            cursor += 1;
+        }
+    }
+    None
+}
+
+fn indent_name(style: char) -> &'static str {
+    if style == '\t' { "tabs" } else { "spaces" }
+}
+
+/// End of the `//` line comment, `/* */` block comment or Rust raw string
+/// (`r"..."`, `r#"..."#`) starting at `index`, for scanners that do not know
+/// the file's language. Line comments end at, not after, their newline.
+pub(crate) fn skip_comment_or_raw_string(bytes: &[u8], index: usize) -> Option<usize> {
+    let rest = &bytes[index..];
+    if rest.starts_with(b"//") {
+        Some(line_end(bytes, index))
+    } else if rest.starts_with(b"/*") {
+        Some(block_comment_end(bytes, index, "/*", "*/", false))
+    } else if rest.first() == Some(&b'r') && starts_raw_string(bytes, index) {
+        Some(raw_string_end(bytes, index))
+    } else {
+        None
+    }
+}
+
+fn blank(out: &mut [u8], start: usize, end: usize) {
+    let end = end.min(out.len());
+    for byte in out.iter_mut().take(end).skip(start) {
+        if *byte != b'\n' && *byte != b'\r' {
+            *byte = b' ';
+        }
+    }
+}
+
+fn line_end(bytes: &[u8], from: usize) -> usize {
+    bytes[from.min(bytes.len())..]
+        .iter()
+        .position(|b| *b == b'\n')
+        .map_or(bytes.len(), |pos| from + pos)
+}
+
+fn find(bytes: &[u8], from: usize, needle: &[u8]) -> Option<usize> {
+    bytes
+        .get(from..)?
+        .windows(needle.len())
+        .position(|window| window == needle)
+        .map(|pos| from + pos)
+}
+
+/// End of a block comment starting at `index`; Rust block comments nest.
+fn block_comment_end(bytes: &[u8], index: usize, open: &str, close: &str, nested: bool) -> usize {
+    let (open, close) = (open.as_bytes(), close.as_bytes());
+    let mut depth = 1;
+    let mut cursor = index + open.len();
+    while cursor < bytes.len() {
+        if bytes[cursor..].starts_with(close) {
+            depth -= 1;
+            cursor += close.len();
+            if depth == 0 || !nested {
+                return cursor;
+            }
+        } else if nested && bytes[cursor..].starts_with(open) {
+            depth += 1;
+            cursor += open.len();
+        } else {
+            cursor += 1;
+        }
+    }
+    bytes.len()
+}
+
+fn is_triple_quote(rest: &[u8]) -> bool {
+    rest.len() >= 3 && rest[0] == rest[1] && rest[1] == rest[2]
+}
+
+/// `r"..."`, `r#"..."#` and `br"..."` when `r` does not end an identifier.
+fn starts_raw_string(bytes: &[u8], index: usize) -> bool {
+    let mut start = index;
+    if start > 0 && bytes[start - 1] == b'b' {
+        start -= 1;
+    }
+    if start > 0 && (bytes[start - 1].is_ascii_alphanumeric() || bytes[start - 1] == b'_') {
+        return false;
+    }
+    let hashes = bytes[index + 1..]
+        .iter()
+        .take_while(|b| **b == b'#')
+        .count();
+    bytes.get(index + 1 + hashes) == Some(&b'"')
+}
+
+fn raw_string_end(bytes: &[u8], index: usize) -> usize {
+    let hashes = bytes[index + 1..]
+        .iter()
+        .take_while(|b| **b == b'#')
+        .count();
+    let mut closing = vec![b'"'];
+    closing.extend(std::iter::repeat_n(b'#', hashes));
+    find(bytes, index + 2 + hashes, &closing).map_or(bytes.len(), |pos| pos + closing.len())
+}
+
+/// Distinguish `'x'`/`'\n'` from lifetimes such as `'a`.
+pub(crate) fn is_char_literal(content: &str, index: usize) -> bool {
+    let rest = &content[index + 1..];
+    let mut chars = rest.chars();
+    match chars.next() {
+        Some('\\') => true,
+        Some(c) => rest[c.len_utf8()..].starts_with('\''),
+        None => false,
+    }
+}
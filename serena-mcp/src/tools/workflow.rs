@@ -1,20 +1,79 @@
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
+use std::ffi::OsString;
 use std::fs;
-use std::path::Path;
+use std::path::{Path, PathBuf};
 
 use anyhow::{Context, Result};
+use once_cell::sync::Lazy;
+use regex::Regex;
 use serde::{Deserialize, Serialize};
 use serde_json::{Value, json};
 use time::{OffsetDateTime, format_description::well_known::Rfc3339};
-use walkdir::{DirEntry, WalkDir};
+use walkdir::WalkDir;
 
+use crate::cancellation;
+use crate::notifications::ProgressReporter;
 use crate::tool::{Tool, ToolRegistry};
-use crate::tools::{resolve_path, state_file};
+use crate::tools::symbols::{Language, language_override};
+use crate::tools::{
+    DEFAULT_IGNORED_DIRS, allow_entry_ignoring, base_dir, is_read_only, resolve_path, state_file,
+    writable_state_file,
+};
+
+const SERENAIGNORE: &str = ".serenaignore";
+
+/// File names most likely to show a project's entry points and style, best
+/// first; `include_excerpts` samples these.
+const SIGNIFICANT_FILES: &[&str] = &[
+    "main.rs",
+    "lib.rs",
+    "index.ts",
+    "index.tsx",
+    "index.js",
+    "main.py",
+    "__main__.py",
+    "__init__.py",
+    "main.go",
+    "Main.java",
+    "Program.cs",
+];
+const MAX_EXCERPT_FILES: usize = 5;
+const EXCERPT_LINES: usize = 20;
+/// Budget for all excerpts together, so the summary stays compact.
+const MAX_EXCERPT_BYTES: usize = 6_000;
+
+/// File stems (compared case-insensitively) marking likely entry points
+/// unless `entry_point_names` replaces them.
+const ENTRY_POINT_NAMES: &[&str] = &["main", "__main__", "index", "app", "server", "program"];
+const MAX_ENTRY_POINTS: usize = 8;
+/// Source files read while looking for a `main` function; later files are
+/// only matched by name.
+const MAX_MAIN_SCAN_FILES: usize = 300;
+/// Files onboarding visits unless `max_scan_files` says otherwise.
+const DEFAULT_MAX_SCAN_FILES: usize = 5_000;
+/// Paths listed in `sample_files` unless `sample_file_limit` says otherwise.
+const DEFAULT_SAMPLE_FILE_LIMIT: usize = 12;
+
+/// Directories holding third-party or generated code, left out of
+/// `language_breakdown` unless `include_vendored` is set.
+const VENDORED_DIRS: &[&str] = &["vendor", "vendored", "third_party", "generated"];
+/// Files larger than this are usually data or build artifacts rather than
+/// hand-written code.
+const DEFAULT_MAX_BREAKDOWN_FILE_BYTES: u64 = 1024 * 1024;
+
+/// A `main` function (or Python `__main__` guard) in any supported language.
+static MAIN_FUNCTION_RE: Lazy<Regex> = Lazy::new(|| {
+    Regex::new(
+        r#"(?m)^\s*(?:pub\s+)?(?:async\s+)?fn\s+main\s*\(|^\s*(?:async\s+)?def\s+main\s*\(|^func\s+main\s*\(|\bstatic\s+(?:async\s+)?[\w<>\[\]]+\s+[Mm]ain\s*\(|^if\s+__name__\s*==\s*['"]__main__['"]"#,
+    )
+    .expect("valid main function regex")
+});
 
 pub fn register(registry: &mut ToolRegistry) {
     registry.register(onboarding_tool());
     registry.register(prepare_for_new_conversation_tool());
     registry.register(check_onboarding_performed_tool());
+    registry.register(language_breakdown_tool());
 }
 
 #[derive(Default, Serialize, Deserialize)]
@@ -39,6 +98,60 @@ struct ProjectSummary {
     sample_files: Vec<String>,
     todo_count: usize,
     readme_excerpt: Option<String>,
+    /// Opening lines of the most significant files; present only when
+    /// requested with `include_excerpts`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    excerpts: Option<Vec<FileExcerpt>>,
+    /// File and directory names skipped during the scan.
+    #[serde(default)]
+    ignored: Vec<String>,
+    /// Likely entry points, shallowest first.
+    #[serde(default)]
+    entry_points: Vec<EntryPoint>,
+    /// File stems the scan treated as entry points.
+    #[serde(default)]
+    entry_point_names: Vec<String>,
+    #[serde(default = "default_max_scan_files")]
+    max_scan_files: usize,
+    #[serde(default = "default_sample_file_limit")]
+    sample_file_limit: usize,
+    /// When the scan stopped at `max_scan_files`: the directories it was
+    /// inside, outermost first, which it only partly covered.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    partially_scanned: Vec<String>,
+    /// When the scan stopped at `max_scan_files`: top-level directories it
+    /// never reached.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    unscanned_directories: Vec<String>,
+}
+
+/// How much of the tree onboarding scans and lists.
+#[derive(Clone, Copy, PartialEq, Eq)]
+struct ScanLimits {
+    max_files: usize,
+    sample_files: usize,
+}
+
+impl ScanLimits {
+    fn new(max_scan_files: Option<usize>, sample_file_limit: Option<usize>) -> Self {
+        Self {
+            max_files: max_scan_files.unwrap_or(DEFAULT_MAX_SCAN_FILES),
+            sample_files: sample_file_limit.unwrap_or(DEFAULT_SAMPLE_FILE_LIMIT),
+        }
+    }
+
+    /// Whether `summary` was scanned with these limits.
+    fn matches(&self, summary: &ProjectSummary) -> bool {
+        summary.max_scan_files == self.max_files && summary.sample_file_limit == self.sample_files
+    }
+}
+
+#[derive(Serialize, Deserialize, Clone)]
+struct EntryPoint {
+    path: String,
+    /// `name` when the file stem matched, `main_function` when the file
+    /// defines a `main` function.
+    reason: String,
 }
 
 #[derive(Serialize, Deserialize, Clone)]
@@ -48,6 +161,14 @@ struct DirectorySummary {
     sample_files: Vec<String>,
 }
 
+#[derive(Serialize, Deserialize, Clone)]
+struct FileExcerpt {
+    path: String,
+    content: String,
+    /// Whether the file continues past the excerpt.
+    truncated: bool,
+}
+
 #[derive(Serialize, Deserialize, Clone)]
 struct LanguageSummary {
     language: String,
@@ -77,8 +198,48 @@ fn onboarding_tool() -> Tool {
                 "type": "boolean",
                 "description": "Force regeneration even if cached",
                 "default": false,
+            },
+            "extra_ignore": {
+                "type": "array",
+                "items": {"type": "string"},
+                "description": "Additional file or directory names to skip, merged with the defaults",
+            },
+            "use_serenaignore": {
+                "type": "boolean",
+                "description": "Also skip names listed one per line in .serenaignore at the project root",
+                "default": true,
+            },
+            "include_excerpts": {
+                "type": "boolean",
+                "description": "Include the first lines of a few significant files (main.rs, lib.rs, index.ts, __init__.py, ...) to show entry points and code style",
+                "default": false,
+            },
+            "entry_point_names": {
+                "type": "array",
+                "items": {"type": "string", "minLength": 1},
+                "description": "File stems treated as entry points, replacing the defaults (main, __main__, index, app, server, program); files defining a main function always count",
+            },
+            "max_scan_files": {
+                "type": "integer",
+                "minimum": 1,
+                "description": "Stop scanning after this many files (default 5000). A truncated summary lists the directories it covered only partly in `partially_scanned` and those it never reached in `unscanned_directories`",
+            },
+            "sample_file_limit": {
+                "type": "integer",
+                "minimum": 0,
+                "description": "Number of paths listed in `sample_files` (default 12)",
             }
         },
+        "examples": [
+            {"project_root": ".", "extra_ignore": ["coverage"]},
+            {"include_excerpts": true},
+            {"max_scan_files": 50000, "sample_file_limit": 30, "refresh": true}
+        ],
+        "x-positional-order": [
+            "project_root", "max_directories", "max_languages", "refresh", "extra_ignore",
+            "use_serenaignore", "include_excerpts", "entry_point_names", "max_scan_files",
+            "sample_file_limit"
+        ],
         "additionalProperties": false
     });
 
@@ -92,6 +253,18 @@ fn onboarding_tool() -> Tool {
         max_languages: Option<usize>,
         #[serde(default)]
         refresh: Option<bool>,
+        #[serde(default)]
+        extra_ignore: Option<Vec<String>>,
+        #[serde(default = "default_true")]
+        use_serenaignore: bool,
+        #[serde(default)]
+        include_excerpts: bool,
+        #[serde(default)]
+        entry_point_names: Option<Vec<String>>,
+        #[serde(default)]
+        max_scan_files: Option<usize>,
+        #[serde(default)]
+        sample_file_limit: Option<usize>,
     }
 
     let handler = move |params| -> Result<Value> {
@@ -99,7 +272,7 @@ fn onboarding_tool() -> Tool {
             serde_json::from_value(params).context("Invalid arguments for onboarding_tool")?;
         let root = match &args.project_root {
             Some(path) => resolve_path(path)?,
-            None => std::env::current_dir()?,
+            None => base_dir()?,
         };
 
         if !root.is_dir() {
@@ -109,12 +282,26 @@ fn onboarding_tool() -> Tool {
         let max_directories = args.max_directories.unwrap_or(6);
         let max_languages = args.max_languages.unwrap_or(6);
         let force_refresh = args.refresh.unwrap_or(false);
+        let extra_ignore =
+            extra_ignore_names(&root, args.extra_ignore.as_deref(), args.use_serenaignore)?;
+        let entry_point_names = entry_point_names(args.entry_point_names.as_deref());
+        let limits = ScanLimits::new(args.max_scan_files, args.sample_file_limit);
 
         let mut state = load_state()?;
         let key = root.to_string_lossy().to_string();
 
+        // A summary scanned with a different ignore set, entry point names
+        // or scan limits, or with excerpts when they were not requested (or
+        // the reverse), is stale.
         let summary = if !force_refresh {
-            state.projects.get(&key).cloned()
+            state
+                .projects
+                .get(&key)
+                .filter(|stored| stored.summary.ignored == effective_ignore_set(&extra_ignore))
+                .filter(|stored| stored.summary.excerpts.is_some() == args.include_excerpts)
+                .filter(|stored| stored.summary.entry_point_names == entry_point_names)
+                .filter(|stored| limits.matches(&stored.summary))
+                .cloned()
         } else {
             None
         };
@@ -122,13 +309,23 @@ fn onboarding_tool() -> Tool {
         let (summary, cache_state) = if let Some(stored) = summary {
             (stored, "cached")
         } else {
-            let summary = collect_project_summary(&root, max_directories, max_languages)?;
+            let summary = collect_project_summary(
+                &root,
+                max_directories,
+                max_languages,
+                &extra_ignore,
+                &entry_point_names,
+                args.include_excerpts,
+                limits,
+            )?;
             let stored = StoredSummary {
                 updated_at: now_string(),
                 summary: summary.clone(),
             };
             state.projects.insert(key.clone(), stored.clone());
-            save_state(&state)?;
+            if !is_read_only() {
+                save_state(&state)?;
+            }
             (stored, "fresh")
         };
 
@@ -146,6 +343,7 @@ fn onboarding_tool() -> Tool {
         schema,
         Box::new(handler),
     )
+    .resolves_paths()
 }
 
 fn prepare_for_new_conversation_tool() -> Tool {
@@ -154,8 +352,18 @@ fn prepare_for_new_conversation_tool() -> Tool {
         "properties": {
             "project_root": {"type": "string"},
             "max_directories": {"type": "integer", "minimum": 1},
-            "max_languages": {"type": "integer", "minimum": 1}
+            "max_languages": {"type": "integer", "minimum": 1},
+            "extra_ignore": {"type": "array", "items": {"type": "string"}},
+            "use_serenaignore": {"type": "boolean", "default": true},
+            "entry_point_names": {"type": "array", "items": {"type": "string", "minLength": 1}},
+            "max_scan_files": {"type": "integer", "minimum": 1},
+            "sample_file_limit": {"type": "integer", "minimum": 0}
         },
+        "examples": [{"max_directories": 4}],
+        "x-positional-order": [
+            "project_root", "max_directories", "max_languages", "extra_ignore", "use_serenaignore",
+            "entry_point_names", "max_scan_files", "sample_file_limit"
+        ],
         "additionalProperties": false
     });
 
@@ -167,6 +375,16 @@ fn prepare_for_new_conversation_tool() -> Tool {
         max_directories: Option<usize>,
         #[serde(default)]
         max_languages: Option<usize>,
+        #[serde(default)]
+        extra_ignore: Option<Vec<String>>,
+        #[serde(default = "default_true")]
+        use_serenaignore: bool,
+        #[serde(default)]
+        entry_point_names: Option<Vec<String>>,
+        #[serde(default)]
+        max_scan_files: Option<usize>,
+        #[serde(default)]
+        sample_file_limit: Option<usize>,
     }
 
     let handler = move |params| -> Result<Value> {
@@ -174,7 +392,7 @@ fn prepare_for_new_conversation_tool() -> Tool {
             .context("Invalid arguments for prepare_for_new_conversation")?;
         let root = match &args.project_root {
             Some(path) => resolve_path(path)?,
-            None => std::env::current_dir()?,
+            None => base_dir()?,
         };
 
         if !root.is_dir() {
@@ -183,19 +401,39 @@ fn prepare_for_new_conversation_tool() -> Tool {
 
         let max_directories = args.max_directories.unwrap_or(6);
         let max_languages = args.max_languages.unwrap_or(6);
+        let extra_ignore =
+            extra_ignore_names(&root, args.extra_ignore.as_deref(), args.use_serenaignore)?;
+        let entry_point_names = entry_point_names(args.entry_point_names.as_deref());
+        let limits = ScanLimits::new(args.max_scan_files, args.sample_file_limit);
 
         let mut state = load_state()?;
         let key = root.to_string_lossy().to_string();
-        let summary = if let Some(stored) = state.projects.get(&key) {
+        let cached = state
+            .projects
+            .get(&key)
+            .filter(|stored| stored.summary.ignored == effective_ignore_set(&extra_ignore))
+            .filter(|stored| stored.summary.entry_point_names == entry_point_names)
+            .filter(|stored| limits.matches(&stored.summary));
+        let summary = if let Some(stored) = cached {
             stored.summary.clone()
         } else {
-            let summary = collect_project_summary(&root, max_directories, max_languages)?;
+            let summary = collect_project_summary(
+                &root,
+                max_directories,
+                max_languages,
+                &extra_ignore,
+                &entry_point_names,
+                false,
+                limits,
+            )?;
             let stored = StoredSummary {
                 updated_at: now_string(),
                 summary: summary.clone(),
             };
             state.projects.insert(key.clone(), stored.clone());
-            save_state(&state)?;
+            if !is_read_only() {
+                save_state(&state)?;
+            }
             summary
         };
 
@@ -214,6 +452,7 @@ fn prepare_for_new_conversation_tool() -> Tool {
         schema,
         Box::new(handler),
     )
+    .resolves_paths()
 }
 
 fn check_onboarding_performed_tool() -> Tool {
@@ -223,6 +462,8 @@ fn check_onboarding_performed_tool() -> Tool {
             "project_root": {"type": "string"}
         },
         "required": ["project_root"],
+        "examples": [{"project_root": "."}],
+        "x-positional-order": ["project_root"],
         "additionalProperties": false
     });
 
@@ -258,42 +499,336 @@ fn check_onboarding_performed_tool() -> Tool {
         schema,
         Box::new(handler),
     )
+    .resolves_paths()
+}
+
+fn language_breakdown_tool() -> Tool {
+    let schema = json!({
+        "type": "object",
+        "properties": {
+            "project_root": {
+                "type": "string",
+                "description": "Project directory to analyse. Defaults to current working directory.",
+            },
+            "extra_ignore": {
+                "type": "array",
+                "items": {"type": "string"},
+                "description": "Additional file or directory names to skip, merged with the defaults",
+            },
+            "use_serenaignore": {
+                "type": "boolean",
+                "description": "Also skip names listed one per line in .serenaignore at the project root",
+                "default": true,
+            },
+            "include_vendored": {
+                "type": "boolean",
+                "description": "Count vendor, vendored, third_party and generated directories too",
+                "default": false,
+            },
+            "max_file_bytes": {
+                "type": "integer",
+                "minimum": 1,
+                "description": "Skip files larger than this many bytes (default 1 MiB)",
+            },
+            "max_languages": {
+                "type": "integer",
+                "minimum": 1,
+                "description": "Limit number of languages listed; the rest are summed into `other_bytes`",
+            }
+        },
+        "examples": [
+            {"project_root": "."},
+            {"extra_ignore": ["fixtures"], "max_file_bytes": 262144, "max_languages": 5}
+        ],
+        "x-positional-order": [
+            "project_root", "extra_ignore", "use_serenaignore", "include_vendored",
+            "max_file_bytes", "max_languages"
+        ],
+        "additionalProperties": false
+    });
+
+    #[derive(Deserialize)]
+    struct Params {
+        #[serde(default)]
+        project_root: Option<String>,
+        #[serde(default)]
+        extra_ignore: Option<Vec<String>>,
+        #[serde(default = "default_true")]
+        use_serenaignore: bool,
+        #[serde(default)]
+        include_vendored: bool,
+        #[serde(default)]
+        max_file_bytes: Option<u64>,
+        #[serde(default)]
+        max_languages: Option<usize>,
+    }
+
+    #[derive(Default)]
+    struct LanguageBytes {
+        bytes: u64,
+        files: usize,
+    }
+
+    let handler = move |params| -> Result<Value> {
+        let args: Params =
+            serde_json::from_value(params).context("Invalid arguments for language_breakdown")?;
+        let root = match &args.project_root {
+            Some(path) => resolve_path(path)?,
+            None => base_dir()?,
+        };
+
+        if !root.is_dir() {
+            anyhow::bail!("{} is not a directory", root.display());
+        }
+
+        let max_file_bytes = args
+            .max_file_bytes
+            .unwrap_or(DEFAULT_MAX_BREAKDOWN_FILE_BYTES);
+        let mut extra_ignore =
+            extra_ignore_names(&root, args.extra_ignore.as_deref(), args.use_serenaignore)?;
+        if !args.include_vendored {
+            extra_ignore.extend(VENDORED_DIRS.iter().map(|name| name.to_string()));
+        }
+
+        let mut by_language: HashMap<String, LanguageBytes> = HashMap::new();
+        let mut files_scanned = 0usize;
+        let mut skipped_large = 0usize;
+        let mut unclassified = 0usize;
+        let mut unclassified_bytes = 0u64;
+        let mut progress = ProgressReporter::new("language_breakdown");
+
+        let walker = WalkDir::new(&root)
+            .follow_links(false)
+            .into_iter()
+            .filter_entry(|entry| allow_entry_ignoring(entry, &extra_ignore));
+        for entry in walker.filter_map(|e| e.ok()) {
+            if !entry.file_type().is_file() {
+                continue;
+            }
+            files_scanned += 1;
+            cancellation::checkpoint()?;
+            progress.file_visited();
+
+            let Ok(metadata) = entry.metadata() else {
+                continue;
+            };
+            if metadata.len() > max_file_bytes {
+                skipped_large += 1;
+                continue;
+            }
+            let language = entry
+                .path()
+                .extension()
+                .and_then(|ext| language_from_extension(&ext.to_string_lossy().to_lowercase()));
+            let Some(language) = language else {
+                unclassified += 1;
+                unclassified_bytes += metadata.len();
+                continue;
+            };
+            let stats = by_language.entry(language).or_default();
+            stats.bytes += metadata.len();
+            stats.files += 1;
+        }
+
+        let total_bytes = by_language.values().map(|stats| stats.bytes).sum::<u64>();
+        let mut languages = by_language.into_iter().collect::<Vec<_>>();
+        languages
+            .sort_by(|(a_name, a), (b_name, b)| b.bytes.cmp(&a.bytes).then(a_name.cmp(b_name)));
+        let listed = args.max_languages.unwrap_or(languages.len());
+        let other_bytes = languages
+            .iter()
+            .skip(listed)
+            .map(|(_, stats)| stats.bytes)
+            .sum::<u64>();
+        let percentage = |bytes: u64| {
+            if total_bytes == 0 {
+                0.0
+            } else {
+                (bytes as f64 * 10_000.0 / total_bytes as f64).round() / 100.0
+            }
+        };
+        let breakdown = languages
+            .iter()
+            .take(listed)
+            .map(|(language, stats)| {
+                json!({
+                    "language": language,
+                    "bytes": stats.bytes,
+                    "files": stats.files,
+                    "percentage": percentage(stats.bytes),
+                })
+            })
+            .collect::<Vec<_>>();
+
+        Ok(json!({
+            "project_root": root.to_string_lossy(),
+            "total_bytes": total_bytes,
+            "languages": breakdown,
+            "other_bytes": other_bytes,
+            "files_scanned": files_scanned,
+            "skipped_large_files": skipped_large,
+            "max_file_bytes": max_file_bytes,
+            "unclassified_files": unclassified,
+            "unclassified_bytes": unclassified_bytes,
+            "ignored": effective_ignore_set(&extra_ignore),
+        }))
+    };
+
+    Tool::new(
+        "language_breakdown",
+        "Sum file sizes per language across the project, largest first, skipping ignored, vendored and oversized files",
+        schema,
+        Box::new(handler),
+    )
+    .resolves_paths()
+    .cacheable()
+}
+
+/// Project-specific names to skip on top of [`DEFAULT_IGNORED_DIRS`]:
+/// `extra` plus, when enabled, each non-comment line of `.serenaignore` in
+/// `root`.
+fn extra_ignore_names(
+    root: &Path,
+    extra: Option<&[String]>,
+    use_serenaignore: bool,
+) -> Result<Vec<String>> {
+    let mut names = extra.unwrap_or_default().to_vec();
+
+    let ignore_file = root.join(SERENAIGNORE);
+    if use_serenaignore && ignore_file.is_file() {
+        let content = fs::read_to_string(&ignore_file)
+            .with_context(|| format!("Failed to read {}", ignore_file.display()))?;
+        names.extend(
+            content
+                .lines()
+                .map(str::trim)
+                .filter(|line| !line.starts_with('#'))
+                .map(str::to_string),
+        );
+    }
+
+    let mut names = names
+        .into_iter()
+        .map(|name| name.trim().trim_end_matches('/').to_string())
+        .filter(|name| !name.is_empty())
+        .collect::<Vec<_>>();
+    names.sort();
+    names.dedup();
+    Ok(names)
+}
+
+/// Defaults merged with `extra`, sorted for reporting and cache comparison.
+fn effective_ignore_set(extra: &[String]) -> Vec<String> {
+    let mut ignored = DEFAULT_IGNORED_DIRS
+        .iter()
+        .map(|name| name.to_string())
+        .chain(extra.iter().cloned())
+        .collect::<Vec<_>>();
+    ignored.sort();
+    ignored.dedup();
+    ignored
+}
+
+/// `requested` (or the defaults) lowercased, sorted and deduplicated, for
+/// matching and cache comparison.
+fn entry_point_names(requested: Option<&[String]>) -> Vec<String> {
+    let mut names = match requested {
+        Some(names) => names
+            .iter()
+            .map(|name| name.trim().to_lowercase())
+            .filter(|name| !name.is_empty())
+            .collect::<Vec<_>>(),
+        None => ENTRY_POINT_NAMES
+            .iter()
+            .map(|name| name.to_string())
+            .collect(),
+    };
+    names.sort();
+    names.dedup();
+    names
 }
 
 fn collect_project_summary(
     root: &Path,
     max_directories: usize,
     max_languages: usize,
+    extra_ignore: &[String],
+    entry_point_names: &[String],
+    include_excerpts: bool,
+    limits: ScanLimits,
 ) -> Result<ProjectSummary> {
-    const MAX_SCAN_FILES: usize = 5_000;
-    const MAX_SAMPLE_FILES: usize = 12;
-
     let mut files_scanned = 0usize;
     let mut scan_truncated = false;
     let mut dir_stats: HashMap<String, DirStats> = HashMap::new();
     let mut language_stats: HashMap<String, usize> = HashMap::new();
     let mut sample_files = Vec::new();
     let mut todo_count = 0usize;
+    // `(rank in SIGNIFICANT_FILES, depth, path)` of excerpt candidates.
+    let mut significant = Vec::new();
+    // `(depth, path, reason)` of likely entry points.
+    let mut entry_points = Vec::new();
+    let mut main_scanned = 0usize;
+    // Top-level directories the walk entered, to tell which it never
+    // reached when the scan is cut short.
+    let mut entered = HashSet::new();
+    let mut partially_scanned = Vec::new();
+    let mut progress = ProgressReporter::new("onboarding");
 
     let walker = WalkDir::new(root)
         .follow_links(false)
         .max_depth(6)
         .into_iter()
-        .filter_entry(|entry| allow_entry(entry));
+        .filter_entry(|entry| allow_entry_ignoring(entry, extra_ignore));
 
     for entry in walker.filter_map(|e| e.ok()) {
         if !entry.file_type().is_file() {
+            if entry.depth() == 1 {
+                entered.insert(entry.file_name().to_os_string());
+            }
             continue;
         }
 
         files_scanned += 1;
-        if files_scanned > MAX_SCAN_FILES {
+        if files_scanned > limits.max_files {
             scan_truncated = true;
+            // The walk is depth-first, so every directory around the first
+            // file left out still had entries to go.
+            if let Ok(relative) = entry.path().strip_prefix(root) {
+                partially_scanned = relative
+                    .ancestors()
+                    .skip(1)
+                    .filter(|dir| !dir.as_os_str().is_empty())
+                    .map(|dir| dir.to_string_lossy().to_string())
+                    .collect();
+                partially_scanned.reverse();
+            }
             break;
         }
+        cancellation::checkpoint()?;
+        progress.file_visited();
 
         if let Ok(relative) = entry.path().strip_prefix(root) {
-            if sample_files.len() < MAX_SAMPLE_FILES {
+            if include_excerpts
+                && let Some(rank) = SIGNIFICANT_FILES
+                    .iter()
+                    .position(|name| entry.file_name() == *name)
+            {
+                significant.push((rank, entry.depth(), relative.to_path_buf()));
+            }
+
+            let reason = if is_entry_point_name(entry.path(), entry_point_names) {
+                Some("name")
+            } else if main_scanned < MAX_MAIN_SCAN_FILES && is_source_file(entry.path()) {
+                main_scanned += 1;
+                defines_main(entry.path()).then_some("main_function")
+            } else {
+                None
+            };
+            if let Some(reason) = reason {
+                entry_points.push((entry.depth(), relative.to_path_buf(), reason));
+            }
+
+            if sample_files.len() < limits.sample_files {
                 sample_files.push(relative.to_string_lossy().to_string());
             }
 
@@ -306,7 +841,7 @@ fn collect_project_summary(
                 })
                 .unwrap_or_else(|| String::from("<root>"));
 
-            let stats = dir_stats.entry(top).or_insert_with(DirStats::default);
+            let stats = dir_stats.entry(top).or_default();
             stats.file_count += 1;
             if stats.sample_files.len() < 3 {
                 stats
@@ -333,21 +868,43 @@ fn collect_project_summary(
             sample_files: stats.sample_files,
         })
         .collect::<Vec<_>>();
-    directories.sort_by(|a, b| b.file_count.cmp(&a.file_count));
+    directories.sort_by_key(|d| std::cmp::Reverse(d.file_count));
     directories.truncate(max_directories);
 
     let mut languages = language_stats
         .into_iter()
-        .map(|(ext, count)| LanguageSummary {
-            language: language_from_extension(&ext),
-            extension: ext,
-            files: count,
+        .filter_map(|(ext, count)| {
+            Some(LanguageSummary {
+                language: language_from_extension(&ext)?,
+                extension: ext,
+                files: count,
+            })
         })
         .collect::<Vec<_>>();
-    languages.sort_by(|a, b| b.files.cmp(&a.files));
+    languages.sort_by_key(|l| std::cmp::Reverse(l.files));
     languages.truncate(max_languages);
 
+    entry_points.sort();
+    let entry_points = entry_points
+        .into_iter()
+        .take(MAX_ENTRY_POINTS)
+        .map(|(_, path, reason)| EntryPoint {
+            path: path.to_string_lossy().to_string(),
+            reason: reason.to_string(),
+        })
+        .collect();
+
+    let unscanned_directories = if scan_truncated {
+        unreached_directories(root, extra_ignore, &entered)?
+    } else {
+        Vec::new()
+    };
+
     let readme_excerpt = read_readme_excerpt(root)?;
+    let excerpts = include_excerpts.then(|| {
+        significant.sort();
+        collect_excerpts(root, &significant)
+    });
 
     Ok(ProjectSummary {
         root: root.to_string_lossy().to_string(),
@@ -359,39 +916,66 @@ fn collect_project_summary(
         sample_files,
         todo_count,
         readme_excerpt,
+        excerpts,
+        ignored: effective_ignore_set(extra_ignore),
+        entry_points,
+        entry_point_names: entry_point_names.to_vec(),
+        max_scan_files: limits.max_files,
+        sample_file_limit: limits.sample_files,
+        partially_scanned,
+        unscanned_directories,
     })
 }
 
+/// Top-level directories of `root` the scan would have walked but did not
+/// enter, sorted.
+fn unreached_directories(
+    root: &Path,
+    extra_ignore: &[String],
+    entered: &HashSet<OsString>,
+) -> Result<Vec<String>> {
+    let mut unreached = Vec::new();
+    for entry in WalkDir::new(root)
+        .min_depth(1)
+        .max_depth(1)
+        .into_iter()
+        .filter_entry(|entry| allow_entry_ignoring(entry, extra_ignore))
+    {
+        let entry = entry.with_context(|| format!("Failed to list {}", root.display()))?;
+        if entry.file_type().is_dir() && !entered.contains(entry.file_name()) {
+            unreached.push(entry.file_name().to_string_lossy().to_string());
+        }
+    }
+    unreached.sort();
+    Ok(unreached)
+}
+
+fn is_entry_point_name(path: &Path, names: &[String]) -> bool {
+    is_source_file(path)
+        && path
+            .file_stem()
+            .map(|stem| stem.to_string_lossy().to_lowercase())
+            .is_some_and(|stem| names.contains(&stem))
+}
+
+/// Whether `path` is code in a language with symbol support, as opposed to
+/// configuration, markup or an unknown type.
+fn is_source_file(path: &Path) -> bool {
+    Language::from_path(path)
+        .is_some_and(|lang| !matches!(lang, Language::Config | Language::Markup))
+}
+
+fn defines_main(path: &Path) -> bool {
+    let too_large = fs::metadata(path).map_or(true, |meta| meta.len() > 512 * 1024);
+    !too_large && fs::read_to_string(path).is_ok_and(|content| MAIN_FUNCTION_RE.is_match(&content))
+}
+
 #[derive(Default)]
 struct DirStats {
     file_count: usize,
     sample_files: Vec<String>,
 }
 
-fn allow_entry(entry: &DirEntry) -> bool {
-    if let Some(name) = entry.file_name().to_str() {
-        const IGNORED: [&str; 9] = [
-            ".git",
-            "target",
-            "node_modules",
-            "venv",
-            ".venv",
-            "dist",
-            "build",
-            ".pytest_cache",
-            "__pycache__",
-        ];
-
-        if entry.file_type().is_dir() && IGNORED.iter().any(|&skip| skip == name) {
-            return false;
-        }
-        if name.starts_with('.') && entry.file_type().is_dir() {
-            return false;
-        }
-    }
-    true
-}
-
 fn count_todo_markers(path: &Path) -> Result<usize> {
     let metadata = fs::metadata(path)?;
     if metadata.len() > 512 * 1024 {
@@ -422,8 +1006,12 @@ fn read_readme_excerpt(root: &Path) -> Result<Option<String>> {
             let mut content = fs::read_to_string(&path)
                 .with_context(|| format!("Failed to read {}", path.display()))?;
             if content.len() > MAX_BYTES {
-                content.truncate(MAX_BYTES);
-                content.push_str("…");
+                let mut end = MAX_BYTES;
+                while !content.is_char_boundary(end) {
+                    end -= 1;
+                }
+                content.truncate(end);
+                content.push('…');
             }
             return Ok(Some(content));
         }
@@ -432,32 +1020,85 @@ fn read_readme_excerpt(root: &Path) -> Result<Option<String>> {
     Ok(None)
 }
 
-fn language_from_extension(ext: &str) -> String {
-    match ext {
-        "rs" => "Rust".to_string(),
-        "py" => "Python".to_string(),
-        "ts" | "tsx" => "TypeScript".to_string(),
-        "js" | "jsx" | "mjs" | "cjs" => "JavaScript".to_string(),
-        "go" => "Go".to_string(),
-        "java" | "kt" | "kts" => "JVM".to_string(),
-        "cs" => "C#".to_string(),
-        "swift" => "Swift".to_string(),
-        "rb" => "Ruby".to_string(),
-        "php" => "PHP".to_string(),
-        "lua" => "Lua".to_string(),
-        "cpp" | "cc" | "cxx" | "h" | "hpp" => "C/C++".to_string(),
-        "c" => "C".to_string(),
-        "dart" => "Dart".to_string(),
-        "scala" => "Scala".to_string(),
-        "hs" => "Haskell".to_string(),
-        "ml" | "mli" => "OCaml".to_string(),
-        "ex" | "exs" => "Elixir".to_string(),
-        "zig" => "Zig".to_string(),
-        "sh" => "Shell".to_string(),
-        "json" => "JSON".to_string(),
-        "yml" | "yaml" => "YAML".to_string(),
-        other => other.to_uppercase(),
+/// Opening lines of the best-ranked `candidates` (already sorted), skipping
+/// empty and unreadable files, within [`MAX_EXCERPT_BYTES`] overall.
+fn collect_excerpts(root: &Path, candidates: &[(usize, usize, PathBuf)]) -> Vec<FileExcerpt> {
+    let mut excerpts = Vec::new();
+    let mut budget = MAX_EXCERPT_BYTES;
+
+    for (_, _, relative) in candidates {
+        if excerpts.len() == MAX_EXCERPT_FILES || budget == 0 {
+            break;
+        }
+        let Ok(content) = fs::read_to_string(root.join(relative)) else {
+            continue;
+        };
+        if content.trim().is_empty() {
+            continue;
+        }
+
+        let mut end = content
+            .match_indices('\n')
+            .nth(EXCERPT_LINES - 1)
+            .map_or(content.len(), |(pos, _)| pos + 1);
+        if end > budget {
+            end = budget;
+            while !content.is_char_boundary(end) {
+                end -= 1;
+            }
+        }
+        budget -= end;
+        excerpts.push(FileExcerpt {
+            path: relative.to_string_lossy().to_string(),
+            content: content[..end].to_string(),
+            truncated: end < content.len(),
+        });
+    }
+
+    excerpts
+}
+
+/// Language name for a lowercase extension, or `None` when neither the
+/// table below nor [`Language`] knows it, so assets such as images and lock
+/// files are not reported as languages.
+pub(crate) fn language_from_extension(ext: &str) -> Option<String> {
+    if let Some(name) = language_override(ext) {
+        return Some(
+            Language::from_name(name)
+                .map(|lang| lang.label().to_string())
+                .unwrap_or_else(|| name.to_string()),
+        );
     }
+
+    let name = match ext {
+        "rs" => "Rust",
+        "py" => "Python",
+        "ts" | "tsx" => "TypeScript",
+        "js" | "jsx" | "mjs" | "cjs" => "JavaScript",
+        "go" => "Go",
+        "java" | "kt" | "kts" => "JVM",
+        "cs" => "C#",
+        "swift" => "Swift",
+        "rb" => "Ruby",
+        "php" => "PHP",
+        "lua" => "Lua",
+        "cpp" | "cc" | "cxx" | "h" | "hpp" => "C/C++",
+        "c" => "C",
+        "dart" => "Dart",
+        "scala" => "Scala",
+        "hs" => "Haskell",
+        "ml" | "mli" => "OCaml",
+        "ex" | "exs" => "Elixir",
+        "zig" => "Zig",
+        "sh" => "Shell",
+        "json" => "JSON",
+        "yml" | "yaml" => "YAML",
+        other => {
+            let path = Path::new("file").with_extension(other);
+            return Language::from_path(&path).map(|_| other.to_uppercase());
+        }
+    };
+    Some(name.to_string())
 }
 
 fn build_conversation_suggestions(summary: &ProjectSummary) -> Vec<Value> {
@@ -471,9 +1112,18 @@ fn build_conversation_suggestions(summary: &ProjectSummary) -> Vec<Value> {
     }
 
     if summary.scan_truncated {
+        let mut message = String::from(
+            "Project is large; consider narrowing scope or running targeted symbol searches.",
+        );
+        if !summary.unscanned_directories.is_empty() {
+            message.push_str(&format!(
+                " Not scanned: {}.",
+                summary.unscanned_directories.join(", ")
+            ));
+        }
         suggestions.push(json!({
             "type": "large_project",
-            "message": "Project is large; consider narrowing scope or running targeted symbol searches.",
+            "message": message,
         }));
     }
 
@@ -496,6 +1146,19 @@ fn build_conversation_suggestions(summary: &ProjectSummary) -> Vec<Value> {
         }));
     }
 
+    if !summary.entry_points.is_empty() {
+        let paths: Vec<&str> = summary
+            .entry_points
+            .iter()
+            .map(|entry| entry.path.as_str())
+            .collect();
+        suggestions.push(json!({
+            "type": "entry_points",
+            "message": format!("Likely entry points to start reading from: {}", paths.join(", ")),
+            "paths": paths,
+        }));
+    }
+
     if summary.readme_excerpt.is_none() {
         suggestions.push(json!({
             "type": "documentation",
@@ -524,12 +1187,24 @@ fn load_state() -> Result<WorkflowState> {
 }
 
 fn save_state(state: &WorkflowState) -> Result<()> {
-    let path = state_file("workflow_state.json")?;
+    let path = writable_state_file("workflow_state.json")?;
     let payload = serde_json::to_vec_pretty(state).context("Failed to serialise workflow state")?;
     fs::write(&path, payload)
         .with_context(|| format!("Failed to write workflow state to {}", path.display()))
 }
 
+fn default_max_scan_files() -> usize {
+    DEFAULT_MAX_SCAN_FILES
+}
+
+fn default_sample_file_limit() -> usize {
+    DEFAULT_SAMPLE_FILE_LIMIT
+}
+
+fn default_true() -> bool {
+    true
+}
+
 fn now_string() -> String {
     OffsetDateTime::now_utc()
         .format(&Rfc3339)
@@ -0,0 +1,100 @@
+mod common;
+
+use std::fs;
+
+use serde_json::json;
+
+use common::{call, scratch_file};
+
+const SOURCE: &str = r###"fn tricky() -> usize {
+    // a stray } in a line comment
+    /* and { another } in a block comment */
+    let raw = r#"closing } inside a raw string"#;
+    let plain = r"}";
+    raw.len() + plain.len()
+}
+
+fn after() -> bool {
+    true
+}
+"###;
+
+#[test]
+fn body_extends_past_braces_in_comments_and_raw_strings() {
+    let path = scratch_file("find", "lib.rs", SOURCE);
+    let result = call(
+        "find_symbol",
+        json!({"name": "tricky", "path": path, "match_substring": false, "include_body": true}),
+    );
+
+    let body = result["matches"][0]["body"]
+        .as_str()
+        .expect("body returned");
+    assert!(
+        body.contains("raw.len() + plain.len()"),
+        "body cut short: {body}"
+    );
+    assert!(!body.contains("fn after"), "body overran: {body}");
+}
+
+#[test]
+fn replace_symbol_body_keeps_following_items_intact() {
+    let path = scratch_file("replace", "lib.rs", SOURCE);
+    call(
+        "replace_symbol_body",
+        json!({"path": path, "symbol": "tricky", "new_body": "0"}),
+    );
+
+    let updated = fs::read_to_string(&path).unwrap();
+    assert!(
+        !updated.contains("stray"),
+        "old body left behind:\n{updated}"
+    );
+    assert!(
+        !updated.contains("plain.len()"),
+        "old body left behind:\n{updated}"
+    );
+    assert!(
+        updated.contains("fn after() -> bool {\n    true\n}"),
+        "following function damaged:\n{updated}"
+    );
+}
+
+#[test]
+fn new_body_with_braces_in_comments_and_raw_strings_is_balanced() {
+    let path = scratch_file("balance", "lib.rs", SOURCE);
+    let new_body = "// } closes nothing\n/* { */\nlet s = r#\"}\"#;\ns.len()";
+    call(
+        "replace_symbol_body",
+        json!({"path": path, "symbol": "tricky", "new_body": new_body}),
+    );
+
+    let updated = fs::read_to_string(&path).unwrap();
+    assert!(updated.contains("let s = r#\"}\"#;"), "{updated}");
+    assert!(updated.contains("fn after()"), "{updated}");
+}
+
+const SPACED: &str = "fn first() {}\n\n\n    \nfn second() {\n}\nfn one() -> u32 { 1 }\nfn two() -> u32 {\n    2\n}\n";
+
+#[test]
+fn declaration_after_blank_lines_reports_its_own_line() {
+    let path = scratch_file("blank", "lib.rs", SPACED);
+    let result = call(
+        "find_symbol",
+        json!({"name": "second", "path": path, "match_substring": false}),
+    );
+
+    assert_eq!(result["matches"][0]["line"], 5);
+    assert_eq!(result["matches"][0]["column"], 1);
+}
+
+#[test]
+fn body_opening_on_the_signature_line_is_found() {
+    let path = scratch_file("inline", "lib.rs", SPACED);
+    let result = call(
+        "find_symbol",
+        json!({"name": "one", "path": path, "match_substring": false, "include_body": true}),
+    );
+
+    assert_eq!(result["matches"][0]["body"], " 1 ");
+}
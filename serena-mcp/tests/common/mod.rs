@@ -0,0 +1,25 @@
+// Each test binary compiles this module and uses only part of it.
+#![allow(dead_code)]
+
+use std::fs;
+use std::path::PathBuf;
+
+use serde_json::Value;
+use serena_mcp::tools::build_registry;
+
+/// Write `content` to `file` in a scratch directory of its own for the test
+/// labelled `name`, so tests running in parallel do not share files.
+pub fn scratch_file(name: &str, file: &str, content: &str) -> PathBuf {
+    let dir = std::env::temp_dir().join(format!("serena-test-{}-{name}", std::process::id()));
+    fs::create_dir_all(&dir).unwrap();
+    let path = dir.join(file);
+    fs::write(&path, content).unwrap();
+    path
+}
+
+/// Call `tool` on a fresh registry, panicking with the error chain if it fails.
+pub fn call(tool: &str, args: Value) -> Value {
+    build_registry()
+        .call(tool, args)
+        .unwrap_or_else(|err| panic!("{tool} failed: {err:#}"))
+}
@@ -0,0 +1,34 @@
+use std::fs;
+
+use serde_json::json;
+use serena_mcp::tools::build_registry;
+
+#[test]
+fn copy_onto_an_alias_of_the_source_is_refused() {
+    let dir = std::env::temp_dir().join(format!("serena-copy-{}", std::process::id()));
+    let _ = fs::remove_dir_all(&dir);
+    fs::create_dir_all(dir.join("sub")).unwrap();
+    let source = dir.join("a.txt");
+    fs::write(&source, "keep me\n").unwrap();
+
+    let mut aliases = vec![dir.join("sub/../a.txt")];
+    #[cfg(unix)]
+    {
+        std::os::unix::fs::symlink(&source, dir.join("link.txt")).unwrap();
+        aliases.push(dir.join("link.txt"));
+    }
+
+    for alias in aliases {
+        let err = build_registry()
+            .call(
+                "copy_file",
+                json!({"from": source, "to": alias, "overwrite": true}),
+            )
+            .unwrap_err();
+        assert!(
+            format!("{err:#}").contains("same file"),
+            "unexpected error: {err:#}"
+        );
+        assert_eq!(fs::read_to_string(&source).unwrap(), "keep me\n");
+    }
+}
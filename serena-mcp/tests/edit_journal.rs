@@ -0,0 +1,52 @@
+use std::fs;
+
+use serde_json::{Value, json};
+use serena_mcp::tools::build_registry;
+
+// One test per binary: the state directory is process-wide.
+#[test]
+fn edits_are_journaled() {
+    let dir = std::env::temp_dir().join(format!("serena-journal-{}", std::process::id()));
+    let _ = fs::remove_dir_all(&dir);
+    fs::create_dir_all(&dir).unwrap();
+    unsafe { std::env::set_var("SERENA_STATE_DIR", dir.join("state")) };
+    let path = dir.join("lib.rs");
+    fs::write(
+        &path,
+        "fn parse(input: &str) -> usize {\n    input.len()\n}\n",
+    )
+    .unwrap();
+
+    let registry = build_registry();
+    let edits = [
+        (
+            "replace_span",
+            json!({"path": path, "start_line": 2, "start_col": 5, "end_line": 2, "end_col": 10, "text": "text"}),
+        ),
+        (
+            "insert_text",
+            json!({"path": path, "content": "// Parses input.", "before_symbol": "parse"}),
+        ),
+        (
+            "edit_symbol_signature",
+            json!({"path": path, "symbol": "parse", "new_signature": "pub fn parse(text: &str) -> usize"}),
+        ),
+    ];
+    for (tool, args) in &edits {
+        registry.call(tool, args.clone()).unwrap();
+    }
+
+    let journal = registry
+        .call("recent_edits", json!({"path": path}))
+        .unwrap();
+    let mut tools: Vec<&str> = journal["edits"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|edit: &Value| edit["tool"].as_str().unwrap())
+        .collect();
+    tools.sort();
+    let mut expected: Vec<&str> = edits.iter().map(|(tool, _)| *tool).collect();
+    expected.sort();
+    assert_eq!(tools, expected, "{journal}");
+}
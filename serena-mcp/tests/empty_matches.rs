@@ -0,0 +1,43 @@
+mod common;
+
+use std::fs;
+
+use serde_json::{Value, json};
+use serena_mcp::tools::build_registry;
+
+use common::scratch_file;
+
+fn assert_refused(tool: &str, args: Value) {
+    let err = build_registry().call(tool, args).unwrap_err();
+    assert!(
+        format!("{err:#}").contains("matches the empty string"),
+        "{tool}: unexpected error: {err:#}"
+    );
+}
+
+#[test]
+fn replace_in_files_refuses_empty_matches() {
+    let path = scratch_file("replace", "replace.txt", "abc\n");
+
+    assert_refused(
+        "replace_in_files",
+        json!({"paths": [path], "pattern": "x*", "replacement": "y", "regex": true}),
+    );
+
+    assert_eq!(fs::read_to_string(&path).unwrap(), "abc\n");
+}
+
+#[test]
+fn replace_in_symbol_refuses_empty_matches() {
+    let source = "fn parse() -> u32 {\n    1\n}\n";
+    let path = scratch_file("symbol", "symbol.rs", source);
+
+    for pattern in ["x*", "\\s*"] {
+        assert_refused(
+            "replace_in_symbol",
+            json!({"path": path, "symbol": "parse", "pattern": pattern, "replacement": "y"}),
+        );
+    }
+
+    assert_eq!(fs::read_to_string(&path).unwrap(), source);
+}
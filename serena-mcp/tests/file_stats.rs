@@ -0,0 +1,31 @@
+use std::fs;
+
+use serde_json::{Value, json};
+use serena_mcp::tools::build_registry;
+
+fn stats(name: &str, content: &str) -> Value {
+    let dir = std::env::temp_dir().join(format!("serena-stats-{}", std::process::id()));
+    fs::create_dir_all(&dir).unwrap();
+    let path = dir.join(name);
+    fs::write(&path, content).unwrap();
+    build_registry()
+        .call("file_stats", json!({"path": path}))
+        .unwrap()["stats"]
+        .clone()
+}
+
+#[test]
+fn comment_markers_follow_the_file_type_and_skip_strings() {
+    let ruby = stats(
+        "tool.rb",
+        "# comment\nx = \"# not a comment\"\n\nputs x # trailing\n",
+    );
+    assert_eq!(ruby["comment_lines"], 1);
+    assert_eq!(ruby["code_lines"], 2);
+    assert_eq!(ruby["blank_lines"], 1);
+
+    let php = stats("page.php", "<?php\n# hash\n// slashes\n/*\n\n*/\necho 1;\n");
+    assert_eq!(php["comment_lines"], 5);
+    assert_eq!(php["code_lines"], 2);
+    assert_eq!(php["blank_lines"], 0);
+}
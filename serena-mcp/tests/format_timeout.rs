@@ -0,0 +1,54 @@
+#![cfg(unix)]
+
+use std::fs;
+use std::os::unix::fs::PermissionsExt;
+use std::time::{Duration, Instant};
+
+use serde_json::json;
+use serena_mcp::tools::build_registry;
+
+// One test per binary: PATH and the timeout are process-wide.
+#[test]
+fn hanging_formatter_is_killed_after_the_timeout() {
+    let dir = std::env::temp_dir().join(format!("serena-format-{}", std::process::id()));
+    let _ = fs::remove_dir_all(&dir);
+    fs::create_dir_all(dir.join("bin")).unwrap();
+    let rustfmt = dir.join("bin/rustfmt");
+    // The shell's own child keeps the pipes open after the shell is killed.
+    fs::write(&rustfmt, "#!/bin/sh\nsleep 30\n").unwrap();
+    fs::set_permissions(&rustfmt, fs::Permissions::from_mode(0o755)).unwrap();
+    fs::write(dir.join("lib.rs"), "fn parse() {}\n").unwrap();
+    let path = std::env::join_paths(
+        std::iter::once(dir.join("bin"))
+            .chain(std::env::split_paths(&std::env::var_os("PATH").unwrap())),
+    )
+    .unwrap();
+    unsafe {
+        std::env::set_var("PATH", path);
+        std::env::set_var("SERENA_FORMAT_TIMEOUT_SECS", "1");
+    }
+
+    let started = Instant::now();
+    let response = build_registry()
+        .call(
+            "replace_symbol_body",
+            json!({
+                "path": dir.join("lib.rs"),
+                "symbol": "parse",
+                "new_body": "fn parse() { }",
+                "format": true,
+                "dry_run": true,
+            }),
+        )
+        .unwrap();
+
+    assert!(started.elapsed() < Duration::from_secs(10), "{response}");
+    assert_eq!(response["format"]["status"], "failed", "{response}");
+    assert!(
+        response["format"]["message"]
+            .as_str()
+            .unwrap()
+            .contains("killed"),
+        "{response}"
+    );
+}
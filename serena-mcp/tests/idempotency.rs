@@ -0,0 +1,49 @@
+use std::fs;
+
+use serde_json::json;
+use serena_mcp::tools::build_registry;
+
+#[test]
+fn reused_key_replays_same_arguments_and_rejects_different_ones() {
+    let dir = std::env::temp_dir().join(format!("serena-idempotency-{}", std::process::id()));
+    let _ = fs::remove_dir_all(&dir);
+    fs::create_dir_all(&dir).unwrap();
+    // The only test in this binary, so no other thread reads the variable.
+    unsafe { std::env::set_var("SERENA_STATE_DIR", dir.join("state")) };
+    let target = dir.join("a.txt");
+    let registry = build_registry();
+
+    let first = registry
+        .call(
+            "write_file",
+            json!({"path": target, "content": "one", "idempotency_key": "k1"}),
+        )
+        .unwrap();
+    fs::write(&target, "changed").unwrap();
+    let replayed = registry
+        .call(
+            "write_file",
+            json!({"content": "one", "idempotency_key": "k1", "path": target}),
+        )
+        .unwrap();
+    assert_eq!(first, replayed);
+    assert_eq!(fs::read_to_string(&target).unwrap(), "changed");
+
+    let err = registry
+        .call(
+            "write_file",
+            json!({"path": target, "content": "two", "idempotency_key": "k1"}),
+        )
+        .unwrap_err();
+    assert!(
+        format!("{err:#}").contains("different arguments"),
+        "unexpected error: {err:#}"
+    );
+    assert_eq!(fs::read_to_string(&target).unwrap(), "changed");
+    let leftovers = fs::read_dir(dir.join("state"))
+        .unwrap()
+        .filter_map(Result::ok)
+        .filter(|entry| entry.file_name().to_string_lossy().ends_with(".tmp"))
+        .count();
+    assert_eq!(leftovers, 0);
+}
@@ -0,0 +1,30 @@
+use std::fs;
+
+use serde_json::json;
+use serena_mcp::tools::build_registry;
+
+#[test]
+fn unknown_extensions_are_unclassified_rather_than_languages() {
+    let dir = std::env::temp_dir().join(format!("serena-breakdown-{}", std::process::id()));
+    let _ = fs::remove_dir_all(&dir);
+    fs::create_dir_all(&dir).unwrap();
+    fs::write(dir.join("main.rs"), "fn main() {}\n").unwrap();
+    fs::write(dir.join("notes.md"), "# Notes\n").unwrap();
+    fs::write(dir.join("logo.png"), [0u8; 100]).unwrap();
+    fs::write(dir.join("Makefile"), "all:\n").unwrap();
+
+    let response = build_registry()
+        .call("language_breakdown", json!({"project_root": dir}))
+        .unwrap();
+
+    let languages: Vec<&str> = response["languages"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|entry| entry["language"].as_str().unwrap())
+        .collect();
+    assert_eq!(languages, ["Rust", "MD"], "{response}");
+    assert_eq!(response["total_bytes"], 13 + 8, "{response}");
+    assert_eq!(response["unclassified_files"], 2, "{response}");
+    assert_eq!(response["unclassified_bytes"], 100 + 5, "{response}");
+}
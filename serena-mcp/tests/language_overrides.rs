@@ -0,0 +1,30 @@
+use std::fs;
+
+use serde_json::json;
+use serena_mcp::tools::build_registry;
+
+// One test per binary: the overrides are read once per process.
+#[test]
+fn overrides_naming_unknown_languages_are_dropped() {
+    let dir = std::env::temp_dir().join(format!("serena-overrides-{}", std::process::id()));
+    let _ = fs::remove_dir_all(&dir);
+    fs::create_dir_all(&dir).unwrap();
+    fs::write(dir.join("lib.rs"), "fn kept() {}\n").unwrap();
+    unsafe {
+        std::env::set_var("SERENA_STATE_DIR", dir.join("state"));
+        std::env::set_var("SERENA_LANG_MAP", "vue=javascript,rs=klingon");
+    }
+
+    let registry = build_registry();
+    let kinds = registry.call("list_symbol_kinds", json!({})).unwrap();
+    assert_eq!(
+        kinds["extension_overrides"],
+        json!({"vue": "javascript"}),
+        "{kinds}"
+    );
+
+    let found = registry
+        .call("find_symbol", json!({"name": "kept", "path": dir}))
+        .unwrap();
+    assert_eq!(found["count"], 1, "{found}");
+}
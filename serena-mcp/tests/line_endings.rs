@@ -0,0 +1,95 @@
+mod common;
+
+use std::fs;
+
+use serde_json::json;
+
+use common::{call, scratch_file};
+
+const CRLF_SOURCE: &str =
+    "fn first() -> u32 {\r\n    1\r\n}\r\n\r\nfn second() -> u32 {\r\n    2\r\n}\r\n";
+
+/// Byte offsets of `\n` not preceded by `\r`.
+fn bare_line_feeds(content: &str) -> Vec<usize> {
+    content
+        .match_indices('\n')
+        .map(|(index, _)| index)
+        .filter(|&index| index == 0 || content.as_bytes()[index - 1] != b'\r')
+        .collect()
+}
+
+#[test]
+fn replace_symbol_body_keeps_crlf_line_endings() {
+    let path = scratch_file("replace", "lib.rs", CRLF_SOURCE);
+    call(
+        "replace_symbol_body",
+        json!({"path": path, "symbol": "first", "new_body": "let x = 10;\nx + 1"}),
+    );
+
+    let updated = fs::read_to_string(&path).unwrap();
+    assert!(updated.contains("let x = 10;\r\n"), "{updated:?}");
+    assert!(updated.contains("fn second()"), "{updated:?}");
+    assert_eq!(
+        bare_line_feeds(&updated),
+        Vec::<usize>::new(),
+        "{updated:?}"
+    );
+}
+
+#[test]
+fn appended_body_and_line_range_edits_keep_crlf_line_endings() {
+    let path = scratch_file("append", "lib.rs", CRLF_SOURCE);
+    call(
+        "replace_symbol_body",
+        json!({"path": path, "symbol": "second", "new_body": "let y = 3;", "mode": "prepend"}),
+    );
+    call(
+        "replace_symbol_body",
+        json!({"path": path, "start_line": 1, "end_line": 3, "new_body": "fn first() -> u32 {\n    5\n}"}),
+    );
+
+    let updated = fs::read_to_string(&path).unwrap();
+    assert!(updated.contains("let y = 3;\r\n"), "{updated:?}");
+    assert!(
+        updated.starts_with("fn first() -> u32 {\r\n    5\r\n}\r\n"),
+        "{updated:?}"
+    );
+    assert_eq!(
+        bare_line_feeds(&updated),
+        Vec::<usize>::new(),
+        "{updated:?}"
+    );
+}
+
+#[test]
+fn explicit_line_ending_overrides_the_detected_one() {
+    let path = scratch_file("explicit", "lib.rs", CRLF_SOURCE);
+    call(
+        "replace_symbol_body",
+        json!({"path": path, "symbol": "first", "new_body": "7", "line_ending": "lf"}),
+    );
+
+    let updated = fs::read_to_string(&path).unwrap();
+    assert!(!bare_line_feeds(&updated).is_empty(), "{updated:?}");
+    assert!(
+        updated.contains("fn second() -> u32 {\r\n    2\r\n}\r\n"),
+        "{updated:?}"
+    );
+}
+
+#[test]
+fn insert_text_keeps_crlf_line_endings() {
+    let path = scratch_file("insert", "lib.rs", CRLF_SOURCE);
+    call(
+        "insert_text",
+        json!({"path": path, "after_symbol": "first", "content": "\nfn middle() {}\n"}),
+    );
+
+    let updated = fs::read_to_string(&path).unwrap();
+    assert!(updated.contains("fn middle() {}\r\n"), "{updated:?}");
+    assert_eq!(
+        bare_line_feeds(&updated),
+        Vec::<usize>::new(),
+        "{updated:?}"
+    );
+}
@@ -0,0 +1,48 @@
+use std::fs;
+
+use serde_json::{Value, json};
+use serena_mcp::tools::build_registry;
+
+// One test per binary: the state directory is process-wide.
+#[test]
+fn null_metadata_value_matches_only_null_or_missing_values() {
+    let state = std::env::temp_dir().join(format!("serena-memory-filter-{}", std::process::id()));
+    let _ = fs::remove_dir_all(&state);
+    unsafe { std::env::set_var("SERENA_STATE_DIR", &state) };
+
+    let registry = build_registry();
+    for (id, metadata) in [
+        ("unowned", json!({"owner": null})),
+        ("owned", json!({"owner": "ops"})),
+        ("untagged", json!({})),
+    ] {
+        registry
+            .call(
+                "write_memory",
+                json!({"id": id, "content": id, "metadata": metadata}),
+            )
+            .unwrap();
+    }
+
+    let ids = |filter: Value| -> Vec<String> {
+        let response = registry.call("read_memory", filter).unwrap();
+        let mut ids: Vec<String> = response["memories"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|memory| memory["id"].as_str().unwrap().to_string())
+            .collect();
+        ids.sort();
+        ids
+    };
+
+    assert_eq!(
+        ids(json!({"metadata_key": "owner", "metadata_value": null})),
+        ["unowned", "untagged"]
+    );
+    assert_eq!(
+        ids(json!({"metadata_key": "owner", "metadata_value": "ops"})),
+        ["owned"]
+    );
+    assert_eq!(ids(json!({"metadata_key": "owner"})), ["owned", "unowned"]);
+}
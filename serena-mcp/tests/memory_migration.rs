@@ -0,0 +1,46 @@
+use std::fs;
+
+use serde_json::{Value, json};
+use serena_mcp::tools::build_registry;
+
+fn entry(id: &str, content: &str, updated_at: &str) -> Value {
+    json!({
+        "id": id,
+        "namespace": "default",
+        "content": content,
+        "created_at": "2026-01-01T00:00:00Z",
+        "updated_at": updated_at,
+    })
+}
+
+#[test]
+fn migration_merges_entries_by_id_keeping_the_newer_copy() {
+    let state = std::env::temp_dir().join(format!("serena-migration-{}", std::process::id()));
+    let _ = fs::remove_dir_all(&state);
+    fs::create_dir_all(state.join("memories")).unwrap();
+    let legacy = json!([
+        entry("stale", "legacy stale", "2026-01-02T00:00:00Z"),
+        entry("fresh", "legacy fresh", "2026-03-01T00:00:00Z"),
+        entry("only-legacy", "legacy only", "2026-01-02T00:00:00Z"),
+    ]);
+    let current = json!([
+        entry("stale", "current stale", "2026-02-01T00:00:00Z"),
+        entry("fresh", "current fresh", "2026-02-01T00:00:00Z"),
+    ]);
+    fs::write(state.join("memories.json"), legacy.to_string()).unwrap();
+    fs::write(state.join("memories/default.json"), current.to_string()).unwrap();
+    // The only test in this binary, so no other thread reads the variable.
+    unsafe { std::env::set_var("SERENA_STATE_DIR", &state) };
+
+    let result = build_registry().call("list_memories", json!({})).unwrap();
+
+    let mut contents = result["memories"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|memory| memory["content"].as_str().unwrap().to_string())
+        .collect::<Vec<_>>();
+    contents.sort();
+    assert_eq!(contents, ["current stale", "legacy fresh", "legacy only"]);
+    assert!(state.join("memories.json.migrated").exists());
+}
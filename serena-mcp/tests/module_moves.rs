@@ -0,0 +1,91 @@
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use serde_json::{Value, json};
+use serena_mcp::tools::build_registry;
+
+fn scratch_project(name: &str, files: &[(&str, &str)]) -> PathBuf {
+    let dir = std::env::temp_dir().join(format!("serena-move-{}-{name}", std::process::id()));
+    let _ = fs::remove_dir_all(&dir);
+    for (rel, content) in files {
+        let path = dir.join(rel);
+        fs::create_dir_all(path.parent().unwrap()).unwrap();
+        fs::write(path, content).unwrap();
+    }
+    dir
+}
+
+fn move_file(root: &Path, from: &str, to: &str) -> anyhow::Result<Value> {
+    build_registry().call(
+        "rename_file_and_update_references",
+        json!({"root": root, "from": root.join(from), "to": root.join(to)}),
+    )
+}
+
+#[test]
+fn python_move_leaves_unrelated_names_alone() {
+    let root = scratch_project(
+        "python",
+        &[
+            ("utils.py", "def helper():\n    return 1\n"),
+            ("app.py", "import utils\n\nprint(utils.helper())\n"),
+            (
+                "other.py",
+                "def run(utils):\n    # utils is a plain argument here\n    return utils + \"utils\"\n",
+            ),
+        ],
+    );
+
+    move_file(&root, "utils.py", "helpers.py").unwrap();
+
+    assert_eq!(
+        fs::read_to_string(root.join("app.py")).unwrap(),
+        "import helpers\n\nprint(helpers.helper())\n"
+    );
+    assert_eq!(
+        fs::read_to_string(root.join("other.py")).unwrap(),
+        "def run(utils):\n    # utils is a plain argument here\n    return utils + \"utils\"\n"
+    );
+}
+
+#[test]
+fn python_move_keeps_locals_in_importing_files() {
+    let root = scratch_project(
+        "python-local",
+        &[
+            ("utils.py", "def helper():\n    return 1\n"),
+            (
+                "app.py",
+                "import utils\n\ndef main():\n    utils_count = utils.helper()\n    name = \"utils\"\n    return utils_count, name\n",
+            ),
+        ],
+    );
+
+    move_file(&root, "utils.py", "helpers.py").unwrap();
+
+    assert_eq!(
+        fs::read_to_string(root.join("app.py")).unwrap(),
+        "import helpers\n\ndef main():\n    utils_count = helpers.helper()\n    name = \"utils\"\n    return utils_count, name\n"
+    );
+}
+
+#[test]
+fn rust_move_to_another_parent_is_refused() {
+    let root = scratch_project(
+        "rust",
+        &[
+            ("src/lib.rs", "mod util;\nmod net;\n"),
+            ("src/util.rs", "pub fn helper() {}\n"),
+            ("src/net/mod.rs", "pub fn connect() {}\n"),
+        ],
+    );
+
+    let err = move_file(&root, "src/util.rs", "src/net/util.rs").unwrap_err();
+
+    assert!(
+        format!("{err:#}").contains("different parent"),
+        "unexpected error: {err:#}"
+    );
+    assert!(root.join("src/util.rs").exists());
+    assert!(!root.join("src/net/util.rs").exists());
+}
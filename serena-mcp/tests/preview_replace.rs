@@ -0,0 +1,53 @@
+use std::fs;
+
+use serde_json::json;
+use serena_mcp::tools::build_registry;
+
+#[test]
+fn patterns_matching_the_empty_string_are_refused() {
+    let dir = std::env::temp_dir().join(format!("serena-preview-{}", std::process::id()));
+    let _ = fs::remove_dir_all(&dir);
+    fs::create_dir_all(&dir).unwrap();
+    fs::write(dir.join("a.txt"), "abc\n").unwrap();
+
+    let err = build_registry()
+        .call(
+            "preview_replace",
+            json!({"pattern": "x*", "replacement": "y", "regex": true, "path": dir}),
+        )
+        .unwrap_err();
+
+    assert!(
+        format!("{err:#}").contains("matches the empty string"),
+        "unexpected error: {err:#}"
+    );
+}
+
+#[test]
+fn scanning_tools_agree_on_the_file_cap() {
+    let dir = std::env::temp_dir().join(format!("serena-preview-cap-{}", std::process::id()));
+    let _ = fs::remove_dir_all(&dir);
+    fs::create_dir_all(&dir).unwrap();
+    for name in ["a.txt", "b.txt", "c.txt"] {
+        fs::write(dir.join(name), "needle\n").unwrap();
+    }
+
+    let registry = build_registry();
+    let search = registry
+        .call(
+            "search_pattern",
+            json!({"pattern": "needle", "path": dir, "max_files": 2}),
+        )
+        .unwrap();
+    let preview = registry
+        .call(
+            "preview_replace",
+            json!({"pattern": "needle", "replacement": "pin", "path": dir, "max_files": 2}),
+        )
+        .unwrap();
+
+    for response in [&search, &preview] {
+        assert_eq!(response["files_scanned"], 2, "{response}");
+        assert_eq!(response["files_capped"], true, "{response}");
+    }
+}
@@ -0,0 +1,42 @@
+use std::fs;
+use std::sync::{Arc, Mutex};
+
+use serde_json::{Value, json};
+use serena_mcp::notifications::with_sink;
+use serena_mcp::tools::build_registry;
+
+fn progress_events(tool: &str, params: Value) -> Vec<Value> {
+    let events = Arc::new(Mutex::new(Vec::new()));
+    let sink = {
+        let events = events.clone();
+        Arc::new(move |method: &str, params: Value| {
+            if method == "progress" {
+                events.lock().unwrap().push(params);
+            }
+        })
+    };
+    with_sink(sink, 1, || build_registry().call(tool, params)).unwrap();
+    Arc::try_unwrap(events).unwrap().into_inner().unwrap()
+}
+
+#[test]
+fn file_counts_are_not_reported_as_matches() {
+    let dir = std::env::temp_dir().join(format!("serena-progress-{}", std::process::id()));
+    let _ = fs::remove_dir_all(&dir);
+    fs::create_dir_all(&dir).unwrap();
+    fs::write(dir.join("a.rs"), "fn one() {}\n").unwrap();
+    fs::write(dir.join("b.rs"), "fn two() {}\n").unwrap();
+
+    let duplicates = progress_events("find_duplicate_symbols", json!({"path": dir}));
+    assert_eq!(duplicates.len(), 2, "{duplicates:?}");
+    for event in &duplicates {
+        assert!(event.get("matches").is_none(), "{event}");
+    }
+
+    let search = progress_events("search_pattern", json!({"pattern": "fn", "path": dir}));
+    assert_eq!(search.len(), 2, "{search:?}");
+    assert!(
+        search.iter().all(|event| event["matches"].is_u64()),
+        "{search:?}"
+    );
+}
@@ -0,0 +1,58 @@
+use std::fs;
+use std::io::Cursor;
+
+use serde_json::json;
+use serena_mcp::rpc::serve;
+use serena_mcp::tools::build_registry;
+
+#[test]
+fn read_only_server_creates_no_state() {
+    let dir = std::env::temp_dir().join(format!("serena-read-only-{}", std::process::id()));
+    let _ = fs::remove_dir_all(&dir);
+    let project = dir.join("project");
+    fs::create_dir_all(project.join("src")).unwrap();
+    fs::write(project.join("src/main.rs"), "fn main() {}\n").unwrap();
+    let state = dir.join("state");
+    // The only test in this binary, so no other thread reads the variable.
+    unsafe { std::env::set_var("SERENA_STATE_DIR", &state) };
+
+    let mut registry = build_registry();
+    registry.set_read_only(true);
+    for (tool, args) in [
+        ("onboarding_tool", json!({"project_root": project})),
+        (
+            "prepare_for_new_conversation",
+            json!({"project_root": project}),
+        ),
+        (
+            "check_onboarding_performed",
+            json!({"project_root": project}),
+        ),
+        ("list_memories", json!({})),
+        ("recent_edits", json!({})),
+    ] {
+        registry
+            .call(tool, args)
+            .unwrap_or_else(|err| panic!("{tool} failed: {err:#}"));
+    }
+
+    let input = ["status", "get_config"]
+        .iter()
+        .enumerate()
+        .map(|(id, method)| {
+            format!(
+                "{}\n",
+                json!({"jsonrpc": "2.0", "id": id, "method": method})
+            )
+        })
+        .collect::<String>();
+    let mut output = Vec::new();
+    serve(&registry, Cursor::new(input), &mut output, 1024 * 1024).unwrap();
+    assert_eq!(String::from_utf8_lossy(&output).lines().count(), 2);
+
+    assert!(
+        !state.exists(),
+        "read-only calls created {}",
+        state.display()
+    );
+}
@@ -0,0 +1,45 @@
+use serde_json::{Value, json};
+use serena_mcp::tool::{Tool, ToolRegistry};
+
+#[test]
+fn shrunk_non_object_responses_are_wrapped_and_marked() {
+    let mut registry = ToolRegistry::new();
+    registry.register(Tool::new(
+        "long_text",
+        "Return a long string",
+        json!({"type": "object", "properties": {}, "additionalProperties": false}),
+        Box::new(|_| Ok(json!("x".repeat(4096)))),
+    ));
+    registry.set_max_response_bytes(Some(512));
+
+    let response = registry.call("long_text", json!({})).unwrap();
+
+    assert!(
+        serde_json::to_vec(&response).unwrap().len() <= 512,
+        "{response}"
+    );
+    assert_eq!(response["response_truncated"], true);
+    assert_eq!(response["original_bytes"], 4098);
+    assert!(
+        response["result"]
+            .as_str()
+            .is_some_and(|text| text.ends_with('…'))
+    );
+}
+
+#[test]
+fn small_non_object_responses_are_untouched() {
+    let mut registry = ToolRegistry::new();
+    registry.register(Tool::new(
+        "short_text",
+        "Return a short string",
+        json!({"type": "object", "properties": {}, "additionalProperties": false}),
+        Box::new(|_| Ok(json!("ok"))),
+    ));
+    registry.set_max_response_bytes(Some(512));
+
+    assert_eq!(
+        registry.call("short_text", json!({})).unwrap(),
+        Value::from("ok")
+    );
+}
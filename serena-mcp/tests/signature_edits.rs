@@ -0,0 +1,43 @@
+mod common;
+
+use std::fs;
+use std::path::PathBuf;
+
+use serde_json::{Value, json};
+use serena_mcp::tools::build_registry;
+
+use common::scratch_file;
+
+fn edit(path: &PathBuf, symbol: &str, new_signature: &str) -> anyhow::Result<Value> {
+    build_registry().call(
+        "edit_symbol_signature",
+        json!({"path": path, "symbol": symbol, "new_signature": new_signature}),
+    )
+}
+
+#[test]
+fn single_line_body_survives_a_signature_ending_in_a_brace() {
+    let path = scratch_file("inline", "lib.rs", "fn f() -> u32 { 1 }\n");
+
+    edit(&path, "f", "pub fn f() -> u32 {").unwrap();
+
+    assert_eq!(
+        fs::read_to_string(&path).unwrap(),
+        "pub fn f() -> u32 { 1 }\n"
+    );
+}
+
+#[test]
+fn signature_without_a_known_keyword_is_refused() {
+    let source =
+        "public class Shop {\n    public int total(int a) {\n        return a;\n    }\n}\n";
+    let path = scratch_file("keyword", "Shop.java", source);
+
+    let err = edit(&path, "total", "public class total(int a, int b) {").unwrap_err();
+
+    assert!(
+        format!("{err:#}").contains("No declaration keyword"),
+        "unexpected error: {err:#}"
+    );
+    assert_eq!(fs::read_to_string(&path).unwrap(), source);
+}
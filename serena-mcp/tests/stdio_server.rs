@@ -0,0 +1,133 @@
+use std::io::Cursor;
+use std::time::Duration;
+
+use serde_json::Value;
+use serena_mcp::call_limit::CallLimiter;
+use serena_mcp::rpc::serve;
+use serena_mcp::tools::build_registry;
+
+fn responses(output: &[u8]) -> Vec<Value> {
+    String::from_utf8_lossy(output)
+        .lines()
+        .map(|line| serde_json::from_str(line).expect("response is JSON"))
+        .collect()
+}
+
+#[test]
+fn oversized_line_is_rejected_and_server_keeps_serving() {
+    let registry = build_registry();
+    let limit = 1024;
+    let oversized = format!(
+        r#"{{"jsonrpc":"2.0","id":7,"method":"ping","params":{{"pad":"{}"}}}}"#,
+        "x".repeat(limit * 64)
+    );
+    let input = format!(
+        "{oversized}\n{}\n",
+        r#"{"jsonrpc":"2.0","id":8,"method":"ping"}"#
+    );
+
+    let mut output = Vec::new();
+    serve(&registry, Cursor::new(input), &mut output, limit).unwrap();
+
+    let responses = responses(&output);
+    assert_eq!(responses.len(), 2);
+    assert_eq!(responses[0]["error"]["code"], -32700);
+    assert_eq!(responses[0]["id"], 7);
+    assert_eq!(responses[1]["id"], 8);
+    assert_eq!(responses[1]["result"]["pong"], true);
+}
+
+#[test]
+fn oversized_final_line_without_newline_is_rejected() {
+    let registry = build_registry();
+    let input = format!(
+        "{}\n{}",
+        r#"{"jsonrpc":"2.0","id":1,"method":"ping"}"#,
+        "y".repeat(4096)
+    );
+
+    let mut output = Vec::new();
+    serve(&registry, Cursor::new(input), &mut output, 256).unwrap();
+
+    let responses = responses(&output);
+    assert_eq!(responses.len(), 2);
+    assert_eq!(responses[0]["result"]["pong"], true);
+    assert_eq!(responses[1]["error"]["code"], -32700);
+    assert!(responses[1]["id"].is_null());
+}
+
+#[test]
+fn streamed_search_sends_each_match_before_the_summary() {
+    let dir = std::env::temp_dir().join(format!("serena-stream-{}", std::process::id()));
+    std::fs::create_dir_all(&dir).unwrap();
+    std::fs::write(dir.join("a.txt"), "needle\nhay\nneedle\n").unwrap();
+    let request = serde_json::json!({
+        "jsonrpc": "2.0",
+        "id": 3,
+        "method": "tools.call",
+        "params": {
+            "tool": "search_pattern",
+            "arguments": {"pattern": "needle", "path": dir, "stream": true},
+        },
+    });
+
+    let registry = build_registry();
+    let mut output = Vec::new();
+    serve(
+        &registry,
+        Cursor::new(format!("{request}\n")),
+        &mut output,
+        1024 * 1024,
+    )
+    .unwrap();
+
+    let lines = responses(&output);
+    assert_eq!(lines.len(), 3, "{lines:?}");
+    for (index, line) in lines[..2].iter().enumerate() {
+        assert_eq!(line["method"], "partial_result");
+        assert_eq!(line["params"]["request_id"], 3);
+        assert_eq!(line["params"]["index"], index);
+        assert_eq!(line["params"]["item"]["line"], index * 2 + 1);
+    }
+    let summary = &lines[2]["result"]["result"];
+    assert_eq!(lines[2]["id"], 3);
+    assert_eq!(summary["streamed"], 2);
+    assert_eq!(summary["count"], 2);
+    assert!(summary.get("matches").is_none());
+}
+
+#[test]
+fn call_is_rejected_while_every_slot_is_taken() {
+    let mut registry = build_registry();
+    registry.set_call_limit(Some(CallLimiter::new(1, Some(Duration::ZERO))));
+    let input = concat!(
+        r#"{"jsonrpc":"2.0","id":1,"method":"tools.call","params":{"tool":"list_symbol_kinds","arguments":{}}}"#,
+        "\n",
+        r#"{"jsonrpc":"2.0","id":2,"method":"status"}"#,
+        "\n",
+    );
+
+    // `serve` handles one request at a time, so the only way to fill the
+    // slot is to hold it from outside.
+    let permit = registry.call_limit().unwrap().acquire().unwrap();
+    let mut output = Vec::new();
+    serve(&registry, Cursor::new(input), &mut output, 1024).unwrap();
+    drop(permit);
+
+    let busy = responses(&output);
+    assert_eq!(busy[0]["error"]["code"], -32000, "{}", busy[0]);
+    assert_eq!(busy[0]["error"]["message"], "Server busy");
+    assert!(
+        busy[0]["error"]["data"]["details"]
+            .as_str()
+            .unwrap()
+            .contains("all 1 call slots stayed in use"),
+        "{}",
+        busy[0]
+    );
+    assert_eq!(busy[1]["result"]["call_limit"]["rejected"], 1);
+
+    let mut output = Vec::new();
+    serve(&registry, Cursor::new(input), &mut output, 1024).unwrap();
+    assert!(responses(&output)[0].get("error").is_none());
+}
@@ -0,0 +1,53 @@
+use std::fs;
+
+use serde_json::json;
+use serena_mcp::tools::build_registry;
+
+// One test per binary: the state directory is process-wide.
+#[test]
+fn index_queries_create_nothing_and_capped_builds_remove_nothing() {
+    let dir = std::env::temp_dir().join(format!("serena-index-{}", std::process::id()));
+    let _ = fs::remove_dir_all(&dir);
+    let project = dir.join("project");
+    let state = dir.join("state");
+    fs::create_dir_all(&project).unwrap();
+    fs::write(project.join("a.rs"), "fn alpha() {}\n").unwrap();
+    fs::write(project.join("b.rs"), "fn beta() {}\n").unwrap();
+    unsafe { std::env::set_var("SERENA_STATE_DIR", &state) };
+
+    let registry = build_registry();
+    let err = registry
+        .call(
+            "query_symbol_index",
+            json!({"name": "alpha", "path": project}),
+        )
+        .unwrap_err();
+    assert!(format!("{err:#}").contains("No symbol index"), "{err:#}");
+    assert!(!state.exists(), "a query created {}", state.display());
+
+    let full = registry
+        .call("build_symbol_index", json!({"path": project}))
+        .unwrap();
+    assert_eq!(full["files_indexed"], 2, "{full}");
+
+    let capped = registry
+        .call(
+            "build_symbol_index",
+            json!({"path": project, "incremental": true, "max_files": 1}),
+        )
+        .unwrap();
+    assert_eq!(capped["files_capped"], true, "{capped}");
+    assert_eq!(capped["files_removed"], 0, "{capped}");
+
+    registry
+        .call("build_symbol_index", json!({"path": project}))
+        .unwrap();
+    fs::remove_file(project.join("b.rs")).unwrap();
+    let pruned = registry
+        .call(
+            "build_symbol_index",
+            json!({"path": project, "incremental": true}),
+        )
+        .unwrap();
+    assert_eq!(pruned["files_removed"], 1, "{pruned}");
+}
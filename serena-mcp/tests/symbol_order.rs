@@ -0,0 +1,33 @@
+use std::fs;
+
+use serde_json::json;
+use serena_mcp::tools::build_registry;
+
+#[test]
+fn ordering_applies_before_max_results() {
+    let dir = std::env::temp_dir().join(format!("serena-order-{}", std::process::id()));
+    let _ = fs::remove_dir_all(&dir);
+    fs::create_dir_all(&dir).unwrap();
+    fs::write(
+        dir.join("lib.rs"),
+        "fn zeta_item() {}\nfn mid_item() {}\nfn alpha_item() {}\n",
+    )
+    .unwrap();
+
+    let response = build_registry()
+        .call(
+            "find_symbol",
+            json!({"name": "_item", "path": dir, "order_by": "name", "max_results": 2}),
+        )
+        .unwrap();
+
+    let names: Vec<&str> = response["matches"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|found| found["name"].as_str().unwrap())
+        .collect();
+    assert_eq!(names, ["alpha_item", "mid_item"]);
+    assert_eq!(response["count"], 2);
+    assert_eq!(response["truncated"], true);
+}
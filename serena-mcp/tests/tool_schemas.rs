@@ -0,0 +1,157 @@
+use serde_json::{Value, json};
+use serena_mcp::tool::{POSITIONAL_ORDER_KEY, SCHEMA_BUNDLE_ID};
+use serena_mcp::tools::build_registry;
+
+fn examples(parameters: &Value) -> &[Value] {
+    parameters["examples"]
+        .as_array()
+        .map(Vec::as_slice)
+        .unwrap_or_default()
+}
+
+#[test]
+fn every_tool_schema_compiles() {
+    let registry = build_registry();
+    assert_eq!(registry.invalid_schemas(), Vec::<String>::new());
+    for descriptor in registry.descriptors() {
+        assert!(
+            registry.get(&descriptor.name).unwrap().schema_compiles(),
+            "{} schema does not compile",
+            descriptor.name
+        );
+    }
+}
+
+#[test]
+fn every_tool_accepts_its_own_examples() {
+    let registry = build_registry();
+    for descriptor in registry.descriptors() {
+        let examples = examples(&descriptor.parameters);
+        assert!(
+            !examples.is_empty(),
+            "{} declares no example arguments",
+            descriptor.name
+        );
+
+        let tool = registry.get(&descriptor.name).unwrap();
+        for example in examples {
+            if let Err(err) = tool.validate(example) {
+                panic!("{} rejects its example {example}: {err}", descriptor.name);
+            }
+        }
+    }
+}
+
+#[test]
+fn every_tool_rejects_known_bad_payloads() {
+    let registry = build_registry();
+    let bad_payloads = [
+        json!({"definitely_not_a_parameter": true}),
+        json!(["not", "an", "object"]),
+        json!({"path_style": "sideways"}),
+    ];
+
+    for descriptor in registry.descriptors() {
+        let tool = registry.get(&descriptor.name).unwrap();
+        for payload in &bad_payloads {
+            assert!(
+                tool.validate(payload).is_err(),
+                "{} accepted bad payload {payload}",
+                descriptor.name
+            );
+        }
+    }
+}
+
+#[test]
+fn schema_bundle_matches_enforced_schemas() {
+    let registry = build_registry();
+    let bundle = registry.schema_bundle();
+    assert_eq!(bundle["$id"], SCHEMA_BUNDLE_ID);
+
+    let defs = bundle["$defs"].as_object().expect("bundle has $defs");
+    assert_eq!(defs.len(), registry.len());
+
+    for descriptor in registry.descriptors() {
+        let schema = defs
+            .get(&descriptor.name)
+            .unwrap_or_else(|| panic!("bundle is missing {}", descriptor.name));
+        assert_eq!(
+            schema["$id"],
+            format!("{SCHEMA_BUNDLE_ID}/{}", descriptor.name)
+        );
+
+        let validator = jsonschema::validator_for(schema)
+            .unwrap_or_else(|err| panic!("{} schema does not compile: {err}", descriptor.name));
+        for example in examples(&descriptor.parameters) {
+            assert!(
+                validator.is_valid(example),
+                "bundled {} schema rejects {example}",
+                descriptor.name
+            );
+        }
+        assert!(!validator.is_valid(&json!({"definitely_not_a_parameter": true})));
+    }
+}
+
+#[test]
+fn tools_that_persist_state_are_not_cacheable() {
+    let registry = build_registry();
+    let cacheable = registry.cacheable_tools();
+    for tool in ["onboarding_tool", "prepare_for_new_conversation"] {
+        assert!(registry.get(tool).is_some(), "{tool} is not registered");
+        assert!(
+            !cacheable.iter().any(|name| name == tool),
+            "{tool} is cacheable"
+        );
+    }
+}
+
+#[test]
+fn positional_order_lists_every_declared_argument_once() {
+    let injected = ["cwd", "path_style", "idempotency_key"];
+    let registry = build_registry();
+    for descriptor in registry.descriptors() {
+        let mut declared = descriptor.parameters["properties"]
+            .as_object()
+            .map(|properties| {
+                properties
+                    .keys()
+                    .filter(|name| !injected.contains(&name.as_str()))
+                    .cloned()
+                    .collect::<Vec<_>>()
+            })
+            .unwrap_or_default();
+        let mut positional = descriptor.parameters[POSITIONAL_ORDER_KEY]
+            .as_array()
+            .map(|names| {
+                names
+                    .iter()
+                    .map(|name| name.as_str().unwrap().to_string())
+                    .collect::<Vec<_>>()
+            })
+            .unwrap_or_default();
+        declared.sort();
+        positional.sort();
+        assert_eq!(positional, declared, "{}", descriptor.name);
+    }
+}
+
+#[test]
+fn positional_arguments_skip_injected_arguments() {
+    let path = std::env::temp_dir().join(format!("serena-positional-{}.txt", std::process::id()));
+    std::fs::write(&path, "abcdef").unwrap();
+    let registry = build_registry();
+
+    let result = registry.call("read_file", json!([path, 3])).unwrap();
+    assert!(result["content"].as_str().unwrap().starts_with("abc"));
+    assert!(!result["content"].as_str().unwrap().contains("def"));
+
+    let err = registry
+        .call("read_file", json!([path, 3, false, "relative-to-root"]))
+        .unwrap_err();
+    assert!(
+        format!("{err:#}").contains("at most 3"),
+        "unexpected error: {err:#}"
+    );
+}
@@ -0,0 +1,94 @@
+mod common;
+
+use serde_json::{Value, json};
+
+use common::{call, scratch_file};
+
+/// Multibyte text before the matches: `ï` is 2 bytes, `☕` 3 and `🦀` 4, so
+/// byte and character columns differ. Symbol names stay ASCII because the
+/// declaration patterns only recognise ASCII identifiers, so the only
+/// multibyte text that can precede a declaration is a block comment.
+const PYTHON_SOURCE: &str = "# café ☕ 🦀
+class Café:
+    def metodo(self):
+        naïve = \"🦀☕\"; return café(naïve)
+
+
+def café(x):
+    return x
+";
+
+const RUST_SOURCE: &str = "// naïve 🦀
+struct Café;
+
+impl Café {
+    /* ☕ */ fn metodo(&self) -> &str { \"☕ naïve\" }
+}
+";
+
+/// `(line, column)` of every entry in `list`.
+fn positions(list: &Value) -> Vec<(u64, u64)> {
+    list.as_array()
+        .unwrap()
+        .iter()
+        .map(|entry| {
+            (
+                entry["line"].as_u64().unwrap(),
+                entry["column"].as_u64().unwrap(),
+            )
+        })
+        .collect()
+}
+
+#[test]
+fn search_and_reference_columns_count_characters() {
+    let path = scratch_file("refs", "refs.py", PYTHON_SOURCE);
+    let line = PYTHON_SOURCE.lines().nth(3).unwrap();
+    let byte_offset = line.find("café(").unwrap();
+    let expected = line[..byte_offset].chars().count() as u64 + 1;
+    assert_eq!(expected, 30);
+    assert_ne!(byte_offset as u64 + 1, expected);
+
+    let search = call(
+        "search_pattern",
+        json!({"pattern": "café(", "path": path, "context_lines": 0}),
+    );
+    assert_eq!(positions(&search["matches"]), vec![(4, 30), (7, 5)]);
+    assert_eq!(search["matches"][0]["match_end"], 35);
+
+    let references = call(
+        "find_referencing_symbols",
+        json!({"name": "café", "path": path, "kind_filter": "reference", "context_lines": 0}),
+    );
+    let on_line_four = positions(&references["matches"])
+        .into_iter()
+        .filter(|(line, _)| *line == 4)
+        .collect::<Vec<_>>();
+    assert_eq!(on_line_four, vec![(4, expected)]);
+}
+
+#[test]
+fn symbol_columns_match_search_columns() {
+    for (file, source, declaration, column) in [
+        ("symbols.py", PYTHON_SOURCE, "def metodo", 5),
+        ("symbols.rs", RUST_SOURCE, "fn metodo", 13),
+    ] {
+        let path = scratch_file(file, file, source);
+        let symbols = call(
+            "find_symbol",
+            json!({"name": "metodo", "path": path, "match_substring": false}),
+        );
+        let search = call(
+            "search_pattern",
+            json!({"pattern": declaration, "path": path, "context_lines": 0}),
+        );
+
+        assert_eq!(positions(&symbols["matches"]).len(), 1, "{file}: {symbols}");
+        assert_eq!(
+            positions(&symbols["matches"]),
+            positions(&search["matches"]),
+            "{file}"
+        );
+        assert_eq!(symbols["matches"][0]["column"], column, "{file}");
+    }
+}
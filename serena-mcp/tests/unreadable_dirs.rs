@@ -0,0 +1,61 @@
+#![cfg(unix)]
+
+use std::fs;
+use std::os::unix::fs::PermissionsExt;
+
+use serde_json::json;
+use serena_mcp::tools::build_registry;
+
+#[test]
+fn listings_skip_unreadable_subdirectories() {
+    let dir = std::env::temp_dir().join(format!("serena-unreadable-{}", std::process::id()));
+    let _ = fs::remove_dir_all(&dir);
+    fs::create_dir_all(dir.join("locked")).unwrap();
+    fs::create_dir_all(dir.join("open")).unwrap();
+    fs::write(dir.join("open/a.txt"), "a\n").unwrap();
+    fs::set_permissions(dir.join("locked"), fs::Permissions::from_mode(0o000)).unwrap();
+    // Permissions do not stop a privileged user; nothing to check then.
+    if fs::read_dir(dir.join("locked")).is_ok() {
+        return;
+    }
+
+    let registry = build_registry();
+    let listing = registry
+        .call("list_dir", json!({"path": dir, "depth": 2}))
+        .unwrap();
+    let tree = registry.call("render_tree", json!({"path": dir})).unwrap();
+
+    fs::set_permissions(dir.join("locked"), fs::Permissions::from_mode(0o755)).unwrap();
+    for response in [&listing, &tree] {
+        let skipped = response["skipped"].as_array().unwrap();
+        assert_eq!(skipped.len(), 1, "{response}");
+        assert!(
+            skipped[0]["path"].as_str().unwrap().ends_with("locked"),
+            "{response}"
+        );
+    }
+    assert_eq!(listing["entries"][1]["children"][0]["name"], "a.txt");
+    assert!(tree["tree"].as_str().unwrap().contains("a.txt"), "{tree}");
+}
+
+#[test]
+fn file_stats_skips_unreadable_files() {
+    let dir = std::env::temp_dir().join(format!("serena-unreadable-stats-{}", std::process::id()));
+    let _ = fs::remove_dir_all(&dir);
+    fs::create_dir_all(&dir).unwrap();
+    fs::write(dir.join("open.rs"), "fn open() {}\n").unwrap();
+    fs::write(dir.join("locked.rs"), "fn locked() {}\n").unwrap();
+    fs::set_permissions(dir.join("locked.rs"), fs::Permissions::from_mode(0o000)).unwrap();
+    // Permissions do not stop a privileged user; nothing to check then.
+    if fs::read(dir.join("locked.rs")).is_ok() {
+        return;
+    }
+
+    let response = build_registry()
+        .call("file_stats", json!({"path": dir}))
+        .unwrap();
+
+    fs::set_permissions(dir.join("locked.rs"), fs::Permissions::from_mode(0o644)).unwrap();
+    assert_eq!(response["totals"]["files"], 1, "{response}");
+    assert_eq!(response["skipped_files"], 1, "{response}");
+}